@@ -0,0 +1,21 @@
+//! Documents shared by the benchmarks.
+
+use jval::{json, Json};
+
+/// An array of `n` records, with strings, numbers and some nesting.
+pub fn records(n: usize) -> Json {
+    Json::Array(
+        (0..n)
+            .map(|i| {
+                json!({
+                    "id": i as f64,
+                    "name": format!("record {i}"),
+                    "score": i as f64 / 7.,
+                    "active": i % 3 == 0,
+                    "tags": ["alpha", "beta", "gamma \"quoted\""],
+                    "location": {"lat": 51.5074, "lon": -0.1278, "note": null},
+                })
+            })
+            .collect(),
+    )
+}
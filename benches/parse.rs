@@ -0,0 +1,69 @@
+//! Parsing and validating throughput on multi-megabyte documents, parsing
+//! both with the bulk scan for tokens and without it.
+//!
+//! To compare two revisions, save a baseline on the first and compare
+//! against it on the second:
+//!
+//! ```sh
+//! cargo bench --bench parse -- --save-baseline before
+//! cargo bench --bench parse -- --baseline before
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jval::{json, FormatOptions, Json, ParseOptions};
+
+mod common;
+
+use common::records;
+
+/// An array of `n` paragraphs of prose, where most of the input is inside
+/// strings.
+fn prose(n: usize) -> Json {
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do \
+                     eiusmod tempor incididunt ut labore et dolore magna aliqua. ";
+    Json::Array(
+        (0..n)
+            .map(|i| json!({"id": i as f64, "text": paragraph.repeat(4)}))
+            .collect(),
+    )
+}
+
+fn parse(c: &mut Criterion) {
+    let documents = [
+        ("records", records(20_000).to_string()),
+        (
+            "records, indented",
+            records(20_000)
+                .to_string_with(&FormatOptions::spaces(2))
+                .unwrap(),
+        ),
+        ("prose", prose(10_000).to_string()),
+    ];
+
+    let scalar = ParseOptions {
+        bulk_scan: false,
+        ..ParseOptions::default()
+    };
+
+    let mut group = c.benchmark_group("parse");
+    for (name, text) in &documents {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::new("parse", name), text, |b, text| {
+            b.iter(|| black_box(text.parse::<Json>().unwrap()));
+        });
+        group.bench_with_input(
+            BenchmarkId::new("parse, no bulk scan", name),
+            text,
+            |b, text| {
+                b.iter(|| black_box(Json::from_str_with(text, &scalar).unwrap()));
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("validate", name), text, |b, text| {
+            b.iter(|| jval::validate(black_box(text.as_bytes())).unwrap());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
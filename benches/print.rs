@@ -0,0 +1,57 @@
+//! Printing throughput on multi-megabyte documents.
+//!
+//! To compare two revisions, save a baseline on the first and compare
+//! against it on the second:
+//!
+//! ```sh
+//! cargo bench --bench print -- --save-baseline before
+//! cargo bench --bench print -- --baseline before
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jval::{json, FormatOptions, Json};
+
+mod common;
+
+use common::records;
+
+/// An array nested `depth` deep around a small object, to stress
+/// indentation.
+fn nested(depth: usize) -> Json {
+    (0..depth).fold(json!({"leaf": [1, 2, 3]}), |inner, _| {
+        Json::Array(vec![inner, json!("sibling")])
+    })
+}
+
+fn print(c: &mut Criterion) {
+    let documents = [("records", records(20_000)), ("nested", nested(2_000))];
+    let options = [
+        ("compact", FormatOptions::compact()),
+        ("spaces", FormatOptions::spaces(2)),
+        ("tabs", FormatOptions::tabs()),
+    ];
+
+    let mut group = c.benchmark_group("print");
+    for (name, document) in &documents {
+        let size = document.to_string().len();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("display", name), document, |b, doc| {
+            b.iter(|| black_box(doc.to_string()));
+        });
+        for (spacing, options) in &options {
+            let id = BenchmarkId::new(*spacing, name);
+            group.bench_with_input(id, document, |b, doc| {
+                let mut out = Vec::with_capacity(size * 2);
+                b.iter(|| {
+                    out.clear();
+                    doc.print(options, &mut out).unwrap();
+                    black_box(&out);
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, print);
+criterion_main!(benches);
@@ -1,9 +1,14 @@
+use std::env;
 use std::fs::File;
 use std::io::{self, Read};
 use std::ops::Range;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use jval::{ErrorKind, Json, Spacing};
+use jval::codegen::{self, CodegenError, Language};
+use jval::filter::{Filter, FilterError};
+use jval::path::{JsonPath, PathError};
+use jval::schema::{Schema, SchemaError};
+use jval::{Change, Colors, DiffOptions, ErrorKind, Errors, FormatOptions, Json, PointerError};
 use structopt::StructOpt;
 use termion::{
     color::{self, Bg, Fg},
@@ -14,6 +19,11 @@ use termion::{
 enum Error {
     IO(io::Error),
     Json(ErrorKind, Range<usize>),
+    Pointer(PointerError),
+    Path(PathError),
+    Filter(FilterError),
+    Schema(SchemaError),
+    Codegen(CodegenError),
 }
 
 impl From<io::Error> for Error {
@@ -22,13 +32,51 @@ impl From<io::Error> for Error {
     }
 }
 
+impl From<PointerError> for Error {
+    fn from(e: PointerError) -> Self {
+        Error::Pointer(e)
+    }
+}
+
+impl From<PathError> for Error {
+    fn from(e: PathError) -> Self {
+        Error::Path(e)
+    }
+}
+
+impl From<FilterError> for Error {
+    fn from(e: FilterError) -> Self {
+        Error::Filter(e)
+    }
+}
+
+impl From<SchemaError> for Error {
+    fn from(e: SchemaError) -> Self {
+        Error::Schema(e)
+    }
+}
+
+impl From<CodegenError> for Error {
+    fn from(e: CodegenError) -> Self {
+        Error::Codegen(e)
+    }
+}
+
 impl From<(ErrorKind, Range<usize>)> for Error {
     fn from((e, r): (ErrorKind, Range<usize>)) -> Self {
         Error::Json(e, r)
     }
 }
 
+/// How the arguments changed, for anyone used to the old ones.
+const AFTER_HELP: &str = "The positional arguments are a filter and then the file to read, as in \
+                          `cli '.items[] | .id' data.json`. A lone argument is the filter, so \
+                          read a file without one with `cli . data.json` or `cli -f data.json`. \
+                          The first positional argument used to be the JSON data itself, which \
+                          is now given with --json.";
+
 #[derive(StructOpt)]
+#[structopt(raw(after_help = "AFTER_HELP"))]
 struct Cli {
     /// Don't print the data to stdout
     #[structopt(short, long, group = "printing")]
@@ -43,72 +91,388 @@ struct Cli {
     /// Print indented with <n> spaces
     #[structopt(long, group = "printing", name = "number")]
     spaces: Option<usize>,
+    /// Keep arrays and objects that fit within <columns> on one line
+    #[structopt(long, name = "columns")]
+    width: Option<usize>,
+    /// Print arrays of similar objects one per line, with their members in aligned columns
+    #[structopt(long = "table-align")]
+    table_align: bool,
+    /// When to color the output, styled by $JQ_COLORS: auto, always or never
+    #[structopt(
+        long,
+        default_value = "auto",
+        raw(possible_values = r#"&["auto", "always", "never"]"#)
+    )]
+    color: String,
+    /// Only print the value at this JSON Pointer (e.g. /servers/0/host)
+    #[structopt(short, long)]
+    pointer: Option<String>,
+    /// Validate the input against this JSON Schema (draft 2020-12) file
+    #[structopt(long)]
+    schema: Option<PathBuf>,
     /// File to read JSON from
     #[structopt(short, long, name = "path", group = "input")]
     file: Option<PathBuf>,
-    /// JSON data to validate
-    #[structopt(name = "json_data", group = "input")]
+    /// JSON data to validate, instead of reading it from a file
+    #[structopt(long, name = "json_data", group = "input")]
     json: Option<String>,
+    /// jq-style filter to run on the input, e.g. '.items[] | .id'
+    #[structopt(name = "filter")]
+    filter: Option<String>,
+    /// File to read JSON from [default: stdin]
+    #[structopt(name = "file", group = "input")]
+    input: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() -> Result<(), Error> {
-    let cli = Cli::from_args();
+#[derive(StructOpt)]
+enum Command {
+    /// Print the nodes selected by an RFC 9535 JSONPath query, as an array
+    #[structopt(name = "query")]
+    Query {
+        /// The query to run, e.g. '$.store.book[*].title'
+        #[structopt(name = "query")]
+        query: String,
+        /// File to read JSON from [default: stdin]
+        #[structopt(name = "file")]
+        file: Option<PathBuf>,
+        /// Print the normalized path of each node, one per line, instead
+        #[structopt(long)]
+        paths: bool,
+    },
+    /// Layer JSON files in order, each after the first as an RFC 7386 merge patch, and print the result
+    #[structopt(name = "merge")]
+    Merge {
+        /// Files to merge; each after the first is applied as a merge patch to those before it
+        #[structopt(name = "files", required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Show the structural differences between two JSON files
+    #[structopt(name = "diff")]
+    Diff {
+        #[structopt(name = "old")]
+        old: PathBuf,
+        #[structopt(name = "new")]
+        new: PathBuf,
+        /// Compare arrays without regard to the order of their elements
+        #[structopt(long = "ignore-order")]
+        ignore_order: bool,
+        /// Treat numbers within <tolerance> of each other as equal
+        #[structopt(long, default_value = "0")]
+        tolerance: f64,
+        /// Skip object members with this name, at any depth
+        #[structopt(long = "ignore-key", name = "key", number_of_values = 1)]
+        ignore_keys: Vec<String>,
+    },
+    /// Infer a JSON Schema (draft 2020-12) that all the sample documents satisfy
+    #[structopt(name = "infer-schema")]
+    InferSchema {
+        /// Files of sample documents [default: stdin]
+        #[structopt(name = "files")]
+        files: Vec<PathBuf>,
+        /// Read one sample document per line (newline-delimited JSON)
+        #[structopt(long)]
+        ndjson: bool,
+    },
+    /// Generate Rust or TypeScript types from sample documents or a JSON Schema
+    #[structopt(name = "codegen")]
+    Codegen {
+        /// Files of sample documents, or the schema [default: stdin]
+        #[structopt(name = "files")]
+        files: Vec<PathBuf>,
+        /// Language to generate: rust or typescript
+        #[structopt(long, default_value = "rust")]
+        lang: Language,
+        /// Name of the root type
+        #[structopt(long, default_value = "Root")]
+        name: String,
+        /// Read the input as a JSON Schema instead of sample documents
+        #[structopt(long = "from-schema")]
+        from_schema: bool,
+        /// Read one sample document per line (newline-delimited JSON)
+        #[structopt(long)]
+        ndjson: bool,
+    },
+}
+
+impl Cli {
+    fn format_options(&self) -> FormatOptions {
+        let mut options = if self.compact {
+            FormatOptions::compact()
+        } else if let Some(n) = self.spaces {
+            FormatOptions::spaces(n)
+        } else {
+            FormatOptions::tabs()
+        };
+        if let Some(width) = self.width {
+            options = options.max_width(width);
+        }
+        if self.colored() {
+            options = options.colors(jq_colors());
+        }
+        options.table_align(self.table_align).trailing_newline(true)
+    }
+
+    fn colored(&self) -> bool {
+        match self.color.as_str() {
+            "always" => true,
+            "never" => false,
+            _ => {
+                termion::is_tty(&io::stdout())
+                    && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+fn jq_colors() -> Colors {
+    match env::var("JQ_COLORS") {
+        Ok(spec) => spec.parse().unwrap_or_else(|e| {
+            eprintln!("Failed to set $JQ_COLORS: {}", e);
+            Colors::default()
+        }),
+        Err(_) => Colors::default(),
+    }
+}
+
+/// Read a file, or stdin, without checking that it's UTF-8.
+fn read_bytes(file: Option<&PathBuf>) -> io::Result<Vec<u8>> {
+    let mut b = Vec::new();
+    if let Some(p) = file {
+        File::open(p)?.read_to_end(&mut b)?;
+    } else {
+        io::stdin().read_to_end(&mut b)?;
+    }
+    Ok(b)
+}
 
-    let json = if let Some(j) = cli.json {
-        j
-    } else if let Some(p) = cli.file {
-        let mut b = String::new();
+fn read_file_or_stdin(file: Option<&PathBuf>) -> io::Result<String> {
+    let mut b = String::new();
+    if let Some(p) = file {
         File::open(p)?.read_to_string(&mut b)?;
-        b
     } else {
-        let mut b = String::new();
         io::stdin().read_to_string(&mut b)?;
-        b
+    }
+    Ok(b)
+}
+
+/// Parse each file (or stdin) as one document, or as one per line with `ndjson`.
+fn read_samples(files: &[PathBuf], ndjson: bool) -> io::Result<Vec<Json>> {
+    let inputs = if files.is_empty() {
+        vec![read_file_or_stdin(None)?]
+    } else {
+        files
+            .iter()
+            .map(|f| read_file_or_stdin(Some(f)))
+            .collect::<io::Result<_>>()?
     };
 
-    let parsed = match json.parse::<Json>() {
-        Ok(data) => data,
-        Err(errvec) => {
-            eprintln!("Encountered {} error(s) while parsing JSON:", errvec.len());
-            for (kind, Range { start, end }) in errvec {
-                eprintln!("\n{:?} from position {} to {}.", kind, start, end);
-
-                let range_start = json[..start]
-                    .rmatch_indices('\n')
-                    .map(|(n, _)| n + 1)
-                    .nth(2)
-                    .unwrap_or_default();
-                let range_end = json[end..]
-                    .match_indices('\n')
-                    .map(|(n, _)| n + end)
-                    .nth(2)
-                    .unwrap_or(json.len());
-                eprintln!(
-                    "{}{red}{white}{}{reset}{}",
-                    &json[range_start..start],
-                    &json[start..end],
-                    &json[end..range_end].trim_end(),
-                    red = Bg(color::Red),
-                    white = Fg(color::LightWhite),
-                    reset = style::Reset
-                );
+    let mut samples = Vec::new();
+    for input in &inputs {
+        if ndjson {
+            let lines = input.lines().filter(|l| !l.trim().is_empty());
+            samples.extend(lines.map(parse_or_exit));
+        } else {
+            samples.push(parse_or_exit(input));
+        }
+    }
+    Ok(samples)
+}
+
+fn main() -> Result<(), Error> {
+    let cli = Cli::from_args();
+
+    match &cli.command {
+        Some(Command::Query { query, file, paths }) => {
+            let query = query.parse::<JsonPath>()?;
+            let json = read_file_or_stdin(file.as_ref())?;
+            let parsed = parse_or_exit(&json);
+            let nodes = query.query(&parsed);
+
+            if *paths {
+                for node in nodes {
+                    println!("{}", node.path);
+                }
+            } else {
+                let values = nodes.into_iter().map(|n| n.value.clone()).collect();
+                Json::Array(values).print(&cli.format_options(), &mut io::stdout())?;
             }
+
+            Ok(())
+        }
+        Some(Command::Merge { files }) => {
+            // The first file is the base, so the nulls in it are kept.
+            let (base, patches) = files.split_first().expect("at least one file is required");
+            let mut merged = parse_or_exit(&read_file_or_stdin(Some(base))?);
+            for file in patches {
+                let json = read_file_or_stdin(Some(file))?;
+                merged.merge_patch(&parse_or_exit(&json));
+            }
+
+            merged.print(&cli.format_options(), &mut io::stdout())?;
+            Ok(())
+        }
+        Some(Command::Diff {
+            old,
+            new,
+            ignore_order,
+            tolerance,
+            ignore_keys,
+        }) => {
+            let a = parse_or_exit(&read_file_or_stdin(Some(old))?);
+            let b = parse_or_exit(&read_file_or_stdin(Some(new))?);
+            let options = DiffOptions {
+                ignore_array_order: *ignore_order,
+                float_tolerance: *tolerance,
+                ignore_keys: ignore_keys.iter().cloned().collect(),
+            };
+
+            let changes = jval::diff_with(&a, &b, &options);
+            if changes.is_empty() {
+                return Ok(());
+            }
+
+            print_diff(&changes, old, new, cli.colored());
             std::process::exit(1);
         }
+        Some(Command::InferSchema { files, ndjson }) => {
+            let samples = read_samples(files, *ndjson)?;
+            jval::schema::infer(&samples).print(&cli.format_options(), &mut io::stdout())?;
+            Ok(())
+        }
+        Some(Command::Codegen {
+            files,
+            lang,
+            name,
+            from_schema,
+            ndjson,
+        }) => {
+            let samples = read_samples(files, *ndjson)?;
+            let code = if *from_schema {
+                let schema = samples.first().unwrap_or(&Json::Boolean(true));
+                codegen::from_schema(schema, name, *lang)?
+            } else {
+                codegen::from_samples(&samples, name, *lang)
+            };
+            print!("{}", code);
+            Ok(())
+        }
+        None => validate(&cli),
+    }
+}
+
+fn validate(cli: &Cli) -> Result<(), Error> {
+    let json = if let Some(j) = &cli.json {
+        j.clone().into_bytes()
+    } else {
+        read_bytes(cli.file.as_ref().or(cli.input.as_ref()))?
+    };
+
+    // Both check the bytes are UTF-8 themselves, and accept the same input.
+    if cli.quiet && cli.schema.is_none() && cli.pointer.is_none() && cli.filter.is_none() {
+        if let Err(errvec) = jval::validate(&json) {
+            exit_with_errors(&json, errvec);
+        }
+        return Ok(());
+    }
+
+    let parsed = match Json::from_slice(&json) {
+        Ok(parsed) => parsed,
+        Err(errvec) => exit_with_errors(&json, errvec),
+    };
+
+    if let Some(path) = &cli.schema {
+        if let Err(errors) = Schema::from_file(path)?.validate(&parsed) {
+            eprintln!("Encountered {} schema violation(s):", errors.len());
+            for e in errors {
+                eprintln!("  {}", e);
+            }
+            std::process::exit(1);
+        }
+    }
+
+    let selected = match &cli.pointer {
+        Some(p) => parsed.try_pointer(p)?,
+        None => &parsed,
+    };
+
+    let outputs = match &cli.filter {
+        Some(f) => f.parse::<Filter>()?.run(selected)?,
+        None => vec![selected.clone()],
     };
 
     if !cli.quiet {
-        let indent = if cli.compact {
-            Spacing::None
-        } else if let Some(n) = cli.spaces {
-            Spacing::Space(n)
+        for output in outputs {
+            output.print(&cli.format_options(), &mut io::stdout())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn print_diff(changes: &[Change], old: &Path, new: &Path, colored: bool) {
+    let paint = |color: &dyn color::Color, line: String| {
+        if colored {
+            println!("{}{}{}", Fg(color), line, style::Reset);
         } else {
-            Spacing::Tab
-        };
+            println!("{}", line);
+        }
+    };
 
-        parsed.print(&indent, &mut io::stdout())?;
-        println!();
+    paint(&color::Red, format!("--- {}", old.display()));
+    paint(&color::Green, format!("+++ {}", new.display()));
+    for change in changes {
+        paint(&color::Cyan, format!("@@ {} @@", change.path()));
+        match change {
+            Change::Removed { value, .. } => paint(&color::Red, format!("-{}", value)),
+            Change::Added { value, .. } => paint(&color::Green, format!("+{}", value)),
+            Change::Changed { from, to, .. } => {
+                paint(&color::Red, format!("-{}", from));
+                paint(&color::Green, format!("+{}", to));
+            }
+        }
     }
+}
 
-    Ok(())
+fn parse_or_exit(json: &str) -> Json {
+    match json.parse::<Json>() {
+        Ok(data) => data,
+        Err(errvec) => exit_with_errors(json.as_bytes(), errvec),
+    }
+}
+
+/// Print each error with the lines around it highlighted, then exit. The
+/// input needn't be UTF-8, and is shown lossily where it isn't.
+fn exit_with_errors(json: &[u8], errvec: Errors) -> ! {
+    eprintln!("Encountered {} error(s) while parsing JSON:", errvec.len());
+    for (kind, Range { start, end }) in errvec {
+        eprintln!("\n{:?} from position {} to {}.", kind, start, end);
+
+        let range_start = json[..start]
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|&(_, &b)| b == b'\n')
+            .map(|(n, _)| n + 1)
+            .nth(2)
+            .unwrap_or_default();
+        let range_end = json[end..]
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'\n')
+            .map(|(n, _)| n + end)
+            .nth(2)
+            .unwrap_or(json.len());
+        eprintln!(
+            "{}{red}{white}{}{reset}{}",
+            String::from_utf8_lossy(&json[range_start..start]),
+            String::from_utf8_lossy(&json[start..end]),
+            String::from_utf8_lossy(&json[end..range_end]).trim_end(),
+            red = Bg(color::Red),
+            white = Fg(color::LightWhite),
+            reset = style::Reset
+        );
+    }
+    std::process::exit(1);
 }
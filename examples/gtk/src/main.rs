@@ -8,7 +8,7 @@ use gtk::{
 use relm::{connect, connect_stream, Relm, Update, Widget};
 use relm_derive::Msg;
 
-use jval::{Json, Spacing};
+use jval::{FormatOptions, Json};
 
 struct Model {
     errs: Vec<Range<usize>>,
@@ -75,23 +75,25 @@ impl Update for Win {
 
             Msg::DoFmt => {
                 if let Some(json) = &self.model.json {
-                    let spacing = match self
+                    let options = match self
                         .widgets
                         .fmt_choose
                         .get_active_text()
                         .expect("spacing should never be unselected")
                         .as_ref()
                     {
-                        "none" => Spacing::None,
-                        "8 spaces" => Spacing::Space(8),
-                        "4 spaces" => Spacing::Space(4),
-                        "2 spaces" => Spacing::Space(2),
-                        "tabs" => Spacing::Tab,
+                        "none" => FormatOptions::compact(),
+                        "8 spaces" => FormatOptions::spaces(8),
+                        "4 spaces" => FormatOptions::spaces(4),
+                        "2 spaces" => FormatOptions::spaces(2),
+                        "tabs" => FormatOptions::tabs(),
+                        "2 spaces, 80 columns" => FormatOptions::spaces(2).max_width(80),
+                        "4 spaces, 80 columns" => FormatOptions::spaces(4).max_width(80),
                         _ => unreachable!(),
                     };
 
                     let mut buf = Vec::new();
-                    json.print(&spacing, &mut buf)
+                    json.print(&options, &mut buf)
                         .expect("was valid JSON, but could not print it");
                     self.widgets
                         .text_buf
@@ -160,6 +162,8 @@ impl Widget for Win {
         fmt_choose.append(Some("4"), "4 spaces");
         fmt_choose.append_text("8 spaces");
         fmt_choose.append_text("tabs");
+        fmt_choose.append_text("2 spaces, 80 columns");
+        fmt_choose.append_text("4 spaces, 80 columns");
         fmt_choose.set_active_id(Some("4"));
 
         let fmt_btn = Button::new_with_label("Format");
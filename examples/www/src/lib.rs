@@ -1,6 +1,6 @@
 use std::ops::Range;
 
-use jval::{Json, Spacing};
+use jval::{FormatOptions, Json};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -11,7 +11,7 @@ pub struct Error {
 
 #[wasm_bindgen]
 pub fn validate(json: &str) -> Option<Error> {
-    json.parse::<Json>()
+    jval::validate(json.as_bytes())
         .map_err(|mut errvec| {
             let (_, Range { start, end }) = errvec.remove(0);
             Error { start, end }
@@ -24,7 +24,7 @@ pub fn format_packed(json: &str) -> Option<String> {
     json.parse::<Json>()
         .map(|j: Json| {
             let mut s = Vec::new();
-            j.print(&Spacing::None, &mut s).unwrap();
+            j.print(&FormatOptions::compact(), &mut s).unwrap();
             String::from_utf8_lossy(&s).into()
         })
         .ok()
@@ -35,7 +35,7 @@ pub fn format_tabs(json: &str) -> Option<String> {
     json.parse::<Json>()
         .map(|j: Json| {
             let mut s = Vec::new();
-            j.print(&Spacing::Tab, &mut s).unwrap();
+            j.print(&FormatOptions::tabs(), &mut s).unwrap();
             String::from_utf8_lossy(&s).into()
         })
         .ok()
@@ -46,7 +46,19 @@ pub fn format_spaces(json: &str, spacing: usize) -> Option<String> {
     json.parse::<Json>()
         .map(|j: Json| {
             let mut s = Vec::new();
-            j.print(&Spacing::Space(spacing), &mut s).unwrap();
+            j.print(&FormatOptions::spaces(spacing), &mut s).unwrap();
+            String::from_utf8_lossy(&s).into()
+        })
+        .ok()
+}
+
+#[wasm_bindgen]
+pub fn format_width(json: &str, spacing: usize, width: usize) -> Option<String> {
+    json.parse::<Json>()
+        .map(|j: Json| {
+            let mut s = Vec::new();
+            j.print(&FormatOptions::spaces(spacing).max_width(width), &mut s)
+                .unwrap();
             String::from_utf8_lossy(&s).into()
         })
         .ok()
@@ -0,0 +1,272 @@
+//! `#[derive(ToJson, FromJson)]` for `jval`; see `jval::FromJson` for the
+//! `#[json(...)]` attributes.
+
+#![warn(clippy::pedantic)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    ext::IdentExt, parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Generics, LitStr,
+};
+
+#[proc_macro_derive(ToJson, attributes(json))]
+pub fn derive_to_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(FromJson, attributes(json))]
+pub fn derive_from_json(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_json(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Options {
+    rename: Option<String>,
+    default: bool,
+    skip: bool,
+    flatten: bool,
+}
+
+impl Options {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Options::default();
+        for attr in attrs.iter().filter(|a| a.path().is_ident("json")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("default") {
+                    options.default = true;
+                } else if meta.path.is_ident("skip") {
+                    options.skip = true;
+                } else if meta.path.is_ident("flatten") {
+                    options.flatten = true;
+                } else {
+                    return Err(meta.error("expected `rename`, `default`, `skip` or `flatten`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(options)
+    }
+}
+
+/// The generics of `input`, with `bound` on each type parameter.
+fn bounded(generics: &Generics, bound: &TokenStream2) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(#bound));
+    }
+    generics
+}
+
+/// The variants of an enum of unit variants, with their JSON names.
+fn unit_variants(data: &syn::DataEnum) -> syn::Result<Vec<(&syn::Ident, String)>> {
+    data.variants
+        .iter()
+        .map(|v| {
+            let options = Options::parse(&v.attrs)?;
+            if !matches!(v.fields, Fields::Unit) {
+                return Err(Error::new_spanned(v, "only unit variants are supported"));
+            }
+            if options.default || options.skip || options.flatten {
+                return Err(Error::new_spanned(v, "variants only support `rename`"));
+            }
+            Ok((
+                &v.ident,
+                options
+                    .rename
+                    .unwrap_or_else(|| v.ident.unraw().to_string()),
+            ))
+        })
+        .collect()
+}
+
+fn to_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut members = Vec::new();
+                for field in &fields.named {
+                    let options = Options::parse(&field.attrs)?;
+                    let ident = &field.ident;
+                    if options.skip {
+                        continue;
+                    }
+                    if options.flatten {
+                        members.push(quote! {
+                            if let ::jval::Json::Object(members) = ::jval::ToJson::to_json(&self.#ident) {
+                                object.extend(members);
+                            }
+                        });
+                    } else {
+                        let key = options.rename.unwrap_or_else(|| {
+                            ident
+                                .as_ref()
+                                .map(|i| i.unraw().to_string())
+                                .unwrap_or_default()
+                        });
+                        members.push(quote! {
+                            object.insert(#key.to_string(), ::jval::ToJson::to_json(&self.#ident));
+                        });
+                    }
+                }
+                quote! {
+                    let mut object = ::std::collections::BTreeMap::new();
+                    #(#members)*
+                    ::jval::Json::Object(object)
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                quote!(::jval::ToJson::to_json(&self.0))
+            }
+            Fields::Unnamed(fields) => {
+                let indices = (0..fields.unnamed.len()).map(syn::Index::from);
+                quote!(::jval::Json::Array(
+                    vec![#(::jval::ToJson::to_json(&self.#indices)),*]
+                ))
+            }
+            Fields::Unit => quote!(::jval::Json::Null),
+        },
+        Data::Enum(data) => {
+            let (idents, names): (Vec<_>, Vec<_>) = unit_variants(data)?.into_iter().unzip();
+            quote! {
+                match self {
+                    #(Self::#idents => ::jval::Json::String(#names.to_string()),)*
+                }
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(input, "unions are not supported")),
+    };
+
+    let name = &input.ident;
+    let generics = bounded(&input.generics, &quote!(::jval::ToJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::jval::ToJson for #name #ty_generics #where_clause {
+            fn to_json(&self) -> ::jval::Json {
+                #body
+            }
+        }
+    })
+}
+
+#[allow(clippy::too_many_lines)]
+fn from_json(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let body = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let mut inits = Vec::new();
+                let mut members = false;
+                for field in &fields.named {
+                    let options = Options::parse(&field.attrs)?;
+                    let ident = &field.ident;
+                    let value = if options.skip {
+                        quote!(::std::default::Default::default())
+                    } else if options.flatten {
+                        quote!(::jval::FromJson::from_json(json)?)
+                    } else {
+                        members = true;
+                        let key = options.rename.unwrap_or_else(|| {
+                            ident
+                                .as_ref()
+                                .map(|i| i.unraw().to_string())
+                                .unwrap_or_default()
+                        });
+                        let missing = if options.default {
+                            quote!(::std::default::Default::default())
+                        } else {
+                            quote! {
+                                ::jval::FromJson::from_json(&::jval::Json::Null)
+                                    .map_err(|_| ::jval::FromJsonError::missing_field(#key))?
+                            }
+                        };
+                        quote! {
+                            match object.get(#key) {
+                                ::std::option::Option::Some(value) => {
+                                    ::jval::FromJson::from_json(value).map_err(|e| e.at(#key))?
+                                }
+                                ::std::option::Option::None => #missing,
+                            }
+                        }
+                    };
+                    inits.push(quote!(#ident: #value));
+                }
+                let object = if members { quote!(object) } else { quote!(_) };
+                quote! {
+                    let #object = match json {
+                        ::jval::Json::Object(object) => object,
+                        _ => return ::std::result::Result::Err(
+                            ::jval::FromJsonError::expected("an object", json),
+                        ),
+                    };
+                    ::std::result::Result::Ok(Self { #(#inits),* })
+                }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                quote!(::std::result::Result::Ok(Self(
+                    ::jval::FromJson::from_json(json)?
+                )))
+            }
+            Fields::Unnamed(fields) => {
+                let len = fields.unnamed.len();
+                let expected = format!("an array of {len} elements");
+                let indices = 0..len;
+                let tokens = indices.clone().map(|i| i.to_string());
+                quote! {
+                    match json {
+                        ::jval::Json::Array(items) if items.len() == #len => {
+                            ::std::result::Result::Ok(Self(#(
+                                ::jval::FromJson::from_json(&items[#indices])
+                                    .map_err(|e| e.at(#tokens))?
+                            ),*))
+                        }
+                        _ => ::std::result::Result::Err(
+                            ::jval::FromJsonError::expected(#expected, json),
+                        ),
+                    }
+                }
+            }
+            Fields::Unit => quote!(::std::result::Result::Ok(Self)),
+        },
+        Data::Enum(data) => {
+            let (idents, names): (Vec<_>, Vec<_>) = unit_variants(data)?.into_iter().unzip();
+            let expected = names
+                .iter()
+                .map(|n| format!("\"{n}\""))
+                .collect::<Vec<_>>()
+                .join(", ");
+            quote! {
+                match json {
+                    #(::jval::Json::String(s) if s == #names => ::std::result::Result::Ok(Self::#idents),)*
+                    ::jval::Json::String(s) => ::std::result::Result::Err(::jval::FromJsonError::new(
+                        format!("unknown variant \"{}\", expected one of {}", s, #expected),
+                    )),
+                    _ => ::std::result::Result::Err(::jval::FromJsonError::expected("a string", json)),
+                }
+            }
+        }
+        Data::Union(_) => return Err(Error::new_spanned(input, "unions are not supported")),
+    };
+
+    let name = &input.ident;
+    let generics = bounded(&input.generics, &quote!(::jval::FromJson));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::jval::FromJson for #name #ty_generics #where_clause {
+            fn from_json(json: &::jval::Json) -> ::std::result::Result<Self, ::jval::FromJsonError> {
+                #body
+            }
+        }
+    })
+}
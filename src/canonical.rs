@@ -0,0 +1,55 @@
+//! The JSON Canonicalization Scheme of [RFC 8785].
+//!
+//! ```
+//! use jval::json;
+//!
+//! let doc = json!({"b": [1e21, 0.5, -0.0], "a": "\u{7f}\n"});
+//! assert_eq!(
+//!     doc.to_canonical_string().unwrap(),
+//!     "{\"a\":\"\u{7f}\\n\",\"b\":[1e+21,0.5,0]}"
+//! );
+//! ```
+//!
+//! [RFC 8785]: https://www.rfc-editor.org/rfc/rfc8785
+
+use std::fmt;
+
+use super::format::{find_non_finite, write_formatted};
+use super::{FormatOptions, Json, KeyOrder};
+
+#[cfg(test)]
+mod tests;
+
+/// A value that has no canonical form: a `NaN` or infinite number.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CanonicalError {
+    /// JSON Pointer to the offending number.
+    pub path: String,
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "non-finite number at \"{}\"", self.path)
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+impl Json {
+    /// Serialize per RFC 8785: no whitespace, object members sorted by the
+    /// UTF-16 code units of their names, numbers as ECMAScript prints them
+    /// and strings with only the escapes JSON requires.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the value contains a `NaN` or infinite number.
+    pub fn to_canonical_string(&self) -> Result<String, CanonicalError> {
+        if let Some(path) = find_non_finite(self) {
+            return Err(CanonicalError { path });
+        }
+        let options = FormatOptions::compact().key_order(KeyOrder::Utf16);
+        let mut out = String::new();
+        let _ = write_formatted(&mut out, self, &options);
+        Ok(out)
+    }
+}
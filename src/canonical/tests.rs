@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn member_order() {
+    // The sorting sample from RFC 8785, section 3.2.3.
+    let doc = json!({
+        "\u{20ac}": "Euro Sign",
+        "\r": "Carriage Return",
+        "\u{fb33}": "Hebrew Letter Dalet With Dagesh",
+        "1": "One",
+        "\u{1f600}": "Emoji: Grinning Face",
+        "\u{80}": "Control",
+        "\u{f6}": "Latin Small Letter O With Diaeresis"
+    });
+    let text = doc.to_canonical_string().unwrap();
+    let keys: Vec<_> = text
+        .split(",\"")
+        .map(|m| m.trim_start_matches("{\"").split('"').next().unwrap())
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            "\\r",
+            "1",
+            "\u{80}",
+            "\u{f6}",
+            "\u{20ac}",
+            "\u{1f600}",
+            "\u{fb33}"
+        ]
+    );
+}
+
+#[test]
+fn canonical_string() {
+    let doc = json!({
+        "numbers": [333_333_333.333_333_3, 1e30, 4.50, 2e-3, 1e-27],
+        "string": "\u{20ac}$\u{f}\nA'B\"\\\\\"/",
+        "literals": [null, true, false]
+    });
+    assert_eq!(
+        doc.to_canonical_string().unwrap(),
+        concat!(
+            r#"{"literals":[null,true,false],"#,
+            r#""numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"#,
+            r#""string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        )
+    );
+}
+
+#[test]
+fn non_finite() {
+    let doc = Json::Object(
+        vec![(
+            "a".to_string(),
+            Json::Array(vec![Json::Number(1.), Json::Number(f64::NAN)]),
+        )]
+        .into_iter()
+        .collect(),
+    );
+    assert_eq!(
+        doc.to_canonical_string().unwrap_err().to_string(),
+        "non-finite number at \"/a/1\""
+    );
+    assert!(Json::Number(f64::NEG_INFINITY)
+        .to_canonical_string()
+        .is_err());
+}
@@ -0,0 +1,931 @@
+//! Typed model generation, from sample documents or a JSON Schema.
+//!
+//! ```
+//! # use jval::{json, codegen::{self, Language}};
+//! let samples = [json!({"id": 1, "userName": "ann"}), json!({"id": 2})];
+//! let code = codegen::from_samples(&samples, "User", Language::TypeScript);
+//! assert_eq!(
+//!     code,
+//!     "export interface User {\n  id: number;\n  userName?: string;\n}\n"
+//! );
+//! ```
+//!
+//! Rust output derives `serde`'s `Serialize` and `Deserialize`, renaming
+//! fields and variants whose JSON names aren't valid identifiers in the usual
+//! case. Members that aren't `required` become `Option`s (or `?` members in
+//! TypeScript), `anyOf`/`oneOf` and multi-`type` schemas become untagged
+//! enums (or union types), and `allOf` merges the members of objects.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+use super::pointer;
+use super::schema;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    Rust,
+    TypeScript,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Ok(Language::Rust),
+            "typescript" | "ts" => Ok(Language::TypeScript),
+            _ => Err(format!(
+                "unknown language \"{s}\", expected rust or typescript"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodegenError {
+    /// JSON Pointer to the part of the schema that can't be turned into a type.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.message, self.path)
+    }
+}
+
+fn error(path: &str, message: impl Into<String>) -> CodegenError {
+    CodegenError {
+        path: path.to_string(),
+        message: message.into(),
+    }
+}
+
+/// Generate types for the documents `schema` accepts, calling the root
+/// type `name`.
+///
+/// # Errors
+///
+/// Fails on `$ref`s that don't point into `schema` itself, and on keywords
+/// whose values have the wrong type.
+pub fn from_schema(schema: &Json, name: &str, language: Language) -> Result<String, CodegenError> {
+    let mut builder = Builder {
+        root: schema,
+        defs: Vec::new(),
+        names: RESERVED.iter().map(ToString::to_string).collect(),
+        refs: BTreeMap::new(),
+        resolving: std::iter::once("#".to_string()).collect(),
+        stack: Vec::new(),
+    };
+
+    let root = builder.ty_owned(schema, name, "", false, Some("#"))?;
+    // The root is only a definition of its own when it isn't, say, an array.
+    let alias = if builder.refs.get("#") == Some(&root) {
+        None
+    } else {
+        Some((builder.unique(name), root))
+    };
+
+    // Writing to a `String` can't fail.
+    let mut out = String::new();
+    let renderer = Renderer {
+        defs: &builder.defs,
+    };
+    let _ = match language {
+        Language::Rust => renderer.rust(&mut out, alias.as_ref()),
+        Language::TypeScript => renderer.typescript(&mut out, alias.as_ref()),
+    };
+    Ok(out)
+}
+
+/// Generate types for documents like `samples`, calling the root type `name`.
+///
+/// The types are those of the schema [`schema::infer`] gives for the samples.
+pub fn from_samples<'a>(
+    samples: impl IntoIterator<Item = &'a Json>,
+    name: &str,
+    language: Language,
+) -> String {
+    match from_schema(&schema::infer(samples), name, language) {
+        Ok(code) => code,
+        Err(e) => unreachable!("inferred schemas are always supported: {}", e),
+    }
+}
+
+/// Names generated types must not shadow.
+const RESERVED: &[&str] = &[
+    "Array",
+    "BTreeMap",
+    "Box",
+    "Deserialize",
+    "Err",
+    "Json",
+    "None",
+    "Number",
+    "Object",
+    "Ok",
+    "Option",
+    "Record",
+    "Result",
+    "Self",
+    "Serialize",
+    "Some",
+    "String",
+    "Vec",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+enum Type {
+    Any,
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array(Box<Type>),
+    Map(Box<Type>),
+    Option(Box<Type>),
+    Named(usize),
+    /// A named type that contains itself, so needs indirection in Rust.
+    Boxed(usize),
+}
+
+impl Type {
+    fn nullable(self) -> Self {
+        match self {
+            Type::Option(_) => self,
+            ty => Type::Option(Box::new(ty)),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Def {
+    name: String,
+    doc: Option<String>,
+    kind: Kind,
+}
+
+#[derive(Debug)]
+enum Kind {
+    Struct(Vec<Field>),
+    /// The values of a string `enum`.
+    Enum(Vec<String>),
+    /// An untagged union of the labelled types.
+    Union(Vec<(String, Type)>),
+}
+
+#[derive(Debug)]
+struct Field {
+    key: String,
+    doc: Option<String>,
+    ty: Type,
+    required: bool,
+}
+
+fn description(schema: &Json) -> Option<String> {
+    schema["description"].as_str().map(str::to_string)
+}
+
+/// Whether `schema` only accepts `null`.
+fn is_null(schema: &Json) -> bool {
+    schema["type"] == Json::from("null") || schema.get("const") == Some(&Json::Null)
+}
+
+/// The types a schema without `type` implies through its other keywords.
+fn implied(o: &BTreeMap<String, Json>) -> Vec<&'static str> {
+    let mut types = Vec::new();
+    let values = match (o.get("enum"), o.get("const")) {
+        (Some(Json::Array(values)), _) => values.iter().collect(),
+        (_, Some(value)) => vec![value],
+        _ => Vec::new(),
+    };
+    for value in values {
+        let ty = match value {
+            Json::Null => "null",
+            Json::Boolean(_) => "boolean",
+            Json::Number(n) if n.fract() == 0. => "integer",
+            Json::Number(_) => "number",
+            Json::String(_) => "string",
+            Json::Array(_) => "array",
+            Json::Object(_) => "object",
+        };
+        if !types.contains(&ty) {
+            types.push(ty);
+        }
+    }
+
+    let keys = |keys: &[&str]| keys.iter().any(|k| o.contains_key(*k));
+    if types.is_empty() && keys(&["properties", "additionalProperties", "required", "allOf"]) {
+        types.push("object");
+    }
+    if types.is_empty() && keys(&["items", "prefixItems"]) {
+        types.push("array");
+    }
+    types
+}
+
+struct Builder<'a> {
+    root: &'a Json,
+    defs: Vec<Def>,
+    names: BTreeSet<String>,
+    /// The types of the `$ref`s seen so far.
+    refs: BTreeMap<String, Type>,
+    /// `$ref`s whose types are being built.
+    resolving: BTreeSet<String>,
+    /// The definitions being built, outermost first.
+    stack: Vec<usize>,
+}
+
+impl<'a> Builder<'a> {
+    fn unique(&mut self, hint: &str) -> String {
+        let mut base = pascal_case(hint);
+        if base.is_empty() {
+            base = "Value".to_string();
+        } else if base.starts_with(|c: char| c.is_ascii_digit()) {
+            base.insert(0, 'T');
+        }
+
+        dedupe(&mut self.names, &base, "")
+    }
+
+    /// Add a definition to fill in later, as the type of `owner` if that is
+    /// a `$ref`.
+    fn reserve(&mut self, hint: &str, doc: Option<String>, owner: Option<&str>) -> usize {
+        let index = self.defs.len();
+        let name = self.unique(hint);
+        self.defs.push(Def {
+            name,
+            doc,
+            kind: Kind::Struct(Vec::new()),
+        });
+        if let Some(r) = owner {
+            self.refs.insert(r.to_string(), Type::Named(index));
+        }
+        index
+    }
+
+    fn ty(
+        &mut self,
+        schema: &'a Json,
+        hint: &str,
+        path: &str,
+        indirect: bool,
+    ) -> Result<Type, CodegenError> {
+        self.ty_owned(schema, hint, path, indirect, None)
+    }
+
+    /// The type of `schema`, which is the target of the `$ref` `owner`, if any.
+    ///
+    /// `indirect` is set when the value is already behind a `Vec` or map,
+    /// so needs no `Box` if it turns out to be recursive.
+    fn ty_owned(
+        &mut self,
+        schema: &'a Json,
+        hint: &str,
+        path: &str,
+        indirect: bool,
+        owner: Option<&str>,
+    ) -> Result<Type, CodegenError> {
+        let o = match schema {
+            Json::Boolean(_) => return Ok(Type::Any),
+            Json::Object(o) => o,
+            _ => return Err(error(path, "expected a schema")),
+        };
+
+        if let Some(r) = o.get("$ref") {
+            let r = r
+                .as_str()
+                .ok_or_else(|| error(path, "$ref must be a string"))?;
+            return self.reference(r, &format!("{path}/$ref"), indirect);
+        }
+
+        let mut types = match o.get("type") {
+            None => implied(o),
+            Some(Json::String(t)) => vec![t.as_str()],
+            Some(Json::Array(ts)) => ts
+                .iter()
+                .map(|t| {
+                    t.as_str()
+                        .ok_or_else(|| error(&format!("{path}/type"), "expected a string"))
+                })
+                .collect::<Result<_, _>>()?,
+            Some(_) => {
+                return Err(error(
+                    &format!("{path}/type"),
+                    "expected a string or an array",
+                ))
+            }
+        };
+
+        let nullable = types.len() > 1 && types.contains(&"null");
+        if nullable {
+            types.retain(|t| *t != "null");
+        }
+        if types.contains(&"number") {
+            types.retain(|t| *t != "integer");
+        }
+
+        let members = match (o.get("anyOf"), o.get("oneOf")) {
+            (Some(m), _) => Some(("anyOf", m)),
+            (_, Some(m)) => Some(("oneOf", m)),
+            _ => None,
+        };
+        let ty = match (types.as_slice(), members) {
+            ([], Some((keyword, members))) => {
+                let path = format!("{path}/{keyword}");
+                let members = members
+                    .as_array()
+                    .ok_or_else(|| error(&path, "expected an array"))?;
+                let mut paths = Vec::new();
+                let mut schemas = Vec::new();
+                for (i, member) in members.iter().enumerate() {
+                    if !is_null(member) {
+                        paths.push(format!("{path}/{i}"));
+                        schemas.push(member);
+                    }
+                }
+                let ty = if let [member] = schemas.as_slice() {
+                    self.ty_owned(member, hint, &paths[0], indirect, owner)?
+                } else {
+                    let index = self.begin_union(schema, hint, owner);
+                    let mut variants = Vec::new();
+                    for (member, path) in schemas.iter().zip(&paths) {
+                        variants.push(self.ty(member, hint, path, false)?);
+                    }
+                    self.finish_union(index, variants)
+                };
+                if schemas.len() < members.len() {
+                    ty.nullable()
+                } else {
+                    ty
+                }
+            }
+            ([], None) => Type::Any,
+            ([t], _) => self.single(t, o, hint, path, indirect, owner)?,
+            (ts, _) => {
+                let ts = ts.to_vec();
+                let index = self.begin_union(schema, hint, owner);
+                let mut variants = Vec::new();
+                for t in ts {
+                    let hint = format!("{hint} {t}");
+                    variants.push(self.single(t, o, &hint, path, false, None)?);
+                }
+                self.finish_union(index, variants)
+            }
+        };
+
+        Ok(if nullable { ty.nullable() } else { ty })
+    }
+
+    fn begin_union(&mut self, schema: &Json, hint: &str, owner: Option<&str>) -> usize {
+        let index = self.reserve(hint, description(schema), owner);
+        self.stack.push(index);
+        index
+    }
+
+    fn finish_union(&mut self, index: usize, variants: Vec<Type>) -> Type {
+        self.stack.pop();
+        let mut labels = BTreeSet::new();
+        let variants = variants
+            .into_iter()
+            .map(|ty| (dedupe(&mut labels, &self.label(&ty), ""), ty))
+            .collect();
+        self.defs[index].kind = Kind::Union(variants);
+        Type::Named(index)
+    }
+
+    fn label(&self, ty: &Type) -> String {
+        match ty {
+            Type::Any => "Any",
+            Type::Null => "Null",
+            Type::Boolean => "Boolean",
+            Type::Integer => "Integer",
+            Type::Number => "Number",
+            Type::String => "String",
+            Type::Array(_) => "Array",
+            Type::Map(_) => "Map",
+            Type::Option(ty) => return self.label(ty),
+            Type::Named(i) | Type::Boxed(i) => &self.defs[*i].name,
+        }
+        .to_string()
+    }
+
+    /// The type of `o` when it is restricted to the single JSON type `t`.
+    fn single(
+        &mut self,
+        t: &str,
+        o: &'a BTreeMap<String, Json>,
+        hint: &str,
+        path: &str,
+        indirect: bool,
+        owner: Option<&str>,
+    ) -> Result<Type, CodegenError> {
+        let doc = o
+            .get("description")
+            .and_then(Json::as_str)
+            .map(str::to_string);
+
+        Ok(match t {
+            "null" => Type::Null,
+            "boolean" => Type::Boolean,
+            "integer" => Type::Integer,
+            "number" => Type::Number,
+            "string" => {
+                let values: Vec<_> = match (o.get("enum"), o.get("const")) {
+                    (Some(Json::Array(values)), _) => {
+                        values.iter().filter_map(Json::as_str).collect()
+                    }
+                    (_, Some(Json::String(value))) => vec![value.as_str()],
+                    _ => Vec::new(),
+                };
+                if values.is_empty() {
+                    Type::String
+                } else {
+                    let index = self.reserve(hint, doc, owner);
+                    let values = values.into_iter().map(str::to_string).collect();
+                    self.defs[index].kind = Kind::Enum(values);
+                    Type::Named(index)
+                }
+            }
+            "array" => match o.get("items") {
+                Some(items) => {
+                    let item = self.ty(items, &singular(hint), &format!("{path}/items"), true)?;
+                    Type::Array(Box::new(item))
+                }
+                None => Type::Array(Box::new(Type::Any)),
+            },
+            "object" => {
+                let has_properties = o
+                    .get("properties")
+                    .and_then(Json::as_object)
+                    .is_some_and(|p| !p.is_empty());
+                match o.get("additionalProperties") {
+                    _ if has_properties || o.contains_key("allOf") => {
+                        self.structure(o, hint, path, doc, owner)?
+                    }
+                    Some(Json::Boolean(false)) => self.structure(o, hint, path, doc, owner)?,
+                    Some(values) => {
+                        let path = format!("{path}/additionalProperties");
+                        let value = self.ty(values, &format!("{hint} value"), &path, indirect)?;
+                        Type::Map(Box::new(value))
+                    }
+                    None => Type::Map(Box::new(Type::Any)),
+                }
+            }
+            _ => {
+                return Err(error(
+                    &format!("{path}/type"),
+                    format!("unknown type \"{t}\""),
+                ))
+            }
+        })
+    }
+
+    fn structure(
+        &mut self,
+        o: &'a BTreeMap<String, Json>,
+        hint: &str,
+        path: &str,
+        doc: Option<String>,
+        owner: Option<&str>,
+    ) -> Result<Type, CodegenError> {
+        let index = self.reserve(hint, doc, owner);
+        self.stack.push(index);
+
+        let mut properties = Vec::new();
+        let mut required = BTreeSet::new();
+        self.collect(o, path, &mut properties, &mut required)?;
+
+        let mut fields = Vec::new();
+        for (key, schema, path) in properties {
+            fields.push(Field {
+                doc: description(schema),
+                ty: self.ty(schema, &key, &path, false)?,
+                required: required.contains(&key),
+                key,
+            });
+        }
+
+        self.stack.pop();
+        self.defs[index].kind = Kind::Struct(fields);
+        Ok(Type::Named(index))
+    }
+
+    /// Gather the members of an object schema and the objects it is `allOf`.
+    fn collect(
+        &self,
+        o: &'a BTreeMap<String, Json>,
+        path: &str,
+        properties: &mut Vec<(String, &'a Json, String)>,
+        required: &mut BTreeSet<String>,
+    ) -> Result<(), CodegenError> {
+        if let Some(all) = o.get("allOf") {
+            let all = all
+                .as_array()
+                .ok_or_else(|| error(&format!("{path}/allOf"), "expected an array"))?;
+            for (i, member) in all.iter().enumerate() {
+                let mut path = format!("{path}/allOf/{i}");
+                let mut member = member;
+                if let Some(r) = member["$ref"].as_str() {
+                    member = self.lookup(r, &format!("{path}/$ref"))?;
+                    path = r[1..].to_string();
+                }
+                if let Json::Object(m) = member {
+                    self.collect(m, &path, properties, required)?;
+                }
+            }
+        }
+        if let Some(p) = o.get("properties") {
+            let p = p
+                .as_object()
+                .ok_or_else(|| error(&format!("{path}/properties"), "expected an object"))?;
+            for (key, schema) in p {
+                if properties.iter().all(|(k, _, _)| k != key) {
+                    let path = format!("{path}/properties/{}", pointer::escape(key));
+                    properties.push((key.clone(), schema, path));
+                }
+            }
+        }
+        if let Some(r) = o.get("required") {
+            let r = r
+                .as_array()
+                .ok_or_else(|| error(&format!("{path}/required"), "expected an array"))?;
+            required.extend(r.iter().filter_map(Json::as_str).map(str::to_string));
+        }
+        Ok(())
+    }
+
+    fn lookup(&self, r: &str, path: &str) -> Result<&'a Json, CodegenError> {
+        let Some(fragment) = r.strip_prefix('#') else {
+            return Err(error(
+                path,
+                format!("cannot resolve \"{r}\", only references within the schema are supported"),
+            ));
+        };
+        self.root
+            .pointer(fragment)
+            .ok_or_else(|| error(path, format!("\"{r}\" does not point into the schema")))
+    }
+
+    fn reference(&mut self, r: &str, path: &str, indirect: bool) -> Result<Type, CodegenError> {
+        let boxed = |ty: &Type, stack: &[usize]| match ty {
+            Type::Named(i) if !indirect && stack.contains(i) => Type::Boxed(*i),
+            Type::Option(inner) => match **inner {
+                Type::Named(i) if !indirect && stack.contains(&i) => {
+                    Type::Option(Box::new(Type::Boxed(i)))
+                }
+                _ => ty.clone(),
+            },
+            _ => ty.clone(),
+        };
+
+        if let Some(ty) = self.refs.get(r) {
+            return Ok(boxed(ty, &self.stack));
+        }
+        if !self.resolving.insert(r.to_string()) {
+            // A cycle through unnamed types only, like an array of itself.
+            return Ok(Type::Any);
+        }
+
+        let target = self.lookup(r, path)?;
+        let hint = r.rsplit('/').next().unwrap_or_default();
+        let ty = self.ty_owned(target, hint, &r[1..], indirect, Some(r))?;
+        self.resolving.remove(r);
+        self.refs.insert(r.to_string(), ty.clone());
+        Ok(ty)
+    }
+}
+
+/// Split an identifier-ish string into its words, at punctuation and at
+/// changes of case (`userID` is `user`, `ID`).
+fn words(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        let prev = i.checked_sub(1).map(|j| chars[j]);
+        let next = chars.get(i + 1);
+        let boundary = c.is_uppercase()
+            && prev.is_some_and(|p| {
+                p.is_lowercase()
+                    || p.is_numeric()
+                    || (p.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+            });
+        if boundary && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.push(c);
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn pascal_case(s: &str) -> String {
+    words(s)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        })
+        .collect()
+}
+
+fn snake_case(s: &str) -> String {
+    let words: Vec<_> = words(s).iter().map(|w| w.to_lowercase()).collect();
+    words.join("_")
+}
+
+/// The name for an element of an array called `s`.
+fn singular(s: &str) -> String {
+    if let Some(stem) = s.strip_suffix("ies").filter(|stem| !stem.is_empty()) {
+        format!("{stem}y")
+    } else if s.ends_with("ss") || !s.ends_with('s') {
+        format!("{s} item")
+    } else {
+        s[..s.len() - 1].to_string()
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// A Rust field name for the JSON member `key`.
+fn field_name(key: &str) -> String {
+    let name = snake_case(key);
+    if name.is_empty() {
+        "field".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{name}")
+    } else if ["crate", "self", "super"].contains(&name.as_str()) {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}")
+    } else {
+        name
+    }
+}
+
+/// A Rust variant name for the string `value`.
+fn variant_name(value: &str) -> String {
+    let name = pascal_case(value);
+    if name.is_empty() {
+        "Empty".to_string()
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{name}")
+    } else if name == "Self" {
+        "SelfValue".to_string()
+    } else {
+        name
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// A string literal that both Rust and TypeScript accept.
+fn quote(s: &str) -> String {
+    format!("{s:?}")
+}
+
+/// `base`, or `base` with a number appended if `names` already has it.
+fn dedupe(names: &mut BTreeSet<String>, base: &str, separator: &str) -> String {
+    let mut name = base.to_string();
+    for n in 2.. {
+        if names.insert(name.clone()) {
+            break;
+        }
+        name = format!("{base}{separator}{n}");
+    }
+    name
+}
+
+fn uses_map(ty: &Type) -> bool {
+    match ty {
+        Type::Map(_) => true,
+        Type::Array(ty) | Type::Option(ty) => uses_map(ty),
+        _ => false,
+    }
+}
+
+fn write_doc(out: &mut String, doc: Option<&str>, indent: &str, language: Language) -> fmt::Result {
+    let Some(doc) = doc else {
+        return Ok(());
+    };
+    let lines: Vec<_> = doc.lines().collect();
+    match (language, lines.as_slice()) {
+        (Language::Rust, _) => {
+            for line in lines {
+                writeln!(out, "{indent}/// {line}")?;
+            }
+        }
+        (Language::TypeScript, [line]) => writeln!(out, "{indent}/** {line} */")?,
+        (Language::TypeScript, _) => {
+            writeln!(out, "{indent}/**")?;
+            for line in lines {
+                writeln!(out, "{indent} * {line}")?;
+            }
+            writeln!(out, "{indent} */")?;
+        }
+    }
+    Ok(())
+}
+
+struct Renderer<'a> {
+    defs: &'a [Def],
+}
+
+impl Renderer<'_> {
+    fn rust_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Any => "jval::Json".to_string(),
+            Type::Null => "()".to_string(),
+            Type::Boolean => "bool".to_string(),
+            Type::Integer => "i64".to_string(),
+            Type::Number => "f64".to_string(),
+            Type::String => "String".to_string(),
+            Type::Array(item) => format!("Vec<{}>", self.rust_type(item)),
+            Type::Map(value) => format!("BTreeMap<String, {}>", self.rust_type(value)),
+            Type::Option(ty) => format!("Option<{}>", self.rust_type(ty)),
+            Type::Named(i) => self.defs[*i].name.clone(),
+            Type::Boxed(i) => format!("Box<{}>", self.defs[*i].name),
+        }
+    }
+
+    fn rust(&self, out: &mut String, alias: Option<&(String, Type)>) -> fmt::Result {
+        let fields = self.defs.iter().flat_map(|def| match &def.kind {
+            Kind::Struct(fields) => fields.iter().map(|f| &f.ty).collect(),
+            Kind::Union(variants) => variants.iter().map(|(_, ty)| ty).collect(),
+            Kind::Enum(_) => Vec::new(),
+        });
+        let mut types = alias.map(|(_, ty)| ty).into_iter().chain(fields);
+
+        writeln!(out, "use serde::{{Deserialize, Serialize}};")?;
+        if types.any(uses_map) {
+            writeln!(out, "use std::collections::BTreeMap;")?;
+        }
+
+        if let Some((name, ty)) = alias {
+            writeln!(out, "\npub type {name} = {};", self.rust_type(ty))?;
+        }
+
+        for def in self.defs {
+            writeln!(out)?;
+            write_doc(out, def.doc.as_deref(), "", Language::Rust)?;
+
+            match &def.kind {
+                Kind::Struct(fields) => {
+                    writeln!(
+                        out,
+                        "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]"
+                    )?;
+                    writeln!(out, "pub struct {} {{", def.name)?;
+                    let mut names = BTreeSet::new();
+                    for field in fields {
+                        write_doc(out, field.doc.as_deref(), "    ", Language::Rust)?;
+
+                        let name = dedupe(&mut names, &field_name(&field.key), "_");
+                        let mut attributes = Vec::new();
+                        if name.trim_start_matches("r#") != field.key {
+                            attributes.push(format!("rename = {}", quote(&field.key)));
+                        }
+                        let ty = if field.required {
+                            field.ty.clone()
+                        } else {
+                            attributes
+                                .push("skip_serializing_if = \"Option::is_none\"".to_string());
+                            field.ty.clone().nullable()
+                        };
+                        if !attributes.is_empty() {
+                            writeln!(out, "    #[serde({})]", attributes.join(", "))?;
+                        }
+                        writeln!(out, "    pub {name}: {},", self.rust_type(&ty))?;
+                    }
+                    writeln!(out, "}}")?;
+                }
+                Kind::Enum(values) => {
+                    writeln!(
+                        out,
+                        "#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]"
+                    )?;
+                    writeln!(out, "pub enum {} {{", def.name)?;
+                    let mut names = BTreeSet::new();
+                    for value in values {
+                        let name = dedupe(&mut names, &variant_name(value), "");
+                        if name != *value {
+                            writeln!(out, "    #[serde(rename = {})]", quote(value))?;
+                        }
+                        writeln!(out, "    {name},")?;
+                    }
+                    writeln!(out, "}}")?;
+                }
+                Kind::Union(variants) => {
+                    writeln!(
+                        out,
+                        "#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]"
+                    )?;
+                    writeln!(out, "#[serde(untagged)]")?;
+                    writeln!(out, "pub enum {} {{", def.name)?;
+                    for (label, ty) in variants {
+                        writeln!(out, "    {label}({}),", self.rust_type(ty))?;
+                    }
+                    writeln!(out, "}}")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn typescript_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Any => "unknown".to_string(),
+            Type::Null => "null".to_string(),
+            Type::Boolean => "boolean".to_string(),
+            Type::Integer | Type::Number => "number".to_string(),
+            Type::String => "string".to_string(),
+            Type::Array(item) => match self.typescript_type(item) {
+                item if item.contains(' ') => format!("({item})[]"),
+                item => format!("{item}[]"),
+            },
+            Type::Map(value) => format!("Record<string, {}>", self.typescript_type(value)),
+            Type::Option(ty) if matches!(**ty, Type::Any | Type::Null) => self.typescript_type(ty),
+            Type::Option(ty) => format!("{} | null", self.typescript_type(ty)),
+            Type::Named(i) | Type::Boxed(i) => self.defs[*i].name.clone(),
+        }
+    }
+
+    fn typescript(&self, out: &mut String, alias: Option<&(String, Type)>) -> fmt::Result {
+        if let Some((name, ty)) = alias {
+            writeln!(out, "export type {name} = {};", self.typescript_type(ty))?;
+        }
+
+        for def in self.defs {
+            if !out.is_empty() {
+                writeln!(out)?;
+            }
+            write_doc(out, def.doc.as_deref(), "", Language::TypeScript)?;
+
+            match &def.kind {
+                Kind::Struct(fields) => {
+                    writeln!(out, "export interface {} {{", def.name)?;
+                    for field in fields {
+                        write_doc(out, field.doc.as_deref(), "  ", Language::TypeScript)?;
+                        let key = if is_identifier(&field.key) {
+                            field.key.clone()
+                        } else {
+                            quote(&field.key)
+                        };
+                        let optional = if field.required { "" } else { "?" };
+                        let ty = self.typescript_type(&field.ty);
+                        writeln!(out, "  {key}{optional}: {ty};")?;
+                    }
+                    writeln!(out, "}}")?;
+                }
+                Kind::Enum(values) => {
+                    let values: Vec<_> = values.iter().map(|v| quote(v)).collect();
+                    writeln!(out, "export type {} = {};", def.name, values.join(" | "))?;
+                }
+                Kind::Union(variants) => {
+                    let types: Vec<_> = variants
+                        .iter()
+                        .map(|(_, ty)| self.typescript_type(ty))
+                        .collect();
+                    writeln!(out, "export type {} = {};", def.name, types.join(" | "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn rust_from_samples() {
+    let samples = [
+        json!({"id": 1, "firstName": "ann", "type": "admin", "tags": ["a"], "score": 1.5}),
+        json!({"id": 2, "type": "admin", "tags": [], "score": null}),
+    ];
+    assert_eq!(
+        from_samples(&samples, "user", Language::Rust),
+        r#"use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    #[serde(rename = "firstName", skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    pub id: i64,
+    pub score: Option<f64>,
+    pub tags: Vec<String>,
+    pub r#type: Type,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Type {
+    #[serde(rename = "admin")]
+    Admin,
+}
+"#
+    );
+}
+
+#[test]
+fn typescript_from_schema() {
+    let schema = json!({
+        "description": "An order.",
+        "type": "object",
+        "properties": {
+            "lines": {"type": "array", "items": {"$ref": "#/$defs/line"}},
+            "status": {"enum": ["open", "shipped"]},
+            "x-meta": {"additionalProperties": {"type": "string"}},
+            "ref": {"type": ["string", "integer", "null"]}
+        },
+        "required": ["lines", "status"],
+        "$defs": {
+            "line": {
+                "type": "object",
+                "properties": {"sku": {"type": "string", "description": "Stock unit."}},
+                "required": ["sku"]
+            }
+        }
+    });
+    assert_eq!(
+        from_schema(&schema, "Order", Language::TypeScript).unwrap(),
+        r#"/** An order. */
+export interface Order {
+  lines: Line[];
+  ref?: Ref | null;
+  status: Status;
+  "x-meta"?: Record<string, string>;
+}
+
+export interface Line {
+  /** Stock unit. */
+  sku: string;
+}
+
+export type Ref = string | number;
+
+export type Status = "open" | "shipped";
+"#
+    );
+}
+
+#[test]
+fn recursion_and_composition() {
+    let schema = json!({
+        "$ref": "#/$defs/node",
+        "$defs": {
+            "named": {"properties": {"name": {"type": "string"}}, "required": ["name"]},
+            "node": {
+                "allOf": [{"$ref": "#/$defs/named"}],
+                "properties": {
+                    "parent": {"anyOf": [{"$ref": "#/$defs/node"}, {"type": "null"}]},
+                    "children": {"items": {"$ref": "#/$defs/node"}},
+                    "value": {"oneOf": [{"type": "boolean"}, {"$ref": "#/$defs/named"}]}
+                }
+            }
+        }
+    });
+    let code = from_schema(&schema, "Tree", Language::Rust).unwrap();
+
+    assert!(code.contains("\npub type Tree = Node;\n"));
+    assert!(code.contains("    pub name: String,\n"));
+    assert!(code.contains("    pub parent: Option<Box<Node>>,\n"));
+    assert!(code.contains("    pub children: Option<Vec<Node>>,\n"));
+    assert!(code.contains("pub enum Value {\n    Boolean(bool),\n    Named(Named),\n}"));
+}
+
+#[test]
+fn names() {
+    assert_eq!(pascal_case("userID"), "UserId");
+    assert_eq!(pascal_case("http_status-code"), "HttpStatusCode");
+    assert_eq!(snake_case("HTTPStatus2Code"), "http_status2_code");
+    assert_eq!(field_name("self"), "self_");
+    assert_eq!(field_name("2fa"), "_2fa");
+    assert_eq!(variant_name(""), "Empty");
+    assert_eq!(singular("categories"), "category");
+    assert_eq!(singular("data"), "data item");
+}
+
+#[test]
+fn errors() {
+    for (schema, path) in &[
+        (json!({"$ref": "other.json"}), "/$ref"),
+        (
+            json!({"properties": {"a": {"$ref": "#/nowhere"}}}),
+            "/properties/a/$ref",
+        ),
+        (json!({"type": "thing"}), "/type"),
+        (json!({"items": 1}), "/items"),
+    ] {
+        let e = from_schema(schema, "Root", Language::Rust).unwrap_err();
+        assert_eq!(e.path, *path, "{e}");
+    }
+    assert_eq!("ts".parse(), Ok(Language::TypeScript));
+    assert!("go".parse::<Language>().is_err());
+}
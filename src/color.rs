@@ -0,0 +1,135 @@
+//! Terminal colors for printed JSON, in the style of jq.
+//!
+//! ```
+//! use jval::{json, Colors, FormatOptions};
+//!
+//! let colors = "0;31:0;90".parse::<Colors>().unwrap();
+//! assert_eq!(colors.r#false, "0;90");
+//! let options = FormatOptions::compact().colors(colors);
+//! assert_eq!(
+//!     json!([null]).to_string_with(&options).unwrap(),
+//!     "\u{1b}[1;39m[\u{1b}[0m\u{1b}[0;31mnull\u{1b}[0m\u{1b}[1;39m]\u{1b}[0m"
+//! );
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// Terminal styles for each kind of token, each the parameters of an SGR
+/// escape sequence, like `1;31` for bold red. Tokens with an empty style
+/// are printed plain.
+///
+/// The default styles are jq's, and [`FromStr`] parses jq's `JQ_COLORS`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Colors {
+    pub null: String,
+    pub r#false: String,
+    pub r#true: String,
+    pub number: String,
+    pub string: String,
+    /// Brackets and commas of arrays.
+    pub array: String,
+    /// Braces, commas and colons of objects.
+    pub object: String,
+    /// Names of object members.
+    pub key: String,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Colors {
+            null: "0;90".to_string(),
+            r#false: "0;39".to_string(),
+            r#true: "0;39".to_string(),
+            number: "0;39".to_string(),
+            string: "0;32".to_string(),
+            array: "1;39".to_string(),
+            object: "1;39".to_string(),
+            key: "34;1".to_string(),
+        }
+    }
+}
+
+/// The kinds of token [`Colors`] has a style for.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Kind {
+    Null,
+    False,
+    True,
+    Number,
+    String,
+    Array,
+    Object,
+    Key,
+}
+
+impl Colors {
+    pub(crate) fn style(&self, kind: Kind) -> &str {
+        match kind {
+            Kind::Null => &self.null,
+            Kind::False => &self.r#false,
+            Kind::True => &self.r#true,
+            Kind::Number => &self.number,
+            Kind::String => &self.string,
+            Kind::Array => &self.array,
+            Kind::Object => &self.object,
+            Kind::Key => &self.key,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColorsError {
+    pub message: String,
+}
+
+impl fmt::Display for ColorsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ColorsError {}
+
+impl FromStr for Colors {
+    type Err = ColorsError;
+
+    /// Parse a `JQ_COLORS` value: up to eight styles separated by colons,
+    /// for null, false, true, numbers, strings, arrays, objects and member
+    /// names, in that order. Styles left off the end keep their defaults.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut colors = Colors::default();
+        if s.is_empty() {
+            return Ok(colors);
+        }
+
+        let mut styles = s.split(':');
+        let fields = [
+            &mut colors.null,
+            &mut colors.r#false,
+            &mut colors.r#true,
+            &mut colors.number,
+            &mut colors.string,
+            &mut colors.array,
+            &mut colors.object,
+            &mut colors.key,
+        ];
+        for (field, style) in IntoIterator::into_iter(fields).zip(&mut styles) {
+            if !style.chars().all(|c| c.is_ascii_digit() || c == ';') {
+                return Err(ColorsError {
+                    message: format!("invalid style \"{style}\": expected digits and ';'"),
+                });
+            }
+            *field = style.to_string();
+        }
+        if styles.next().is_some() {
+            return Err(ColorsError {
+                message: "too many styles: expected at most 8".to_string(),
+            });
+        }
+        Ok(colors)
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn jq_colors() {
+    assert_eq!("".parse::<Colors>(), Ok(Colors::default()));
+
+    let colors = "0;31:0;32:0;33::1".parse::<Colors>().unwrap();
+    assert_eq!(colors.null, "0;31");
+    assert_eq!(colors.r#true, "0;33");
+    assert_eq!(colors.number, "");
+    assert_eq!(colors.string, "1");
+    assert_eq!(colors.key, Colors::default().key);
+
+    let all = "1:2:3:4:5:6:7:8".parse::<Colors>().unwrap();
+    assert_eq!((all.object.as_str(), all.key.as_str()), ("7", "8"));
+}
+
+#[test]
+fn invalid() {
+    assert_eq!(
+        "1;31:red".parse::<Colors>().unwrap_err().to_string(),
+        "invalid style \"red\": expected digits and ';'"
+    );
+    assert!("1:2:3:4:5:6:7:8:9".parse::<Colors>().is_err());
+}
@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryInto;
+use std::fmt;
+use std::hash::BuildHasher;
+
+use super::pointer;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// Conversion of a Rust value into a [`Json`] tree.
+///
+/// With the `derive` feature, `#[derive(ToJson)]` implements this for
+/// structs and for enums of unit variants; see [`FromJson`] for the
+/// attributes it takes.
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+}
+
+/// Conversion of a [`Json`] tree into a Rust value.
+///
+/// With the `derive` feature, `#[derive(FromJson)]` implements this for
+/// structs, which are read from objects, and for enums of unit variants,
+/// which are read from strings. Fields take `#[json(...)]` attributes:
+///
+/// - `rename = "name"` reads and writes the member `name` instead.
+/// - `default` uses `Default::default()` when the member is missing.
+/// - `skip` never reads or writes the field, which is always the default.
+/// - `flatten` reads the field from the containing object itself, and
+///   writes its members into it.
+///
+/// Missing members are read as `null`, so `Option` fields may be left out.
+pub trait FromJson: Sized {
+    /// # Errors
+    ///
+    /// Fails if `json` doesn't have the shape of a `Self`.
+    fn from_json(json: &Json) -> Result<Self, FromJsonError>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FromJsonError {
+    /// JSON Pointer to the offending value.
+    pub path: String,
+    pub message: String,
+}
+
+impl FromJsonError {
+    #[must_use]
+    pub fn new(message: impl Into<String>) -> Self {
+        FromJsonError {
+            path: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// An error for finding `found` where `what` was expected.
+    #[must_use]
+    pub fn expected(what: &str, found: &Json) -> Self {
+        let found = match found {
+            Json::Null => "null",
+            Json::Boolean(_) => "a boolean",
+            Json::Number(_) => "a number",
+            Json::String(_) => "a string",
+            Json::Array(_) => "an array",
+            Json::Object(_) => "an object",
+        };
+        Self::new(format!("expected {what}, found {found}"))
+    }
+
+    #[must_use]
+    pub fn missing_field(name: &str) -> Self {
+        Self::new(format!("missing field \"{name}\""))
+    }
+
+    /// Move the error down into the member or element `token`.
+    #[must_use]
+    pub fn at(mut self, token: &str) -> Self {
+        self.path = format!("/{}{}", pointer::escape(token), self.path);
+        self
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.message, self.path)
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+impl ToJson for Json {
+    fn to_json(&self) -> Json {
+        self.clone()
+    }
+}
+
+impl FromJson for Json {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        Ok(json.clone())
+    }
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Json {
+        Json::Boolean(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        json.as_bool()
+            .ok_or_else(|| FromJsonError::expected("a boolean", json))
+    }
+}
+
+macro_rules! integers {
+    ($($t:ty)*) => {$(
+        #[allow(clippy::cast_lossless, clippy::cast_precision_loss)]
+        impl ToJson for $t {
+            fn to_json(&self) -> Json {
+                Json::Number(*self as f64)
+            }
+        }
+
+        #[allow(
+            clippy::cast_lossless,
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        impl FromJson for $t {
+            fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+                // One past the largest is a power of two, so it's exact even
+                // where the largest itself rounds up to it, as for `i64`.
+                let (min, end) = (<$t>::MIN as f64, <$t>::MAX as f64 + 1.);
+                match json {
+                    Json::Number(n) if n.fract() == 0. && *n >= min && *n < end => Ok(*n as $t),
+                    Json::Number(n) => Err(FromJsonError::new(format!(
+                        "expected an integer from {} to {}, found {}",
+                        <$t>::MIN, <$t>::MAX, n
+                    ))),
+                    _ => Err(FromJsonError::expected("an integer", json)),
+                }
+            }
+        }
+    )*};
+}
+
+integers!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl ToJson for f64 {
+    fn to_json(&self) -> Json {
+        Json::Number(*self)
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        json.as_f64()
+            .ok_or_else(|| FromJsonError::expected("a number", json))
+    }
+}
+
+impl ToJson for f32 {
+    fn to_json(&self) -> Json {
+        Json::Number((*self).into())
+    }
+}
+
+impl FromJson for f32 {
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        f64::from_json(json).map(|n| n as f32)
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_string())
+    }
+}
+
+impl ToJson for String {
+    fn to_json(&self) -> Json {
+        Json::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        json.as_str()
+            .map(str::to_string)
+            .ok_or_else(|| FromJsonError::expected("a string", json))
+    }
+}
+
+impl ToJson for char {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_string())
+    }
+}
+
+impl FromJson for char {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        let mut chars = json.as_str().unwrap_or_default().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromJsonError::expected("a single character string", json)),
+        }
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for Box<T> {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Box<T> {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        T::from_json(json).map(Box::new)
+    }
+}
+
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Json {
+        self.as_ref().map_or(Json::Null, ToJson::to_json)
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        match json {
+            Json::Null => Ok(None),
+            _ => T::from_json(json).map(Some),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(ToJson::to_json).collect())
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Json {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        json.as_array()
+            .ok_or_else(|| FromJsonError::expected("an array", json))?
+            .iter()
+            .enumerate()
+            .map(|(i, item)| T::from_json(item).map_err(|e| e.at(&i.to_string())))
+            .collect()
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> Json {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: FromJson, const N: usize> FromJson for [T; N] {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        Vec::from_json(json)?.try_into().map_err(|items: Vec<T>| {
+            FromJsonError::new(format!(
+                "expected an array of {N} elements, found {}",
+                items.len()
+            ))
+        })
+    }
+}
+
+macro_rules! tuples {
+    ($($len:literal => ($($t:ident $i:tt)+))+) => {$(
+        impl<$($t: ToJson),+> ToJson for ($($t,)+) {
+            fn to_json(&self) -> Json {
+                Json::Array(vec![$(self.$i.to_json()),+])
+            }
+        }
+
+        impl<$($t: FromJson),+> FromJson for ($($t,)+) {
+            fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+                match json.as_array() {
+                    Some(items) if items.len() == $len => Ok(($(
+                        $t::from_json(&items[$i]).map_err(|e| e.at(stringify!($i)))?,
+                    )+)),
+                    Some(items) => Err(FromJsonError::new(format!(
+                        "expected an array of {} elements, found {}",
+                        $len,
+                        items.len()
+                    ))),
+                    None => Err(FromJsonError::expected("an array", json)),
+                }
+            }
+        }
+    )+};
+}
+
+tuples! {
+    1 => (A 0)
+    2 => (A 0 B 1)
+    3 => (A 0 B 1 C 2)
+    4 => (A 0 B 1 C 2 D 3)
+    5 => (A 0 B 1 C 2 D 3 E 4)
+    6 => (A 0 B 1 C 2 D 3 E 4 F 5)
+}
+
+fn members<'a, V: 'a + ToJson>(members: impl Iterator<Item = (&'a String, &'a V)>) -> Json {
+    Json::Object(members.map(|(k, v)| (k.clone(), v.to_json())).collect())
+}
+
+fn from_members<V: FromJson, M: std::iter::FromIterator<(String, V)>>(
+    json: &Json,
+) -> Result<M, FromJsonError> {
+    json.as_object()
+        .ok_or_else(|| FromJsonError::expected("an object", json))?
+        .iter()
+        .map(|(k, v)| Ok((k.clone(), V::from_json(v).map_err(|e| e.at(k))?)))
+        .collect()
+}
+
+impl<V: ToJson> ToJson for BTreeMap<String, V> {
+    fn to_json(&self) -> Json {
+        members(self.iter())
+    }
+}
+
+impl<V: FromJson> FromJson for BTreeMap<String, V> {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        from_members(json)
+    }
+}
+
+impl<V: ToJson, S> ToJson for HashMap<String, V, S> {
+    fn to_json(&self) -> Json {
+        members(self.iter())
+    }
+}
+
+impl<V: FromJson, S: BuildHasher + Default> FromJson for HashMap<String, V, S> {
+    fn from_json(json: &Json) -> Result<Self, FromJsonError> {
+        from_members(json)
+    }
+}
@@ -0,0 +1,203 @@
+#![cfg(test)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use jval_derive::{FromJson, ToJson};
+
+use super::*;
+
+#[derive(Debug, Default, FromJson, PartialEq, ToJson)]
+struct Config {
+    name: String,
+    #[json(rename = "listen-port")]
+    port: u16,
+    #[json(default)]
+    tags: Vec<String>,
+    limits: Option<Limits>,
+    #[json(skip)]
+    cache: usize,
+    #[json(flatten)]
+    extra: BTreeMap<String, Json>,
+}
+
+#[derive(Debug, Default, FromJson, PartialEq, ToJson)]
+struct Limits {
+    depth: i32,
+    mode: Mode,
+}
+
+#[derive(Debug, Default, FromJson, PartialEq, ToJson)]
+enum Mode {
+    #[json(rename = "fast")]
+    Fast,
+    #[default]
+    Safe,
+}
+
+#[derive(Debug, FromJson, PartialEq, ToJson)]
+struct Point(f64, f64);
+
+#[derive(Debug, FromJson, PartialEq, ToJson)]
+struct Id(u32);
+
+#[derive(Debug, FromJson, PartialEq, ToJson)]
+struct Event {
+    r#type: String,
+}
+
+#[test]
+fn primitives() {
+    assert_eq!(true.to_json(), json!(true));
+    assert_eq!(7u8.to_json(), json!(7));
+    assert_eq!("a".to_json(), json!("a"));
+    assert_eq!('c'.to_json(), json!("c"));
+    assert_eq!(i64::from_json(&json!(-3)), Ok(-3));
+    assert_eq!(f32::from_json(&json!(0.5)), Ok(0.5));
+    assert_eq!(char::from_json(&json!("x")), Ok('x'));
+    assert_eq!(String::from_json(&json!("s")), Ok("s".to_string()));
+
+    assert_eq!(
+        u8::from_json(&json!(256)).unwrap_err().message,
+        "expected an integer from 0 to 255, found 256"
+    );
+    assert!(i32::from_json(&json!(1.5)).is_err());
+    assert_eq!(
+        bool::from_json(&json!(null)).unwrap_err().message,
+        "expected a boolean, found null"
+    );
+    assert!(char::from_json(&json!("xy")).is_err());
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn integer_bounds() {
+    assert_eq!(u8::from_json(&json!(255)), Ok(255));
+    assert_eq!(i8::from_json(&json!(-128)), Ok(-128));
+    assert!(i8::from_json(&json!(-129)).is_err());
+    assert_eq!(i32::from_json(&json!(2_147_483_647)), Ok(i32::MAX));
+    assert!(i32::from_json(&json!(2_147_483_648_u32)).is_err());
+    assert_eq!(u32::from_json(&json!(4_294_967_295_u32)), Ok(u32::MAX));
+    assert!(u32::from_json(&json!(4_294_967_296_u64)).is_err());
+
+    // `MAX` rounds up to the power of two past it as an `f64`, which
+    // mustn't be taken for it.
+    let two = |n: i32| Json::Number(2f64.powi(n));
+    assert_eq!(i64::from_json(&Json::Number(i64::MIN as f64)), Ok(i64::MIN));
+    assert!(i64::from_json(&two(63)).is_err());
+    assert_eq!(i64::from_json(&two(62)), Ok(1 << 62));
+    assert!(u64::from_json(&two(64)).is_err());
+    assert!(usize::from_json(&two(64)).is_err());
+    assert_eq!(u64::from_json(&two(63)), Ok(1 << 63));
+    assert_eq!(
+        u64::from_json(&Json::Number(u64::MAX as f64 - 2048.)),
+        Ok(u64::MAX - 2047)
+    );
+}
+
+#[test]
+fn collections() {
+    assert_eq!(vec![Some(1), None].to_json(), json!([1, null]));
+    assert_eq!([1, 2, 3].to_json(), json!([1, 2, 3]));
+    assert_eq!((1, "a", false).to_json(), json!([1, "a", false]));
+    assert_eq!(<[u8; 2]>::from_json(&json!([1, 2])), Ok([1, 2]));
+    assert_eq!(
+        <[u8; 2]>::from_json(&json!([1])).unwrap_err().message,
+        "expected an array of 2 elements, found 1"
+    );
+    assert_eq!(
+        <(u8, String)>::from_json(&json!([1, "b"])),
+        Ok((1, "b".to_string()))
+    );
+
+    let mut map = HashMap::new();
+    map.insert("k".to_string(), vec![1]);
+    assert_eq!(map.to_json(), json!({"k": [1]}));
+    assert_eq!(HashMap::from_json(&json!({"k": [1]})), Ok(map));
+    let map = BTreeMap::<String, Option<bool>>::from_json(&json!({"a": null})).unwrap();
+    assert_eq!(map["a"], None);
+}
+
+#[test]
+fn error_paths() {
+    let e = Vec::<BTreeMap<String, u8>>::from_json(&json!([{}, {"a/b": "x"}])).unwrap_err();
+    assert_eq!(e.path, "/1/a~1b");
+    assert_eq!(
+        e.to_string(),
+        "expected an integer, found a string at \"/1/a~1b\""
+    );
+    let e = <(u8, (bool, u8))>::from_json(&json!([1, [true, null]])).unwrap_err();
+    assert_eq!(e.path, "/1/1");
+}
+
+#[test]
+fn derive() {
+    let config = Config {
+        name: "svc".to_string(),
+        port: 80,
+        tags: vec!["a".to_string()],
+        limits: Some(Limits {
+            depth: 2,
+            mode: Mode::Fast,
+        }),
+        cache: 9,
+        extra: vec![("region".to_string(), json!("eu"))]
+            .into_iter()
+            .collect(),
+    };
+    let json = config.to_json();
+    assert_eq!(
+        json,
+        json!({
+            "name": "svc", "listen-port": 80, "tags": ["a"],
+            "limits": {"depth": 2, "mode": "fast"}, "region": "eu"
+        })
+    );
+
+    let read = Config::from_json(&json).unwrap();
+    assert_eq!(read.cache, 0);
+    assert_eq!(read.limits, config.limits);
+    assert_eq!(read.extra["region"], json!("eu"));
+
+    let read = Config::from_json(&json!({"name": "x", "listen-port": 1})).unwrap();
+    assert_eq!((read.tags, read.limits), (vec![], None));
+
+    assert_eq!(Point(1., 2.).to_json(), json!([1, 2]));
+    assert_eq!(Point::from_json(&json!([1, 2])), Ok(Point(1., 2.)));
+    assert_eq!(Id(4).to_json(), json!(4));
+    assert_eq!(Id::from_json(&json!(4)), Ok(Id(4)));
+    assert_eq!(Mode::from_json(&json!("Safe")), Ok(Mode::Safe));
+
+    // Raw identifiers are named without their `r#`.
+    let event = Event {
+        r#type: "click".to_string(),
+    };
+    assert_eq!(event.to_json(), json!({"type": "click"}));
+    assert_eq!(Event::from_json(&json!({"type": "click"})), Ok(event));
+}
+
+#[test]
+fn derive_errors() {
+    let e = Config::from_json(&json!({"listen-port": 1})).unwrap_err();
+    assert_eq!(e.to_string(), "missing field \"name\" at \"\"");
+
+    let e = Config::from_json(
+        &json!({"name": "x", "listen-port": 1, "limits": {"depth": 1, "mode": "slow"}}),
+    )
+    .unwrap_err();
+    assert_eq!(e.path, "/limits/mode");
+    assert_eq!(
+        e.message,
+        "unknown variant \"slow\", expected one of \"fast\", \"Safe\""
+    );
+
+    let e = Point::from_json(&json!([1, "2"])).unwrap_err();
+    assert_eq!(e.path, "/1");
+    assert_eq!(
+        Point::from_json(&json!([1])).unwrap_err().message,
+        "expected an array of 2 elements, found an array"
+    );
+    assert_eq!(
+        Limits::from_json(&json!([])).unwrap_err().message,
+        "expected an object, found an array"
+    );
+}
@@ -0,0 +1,160 @@
+use std::collections::BTreeSet;
+
+use super::pointer;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// A difference between two documents, located by JSON Pointer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added { path: String, value: Json },
+    Removed { path: String, value: Json },
+    Changed { path: String, from: Json, to: Json },
+}
+
+impl Change {
+    #[must_use]
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DiffOptions {
+    /// Compare arrays as multisets, so reordered elements are not reported.
+    pub ignore_array_order: bool,
+    /// Numbers that differ by no more than this are considered equal.
+    pub float_tolerance: f64,
+    /// Object members with these names are skipped at any depth.
+    pub ignore_keys: BTreeSet<String>,
+}
+
+/// List the differences between `a` and `b`.
+#[must_use]
+pub fn diff(a: &Json, b: &Json) -> Vec<Change> {
+    diff_with(a, b, &DiffOptions::default())
+}
+
+/// Like `diff`, with options to relax what counts as a difference.
+#[must_use]
+pub fn diff_with(a: &Json, b: &Json, options: &DiffOptions) -> Vec<Change> {
+    let mut changes = Vec::new();
+    Differ {
+        options,
+        path: Vec::new(),
+        changes: &mut changes,
+    }
+    .compare(a, b);
+    changes
+}
+
+struct Differ<'a> {
+    options: &'a DiffOptions,
+    path: Vec<String>,
+    changes: &'a mut Vec<Change>,
+}
+
+impl Differ<'_> {
+    fn here(&self) -> String {
+        pointer::join(&self.path)
+    }
+
+    fn child(&self, token: &str) -> String {
+        pointer::join(self.path.iter().map(String::as_str).chain(Some(token)))
+    }
+
+    fn equal(&self, a: &Json, b: &Json) -> bool {
+        let mut scratch = Vec::new();
+        Differ {
+            options: self.options,
+            path: Vec::new(),
+            changes: &mut scratch,
+        }
+        .compare(a, b);
+        scratch.is_empty()
+    }
+
+    fn compare(&mut self, a: &Json, b: &Json) {
+        let options = self.options;
+
+        match (a, b) {
+            (Json::Number(x), Json::Number(y)) if (x - y).abs() <= options.float_tolerance => {}
+            (Json::Object(left), Json::Object(right)) => {
+                let kept = |(key, _): &(&String, &Json)| !options.ignore_keys.contains(*key);
+
+                for (key, old) in left.iter().filter(kept) {
+                    match right.get(key) {
+                        Some(new) => {
+                            self.path.push(key.clone());
+                            self.compare(old, new);
+                            self.path.pop();
+                        }
+                        None => self.changes.push(Change::Removed {
+                            path: self.child(key),
+                            value: old.clone(),
+                        }),
+                    }
+                }
+                for (key, new) in right.iter().filter(kept) {
+                    if !left.contains_key(key) {
+                        self.changes.push(Change::Added {
+                            path: self.child(key),
+                            value: new.clone(),
+                        });
+                    }
+                }
+            }
+            (Json::Array(left), Json::Array(right)) if options.ignore_array_order => {
+                let mut unmatched: Vec<_> = (0..right.len()).collect();
+                for (i, old) in left.iter().enumerate() {
+                    match unmatched.iter().position(|&j| self.equal(old, &right[j])) {
+                        Some(found) => {
+                            unmatched.remove(found);
+                        }
+                        None => self.changes.push(Change::Removed {
+                            path: self.child(&i.to_string()),
+                            value: old.clone(),
+                        }),
+                    }
+                }
+                for j in unmatched {
+                    self.changes.push(Change::Added {
+                        path: self.child(&j.to_string()),
+                        value: right[j].clone(),
+                    });
+                }
+            }
+            (Json::Array(left), Json::Array(right)) => {
+                for (i, (old, new)) in left.iter().zip(right).enumerate() {
+                    self.path.push(i.to_string());
+                    self.compare(old, new);
+                    self.path.pop();
+                }
+                for (i, old) in left.iter().enumerate().skip(right.len()) {
+                    self.changes.push(Change::Removed {
+                        path: self.child(&i.to_string()),
+                        value: old.clone(),
+                    });
+                }
+                for (i, new) in right.iter().enumerate().skip(left.len()) {
+                    self.changes.push(Change::Added {
+                        path: self.child(&i.to_string()),
+                        value: new.clone(),
+                    });
+                }
+            }
+            _ if a == b => {}
+            _ => self.changes.push(Change::Changed {
+                path: self.here(),
+                from: a.clone(),
+                to: b.clone(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,76 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn basic() {
+    let a = json!({"name": "a", "n": 1, "tags": ["x", "y"], "gone": true});
+    let b = json!({"name": "b", "n": 1, "tags": ["x", "y", "z"], "new": null});
+
+    assert_eq!(
+        diff(&a, &b),
+        vec![
+            Change::Removed {
+                path: "/gone".into(),
+                value: json!(true)
+            },
+            Change::Changed {
+                path: "/name".into(),
+                from: json!("a"),
+                to: json!("b")
+            },
+            Change::Added {
+                path: "/tags/2".into(),
+                value: json!("z")
+            },
+            Change::Added {
+                path: "/new".into(),
+                value: json!(null)
+            },
+        ]
+    );
+    assert!(diff(&a, &a).is_empty());
+    assert_eq!(diff(&json!(1), &json!("1"))[0].path(), "");
+    assert_eq!(
+        diff(&json!({"a/b": [1]}), &json!({"a/b": [2]}))[0].path(),
+        "/a~1b/0"
+    );
+}
+
+#[test]
+fn options() {
+    let a = json!({"items": [1, 2, 3], "ratio": 0.33333, "updated": "monday"});
+    let b = json!({"items": [3, 1, 2], "ratio": 0.3334, "updated": "tuesday"});
+
+    assert_eq!(diff(&a, &b).len(), 5);
+
+    let mut options = DiffOptions {
+        ignore_array_order: true,
+        float_tolerance: 1e-3,
+        ignore_keys: BTreeSet::new(),
+    };
+    assert_eq!(diff_with(&a, &b, &options).len(), 1);
+
+    options.ignore_keys.insert("updated".to_string());
+    assert!(diff_with(&a, &b, &options).is_empty());
+
+    assert_eq!(
+        diff_with(&json!([1, 1, 2]), &json!([1, 2, 2]), &options),
+        vec![
+            Change::Removed {
+                path: "/1".into(),
+                value: json!(1)
+            },
+            Change::Added {
+                path: "/2".into(),
+                value: json!(2)
+            },
+        ]
+    );
+    assert!(diff_with(
+        &json!([{"v": 1.0001, "updated": 1}]),
+        &json!([{"v": 1, "updated": 2}]),
+        &options
+    )
+    .is_empty());
+}
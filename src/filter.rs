@@ -0,0 +1,1541 @@
+//! A subset of the jq filter language, evaluated over `Json` values.
+//!
+//! Supported: `.`, `..`, `.foo`, `.[e]`, `.[e:e]`, `.[]`, `?`, `|`, `,`,
+//! `//`, `and`/`or`, comparisons, arithmetic, array and object construction,
+//! string interpolation, `if`/`elif`/`else`, `e as $x | ...`, and the
+//! builtins listed in `BUILTINS`.
+//!
+//! ```
+//! # use jval::{json, filter::Filter};
+//! let doc = json!({"items": [{"id": 1}, {"id": 2}]});
+//! let filter: Filter = ".items[] | .id * 10".parse().unwrap();
+//! assert_eq!(filter.run(&doc).unwrap(), vec![json!(10), json!(20)]);
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::regex::Regex;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// The longest string `*` repeats one to, in bytes.
+const MAX_REPEAT: usize = 1 << 28;
+
+/// The most numbers one `range` makes.
+const MAX_RANGE: u64 = 1 << 24;
+
+/// The largest number below which every integer is a distinct `f64`, and
+/// so the furthest `range` can count by ones.
+const MAX_EXACT: f64 = 9_007_199_254_740_992.;
+
+/// Builtin functions and their arities.
+const BUILTINS: &[(&str, usize)] = &[
+    ("add", 0),
+    ("all", 0),
+    ("any", 0),
+    ("ascii_downcase", 0),
+    ("ascii_upcase", 0),
+    ("empty", 0),
+    ("error", 1),
+    ("first", 0),
+    ("first", 1),
+    ("from_entries", 0),
+    ("has", 1),
+    ("join", 1),
+    ("keys", 0),
+    ("keys_unsorted", 0),
+    ("last", 0),
+    ("length", 0),
+    ("map", 1),
+    ("max", 0),
+    ("min", 0),
+    ("not", 0),
+    ("range", 1),
+    ("range", 2),
+    ("reverse", 0),
+    ("select", 1),
+    ("sort", 0),
+    ("sort_by", 1),
+    ("split", 1),
+    ("test", 1),
+    ("to_entries", 0),
+    ("tojson", 0),
+    ("tonumber", 0),
+    ("tostring", 0),
+    ("type", 0),
+    ("unique", 0),
+    ("values", 0),
+    ("with_entries", 1),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at position {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+/// A compiled filter.
+#[derive(Debug)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { src: s, pos: 0 };
+        parser.skip_ws();
+        let expr = parser.pipe()?;
+        parser.skip_ws();
+
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected character"));
+        }
+
+        Ok(Filter { expr })
+    }
+}
+
+impl Filter {
+    /// Run the filter on an input value, collecting every output.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the span of the sub-expression that raised a runtime error,
+    /// e.g. indexing a number with a string.
+    pub fn run(&self, input: &Json) -> Result<Vec<Json>, FilterError> {
+        self.expr.eval(input, &Env::Empty)
+    }
+}
+
+// syntax tree
+
+#[derive(Debug)]
+struct Expr {
+    kind: Kind,
+    span: Range<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Part {
+    Literal(String),
+    Interpolated(Expr),
+}
+
+#[derive(Debug)]
+enum Kind {
+    Identity,
+    Recurse,
+    Literal(Json),
+    String(Vec<Part>),
+    Index(Box<Expr>, Box<Expr>),
+    Slice(Box<Expr>, Option<Box<Expr>>, Option<Box<Expr>>),
+    Iterate(Box<Expr>),
+    Optional(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Alternative(Box<Expr>, Box<Expr>),
+    Array(Option<Box<Expr>>),
+    Object(Vec<(Expr, Expr)>),
+    Call(String, Vec<Expr>),
+    Variable(String),
+    Bind(Box<Expr>, String, Box<Expr>),
+    If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
+}
+
+// evaluation
+
+enum Env<'a> {
+    Empty,
+    Bind(&'a str, Json, &'a Env<'a>),
+}
+
+impl Env<'_> {
+    fn get(&self, name: &str) -> Option<&Json> {
+        match self {
+            Env::Empty => None,
+            Env::Bind(n, v, _) if *n == name => Some(v),
+            Env::Bind(_, _, parent) => parent.get(name),
+        }
+    }
+}
+
+fn truthy(v: &Json) -> bool {
+    !matches!(v, Json::Null | Json::Boolean(false))
+}
+
+fn type_name(v: &Json) -> &'static str {
+    match v {
+        Json::Null => "null",
+        Json::Boolean(_) => "boolean",
+        Json::Number(_) => "number",
+        Json::String(_) => "string",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+/// jq's total order: null < false < true < numbers < strings < arrays < objects.
+fn compare(a: &Json, b: &Json) -> Ordering {
+    fn rank(v: &Json) -> u8 {
+        match v {
+            Json::Null => 0,
+            Json::Boolean(false) => 1,
+            Json::Boolean(true) => 2,
+            Json::Number(_) => 3,
+            Json::String(_) => 4,
+            Json::Array(_) => 5,
+            Json::Object(_) => 6,
+        }
+    }
+
+    match (a, b) {
+        (Json::Number(x), Json::Number(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+        (Json::String(x), Json::String(y)) => x.cmp(y),
+        (Json::Array(x), Json::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare(x, y))
+            .find(|o| *o != Ordering::Equal)
+            .unwrap_or_else(|| x.len().cmp(&y.len())),
+        (Json::Object(x), Json::Object(y)) => x.keys().cmp(y.keys()).then_with(|| {
+            x.values()
+                .zip(y.values())
+                .map(|(x, y)| compare(x, y))
+                .find(|o| *o != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        }),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+impl Expr {
+    fn fail<T>(&self, message: String) -> Result<T, FilterError> {
+        Err(FilterError {
+            message,
+            span: self.span.clone(),
+        })
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn eval(&self, input: &Json, env: &Env) -> Result<Vec<Json>, FilterError> {
+        match &self.kind {
+            Kind::Identity => Ok(vec![input.clone()]),
+            Kind::Recurse => {
+                fn walk(v: &Json, out: &mut Vec<Json>) {
+                    out.push(v.clone());
+                    match v {
+                        Json::Array(a) => a.iter().for_each(|c| walk(c, out)),
+                        Json::Object(o) => o.values().for_each(|c| walk(c, out)),
+                        _ => {}
+                    }
+                }
+
+                let mut out = Vec::new();
+                walk(input, &mut out);
+                Ok(out)
+            }
+            Kind::Literal(j) => Ok(vec![j.clone()]),
+            Kind::String(parts) => {
+                let mut out = vec![String::new()];
+                for part in parts {
+                    match part {
+                        Part::Literal(s) => out.iter_mut().for_each(|o| o.push_str(s)),
+                        Part::Interpolated(e) => {
+                            let values = e.eval(input, env)?;
+                            out = out
+                                .iter()
+                                .flat_map(|prefix| {
+                                    values.iter().map(move |v| match v {
+                                        Json::String(s) => format!("{prefix}{s}"),
+                                        v => format!("{prefix}{v}"),
+                                    })
+                                })
+                                .collect();
+                        }
+                    }
+                }
+                Ok(out.into_iter().map(Json::String).collect())
+            }
+            Kind::Index(target, index) => {
+                let mut out = Vec::new();
+                for t in target.eval(input, env)? {
+                    for i in index.eval(input, env)? {
+                        out.push(self.index(&t, &i)?);
+                    }
+                }
+                Ok(out)
+            }
+            Kind::Slice(target, from, to) => {
+                let bound = |e: &Option<Box<Expr>>| match e {
+                    Some(e) => e.eval(input, env),
+                    None => Ok(vec![Json::Null]),
+                };
+                let mut out = Vec::new();
+                for t in target.eval(input, env)? {
+                    for f in bound(from)? {
+                        for u in bound(to)? {
+                            out.push(self.slice(&t, &f, &u)?);
+                        }
+                    }
+                }
+                Ok(out)
+            }
+            Kind::Iterate(target) => {
+                let mut out = Vec::new();
+                for t in target.eval(input, env)? {
+                    match t {
+                        Json::Array(a) => out.extend(a),
+                        Json::Object(o) => out.extend(o.into_values()),
+                        v => return self.fail(format!("Cannot iterate over {}", type_name(&v))),
+                    }
+                }
+                Ok(out)
+            }
+            Kind::Optional(e) => Ok(e.eval(input, env).unwrap_or_default()),
+            Kind::Pipe(a, b) => {
+                let mut out = Vec::new();
+                for v in a.eval(input, env)? {
+                    out.extend(b.eval(&v, env)?);
+                }
+                Ok(out)
+            }
+            Kind::Comma(a, b) => {
+                let mut out = a.eval(input, env)?;
+                out.extend(b.eval(input, env)?);
+                Ok(out)
+            }
+            Kind::Negate(e) => e
+                .eval(input, env)?
+                .into_iter()
+                .map(|v| match v {
+                    Json::Number(n) => Ok(Json::Number(-n)),
+                    v => self.fail(format!("{} cannot be negated", type_name(&v))),
+                })
+                .collect(),
+            Kind::Binary(op, a, b) => {
+                let rights = b.eval(input, env)?;
+                let lefts = a.eval(input, env)?;
+                let mut out = Vec::new();
+                for r in &rights {
+                    for l in &lefts {
+                        out.push(self.binary(*op, l, r)?);
+                    }
+                }
+                Ok(out)
+            }
+            Kind::And(a, b) | Kind::Or(a, b) => {
+                let is_and = matches!(self.kind, Kind::And(..));
+                let mut out = Vec::new();
+                for l in a.eval(input, env)? {
+                    if truthy(&l) != is_and {
+                        out.push(Json::Boolean(!is_and));
+                        continue;
+                    }
+                    for r in b.eval(input, env)? {
+                        out.push(Json::Boolean(truthy(&r)));
+                    }
+                }
+                Ok(out)
+            }
+            Kind::Alternative(a, b) => {
+                let good: Vec<_> = a
+                    .eval(input, env)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(truthy)
+                    .collect();
+                if good.is_empty() {
+                    b.eval(input, env)
+                } else {
+                    Ok(good)
+                }
+            }
+            Kind::Array(e) => Ok(vec![Json::Array(match e {
+                Some(e) => e.eval(input, env)?,
+                None => Vec::new(),
+            })]),
+            Kind::Object(entries) => {
+                let mut out = vec![BTreeMap::new()];
+                for (k, v) in entries {
+                    let keys = k.eval(input, env)?;
+                    let values = v.eval(input, env)?;
+                    let mut next = Vec::new();
+                    for m in &out {
+                        for key in &keys {
+                            let key = match key {
+                                Json::String(s) => s,
+                                other => {
+                                    return k.fail(format!(
+                                        "Object keys must be strings, not {}",
+                                        type_name(other)
+                                    ))
+                                }
+                            };
+                            for value in &values {
+                                let mut m = m.clone();
+                                m.insert(key.clone(), value.clone());
+                                next.push(m);
+                            }
+                        }
+                    }
+                    out = next;
+                }
+                Ok(out.into_iter().map(Json::Object).collect())
+            }
+            Kind::Call(name, args) => self.call(name, args, input, env),
+            Kind::Variable(name) => env
+                .get(name)
+                .map(|v| vec![v.clone()])
+                .map_or_else(|| self.fail(format!("${name} is not defined")), Ok),
+            Kind::Bind(source, name, body) => {
+                let mut out = Vec::new();
+                for v in source.eval(input, env)? {
+                    out.extend(body.eval(input, &Env::Bind(name, v, env))?);
+                }
+                Ok(out)
+            }
+            Kind::If(cond, then, otherwise) => {
+                let mut out = Vec::new();
+                for c in cond.eval(input, env)? {
+                    if truthy(&c) {
+                        out.extend(then.eval(input, env)?);
+                    } else if let Some(e) = otherwise {
+                        out.extend(e.eval(input, env)?);
+                    } else {
+                        out.push(input.clone());
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn index(&self, target: &Json, index: &Json) -> Result<Json, FilterError> {
+        match (target, index) {
+            (Json::Null, Json::String(_) | Json::Number(_)) => Ok(Json::Null),
+            (Json::Object(o), Json::String(k)) => Ok(o.get(k).cloned().unwrap_or_default()),
+            (Json::Array(a), Json::Number(n)) => {
+                let i = n.floor();
+                let i = if i < 0. { a.len() as f64 + i } else { i };
+                Ok(if i < 0. {
+                    Json::Null
+                } else {
+                    a.get(i as usize).cloned().unwrap_or_default()
+                })
+            }
+            (t, i) => self.fail(format!(
+                "Cannot index {} with {}",
+                type_name(t),
+                type_name(i)
+            )),
+        }
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn slice(&self, target: &Json, from: &Json, to: &Json) -> Result<Json, FilterError> {
+        let bounds = |len: usize| -> Result<(usize, usize), FilterError> {
+            let clamp = |b: &Json, default: usize| match b {
+                Json::Null => Ok(default),
+                Json::Number(n) => {
+                    let n = n.floor();
+                    let n = if n < 0. { len as f64 + n } else { n };
+                    Ok(n.max(0.).min(len as f64) as usize)
+                }
+                b => self.fail(format!(
+                    "Slice bounds must be numbers, not {}",
+                    type_name(b)
+                )),
+            };
+            let start = clamp(from, 0)?;
+            let end = clamp(to, len)?;
+            Ok((start, end.max(start)))
+        };
+
+        match target {
+            Json::Null => Ok(Json::Null),
+            Json::Array(a) => {
+                let (s, e) = bounds(a.len())?;
+                Ok(Json::Array(a[s..e].to_vec()))
+            }
+            Json::String(st) => {
+                let chars: Vec<char> = st.chars().collect();
+                let (s, e) = bounds(chars.len())?;
+                Ok(Json::String(chars[s..e].iter().collect()))
+            }
+            t => self.fail(format!("Cannot slice {}", type_name(t))),
+        }
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss,
+        clippy::float_cmp,
+        clippy::many_single_char_names
+    )]
+    fn binary(&self, op: BinOp, l: &Json, r: &Json) -> Result<Json, FilterError> {
+        let cmp = || compare(l, r);
+        let result = match (op, l, r) {
+            (BinOp::Eq, ..) => Json::Boolean(cmp() == Ordering::Equal),
+            (BinOp::Ne, ..) => Json::Boolean(cmp() != Ordering::Equal),
+            (BinOp::Lt, ..) => Json::Boolean(cmp() == Ordering::Less),
+            (BinOp::Le, ..) => Json::Boolean(cmp() != Ordering::Greater),
+            (BinOp::Gt, ..) => Json::Boolean(cmp() == Ordering::Greater),
+            (BinOp::Ge, ..) => Json::Boolean(cmp() != Ordering::Less),
+
+            (BinOp::Add, Json::Null, v) | (BinOp::Add, v, Json::Null) => v.clone(),
+            (BinOp::Add, Json::Number(a), Json::Number(b)) => Json::Number(a + b),
+            (BinOp::Add, Json::String(a), Json::String(b)) => Json::String(format!("{a}{b}")),
+            (BinOp::Add, Json::Array(a), Json::Array(b)) => {
+                Json::Array(a.iter().chain(b).cloned().collect())
+            }
+            (BinOp::Add, Json::Object(a), Json::Object(b)) => {
+                let mut m = a.clone();
+                m.extend(b.iter().map(|(k, v)| (k.clone(), v.clone())));
+                Json::Object(m)
+            }
+
+            (BinOp::Sub, Json::Number(a), Json::Number(b)) => Json::Number(a - b),
+            (BinOp::Sub, Json::Array(a), Json::Array(b)) => {
+                Json::Array(a.iter().filter(|v| !b.contains(v)).cloned().collect())
+            }
+
+            (BinOp::Mul, Json::Number(a), Json::Number(b)) => Json::Number(a * b),
+            (BinOp::Mul, Json::String(s), Json::Number(n))
+            | (BinOp::Mul, Json::Number(n), Json::String(s)) => {
+                if *n <= 0. {
+                    Json::Null
+                } else if n.ceil() * s.len() as f64 > MAX_REPEAT as f64 {
+                    return self.fail("Repeat string result too long".to_string());
+                } else {
+                    Json::String(s.repeat(n.ceil() as usize))
+                }
+            }
+            (BinOp::Mul, Json::Object(_), Json::Object(_)) => deep_merge(l, r),
+
+            (BinOp::Div, Json::Number(_), Json::Number(b)) if *b == 0. => {
+                return self.fail("Division by zero".to_string())
+            }
+            (BinOp::Div, Json::Number(a), Json::Number(b)) => Json::Number(a / b),
+            (BinOp::Div, Json::String(a), Json::String(b)) => {
+                Json::Array(split(a, b).into_iter().map(Json::String).collect())
+            }
+
+            (BinOp::Rem, Json::Number(a), Json::Number(b)) => {
+                let (a, b) = (a.trunc(), b.trunc());
+                if b == 0. {
+                    return self.fail("Modulo by zero".to_string());
+                }
+                Json::Number(a % b)
+            }
+
+            (op, l, r) => {
+                let verb = match op {
+                    BinOp::Add => "added",
+                    BinOp::Sub => "subtracted",
+                    BinOp::Mul => "multiplied",
+                    BinOp::Div => "divided",
+                    _ => "divided (remainder)",
+                };
+                return self.fail(format!(
+                    "{} and {} cannot be {}",
+                    type_name(l),
+                    type_name(r),
+                    verb
+                ));
+            }
+        };
+
+        Ok(result)
+    }
+
+    #[allow(clippy::too_many_lines, clippy::cast_precision_loss)]
+    fn call(
+        &self,
+        name: &str,
+        args: &[Expr],
+        input: &Json,
+        env: &Env,
+    ) -> Result<Vec<Json>, FilterError> {
+        let one = |v: Json| Ok(vec![v]);
+        let type_error =
+            |what: &str| self.fail(format!("{} ({}) {}", type_name(input), input, what));
+
+        match (name, args) {
+            ("empty", []) => Ok(Vec::new()),
+            ("not", []) => one(Json::Boolean(!truthy(input))),
+            ("type", []) => one(Json::String(type_name(input).to_string())),
+            ("tojson", []) => one(Json::String(input.to_string())),
+            ("length", []) => match input {
+                Json::Null => one(Json::Number(0.)),
+                Json::Number(n) => one(Json::Number(n.abs())),
+                Json::String(s) => one(Json::Number(s.chars().count() as f64)),
+                Json::Array(a) => one(Json::Number(a.len() as f64)),
+                Json::Object(o) => one(Json::Number(o.len() as f64)),
+                Json::Boolean(_) => type_error("has no length"),
+            },
+            ("keys" | "keys_unsorted", []) => match input {
+                Json::Object(o) => one(o.keys().map(|k| Json::String(k.clone())).collect()),
+                Json::Array(a) => one((0..a.len()).map(Json::from).collect()),
+                _ => type_error("has no keys"),
+            },
+            ("values", []) => Ok(if input.is_null() {
+                Vec::new()
+            } else {
+                vec![input.clone()]
+            }),
+            ("has", [key]) => key
+                .eval(input, env)?
+                .into_iter()
+                .map(|k| match (input, &k) {
+                    (Json::Object(o), Json::String(s)) => Ok(Json::Boolean(o.contains_key(s))),
+                    (Json::Array(a), Json::Number(n)) => {
+                        Ok(Json::Boolean(*n >= 0. && *n < a.len() as f64))
+                    }
+                    _ => self.fail(format!(
+                        "Cannot check whether {} has a {} key",
+                        type_name(input),
+                        type_name(&k)
+                    )),
+                })
+                .collect(),
+            ("select", [f]) => Ok(f
+                .eval(input, env)?
+                .iter()
+                .filter(|v| truthy(v))
+                .map(|_| input.clone())
+                .collect()),
+            ("map", [f]) => {
+                let mut out = Vec::new();
+                for v in self.elements(input)? {
+                    out.extend(f.eval(&v, env)?);
+                }
+                one(Json::Array(out))
+            }
+            ("with_entries", [f]) => {
+                let entries = self.to_entries(input)?;
+                let mut mapped = Vec::new();
+                for e in entries {
+                    mapped.extend(f.eval(&e, env)?);
+                }
+                one(self.collect_entries(&mapped)?)
+            }
+            ("to_entries", []) => one(Json::Array(self.to_entries(input)?)),
+            ("from_entries", []) => match input {
+                Json::Array(a) => one(self.collect_entries(a)?),
+                _ => type_error("cannot be turned into an object"),
+            },
+            ("add", []) => {
+                let mut acc = Json::Null;
+                for v in self.elements(input)? {
+                    acc = self.binary(BinOp::Add, &acc, &v)?;
+                }
+                one(acc)
+            }
+            ("any", []) => one(Json::Boolean(self.elements(input)?.iter().any(truthy))),
+            ("all", []) => one(Json::Boolean(self.elements(input)?.iter().all(truthy))),
+            ("first", []) => self.index(input, &Json::Number(0.)).map(|v| vec![v]),
+            ("last", []) => self.index(input, &Json::Number(-1.)).map(|v| vec![v]),
+            ("first", [f]) => Ok(f.eval(input, env)?.into_iter().take(1).collect()),
+            ("reverse", []) => match input {
+                Json::Null => one(Json::Array(Vec::new())),
+                Json::Array(a) => one(Json::Array(a.iter().rev().cloned().collect())),
+                Json::String(s) => one(Json::String(s.chars().rev().collect())),
+                _ => type_error("cannot be reversed"),
+            },
+            ("sort", []) => {
+                let mut a = self.array(input)?.clone();
+                a.sort_by(compare);
+                one(Json::Array(a))
+            }
+            ("sort_by", [f]) => {
+                let mut keyed = Vec::new();
+                for v in self.array(input)? {
+                    keyed.push((Json::Array(f.eval(v, env)?), v.clone()));
+                }
+                keyed.sort_by(|(a, _), (b, _)| compare(a, b));
+                one(Json::Array(keyed.into_iter().map(|(_, v)| v).collect()))
+            }
+            ("unique", []) => {
+                let mut a = self.array(input)?.clone();
+                a.sort_by(compare);
+                a.dedup_by(|x, y| compare(x, y) == Ordering::Equal);
+                one(Json::Array(a))
+            }
+            ("min", []) => one(self
+                .array(input)?
+                .iter()
+                .min_by(|a, b| compare(a, b))
+                .cloned()
+                .unwrap_or_default()),
+            ("max", []) => one(self
+                .array(input)?
+                .iter()
+                .max_by(|a, b| compare(a, b))
+                .cloned()
+                .unwrap_or_default()),
+            ("tostring", []) => one(match input {
+                Json::String(_) => input.clone(),
+                v => Json::String(v.to_string()),
+            }),
+            ("tonumber", []) => match input {
+                Json::Number(_) => one(input.clone()),
+                Json::String(s) => match s.trim().parse::<Json>() {
+                    Ok(n @ Json::Number(_)) => one(n),
+                    _ => type_error("cannot be parsed as a number"),
+                },
+                _ => type_error("cannot be parsed as a number"),
+            },
+            ("ascii_downcase" | "ascii_upcase", []) => match input {
+                Json::String(s) if name == "ascii_downcase" => one(s.to_ascii_lowercase().into()),
+                Json::String(s) => one(s.to_ascii_uppercase().into()),
+                _ => type_error("cannot be case-converted"),
+            },
+            ("split", [sep]) => self.strings(sep, input, env, |s, sep| {
+                Ok(split(s, sep).into_iter().map(Json::String).collect())
+            }),
+            ("join", [sep]) => {
+                let mut out = Vec::new();
+                for sep in sep.eval(input, env)? {
+                    let Json::String(sep) = sep else {
+                        return self.fail("join separator must be a string".to_string());
+                    };
+                    let mut parts = Vec::new();
+                    for v in self.elements(input)? {
+                        parts.push(match v {
+                            Json::Null => String::new(),
+                            Json::String(s) => s,
+                            Json::Number(_) | Json::Boolean(_) => v.to_string(),
+                            v => return self.fail(format!("Cannot join with {}", type_name(&v))),
+                        });
+                    }
+                    out.push(Json::String(parts.join(&sep)));
+                }
+                Ok(out)
+            }
+            ("test", [re]) => self.strings(re, input, env, |s, re| {
+                Regex::new(re)
+                    .map(|re| Json::Boolean(re.is_match(s)))
+                    .map_err(|_| format!("{re:?} is not a valid regular expression"))
+            }),
+            ("range", [to]) => {
+                let mut out = Vec::new();
+                for to in to.eval(input, env)? {
+                    out.extend(self.range(&Json::Number(0.), &to)?);
+                }
+                Ok(out)
+            }
+            ("range", [from, to]) => {
+                let mut out = Vec::new();
+                for from in from.eval(input, env)? {
+                    for to in to.eval(input, env)? {
+                        out.extend(self.range(&from, &to)?);
+                    }
+                }
+                Ok(out)
+            }
+            ("error", [msg]) => match msg.eval(input, env)?.into_iter().next() {
+                Some(Json::String(s)) => self.fail(s),
+                Some(v) => self.fail(format!("{v} (not a string)")),
+                None => Ok(Vec::new()),
+            },
+            _ => self.fail(format!("{}/{} is not defined", name, args.len())),
+        }
+    }
+
+    fn elements(&self, input: &Json) -> Result<Vec<Json>, FilterError> {
+        match input {
+            Json::Array(a) => Ok(a.clone()),
+            Json::Object(o) => Ok(o.values().cloned().collect()),
+            Json::Null => Ok(Vec::new()),
+            v => self.fail(format!("Cannot iterate over {}", type_name(v))),
+        }
+    }
+
+    fn array<'a>(&self, input: &'a Json) -> Result<&'a Vec<Json>, FilterError> {
+        match input {
+            Json::Array(a) => Ok(a),
+            v => self.fail(format!(
+                "{} cannot be sorted, as it is not an array",
+                type_name(v)
+            )),
+        }
+    }
+
+    fn to_entries(&self, input: &Json) -> Result<Vec<Json>, FilterError> {
+        match input {
+            Json::Object(o) => Ok(o
+                .iter()
+                .map(|(k, v)| {
+                    let mut entry = BTreeMap::new();
+                    entry.insert("key".to_string(), Json::String(k.clone()));
+                    entry.insert("value".to_string(), v.clone());
+                    Json::Object(entry)
+                })
+                .collect()),
+            v => self.fail(format!("{} has no keys", type_name(v))),
+        }
+    }
+
+    fn collect_entries(&self, entries: &[Json]) -> Result<Json, FilterError> {
+        let mut out = BTreeMap::new();
+        for e in entries {
+            let key = ["key", "k", "name", "Name", "Key", "K"]
+                .iter()
+                .find_map(|k| e.get(*k).filter(|v| truthy(v)));
+            let key = match key {
+                Some(Json::String(s)) => s.clone(),
+                Some(v @ (Json::Number(_) | Json::Boolean(_))) => v.to_string(),
+                _ => return self.fail("Cannot use entry without a string key".to_string()),
+            };
+            let value = ["value", "v", "Value", "V"]
+                .iter()
+                .find_map(|k| e.get(*k))
+                .cloned()
+                .unwrap_or_default();
+            out.insert(key, value);
+        }
+        Ok(Json::Object(out))
+    }
+
+    fn strings<F>(
+        &self,
+        arg: &Expr,
+        input: &Json,
+        env: &Env,
+        f: F,
+    ) -> Result<Vec<Json>, FilterError>
+    where
+        F: Fn(&str, &str) -> Result<Json, String>,
+    {
+        let Json::String(s) = input else {
+            return self.fail(format!(
+                "{} cannot be matched, as it is not a string",
+                type_name(input)
+            ));
+        };
+
+        arg.eval(input, env)?
+            .into_iter()
+            .map(|a| match a {
+                Json::String(a) => f(s, &a).or_else(|e| self.fail(e)),
+                a => self.fail(format!("{} is not a string", type_name(&a))),
+            })
+            .collect()
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn range(&self, from: &Json, to: &Json) -> Result<Vec<Json>, FilterError> {
+        match (from, to) {
+            (Json::Number(from), Json::Number(to)) => {
+                if from.abs() > MAX_EXACT || to.abs() > MAX_EXACT {
+                    return self.fail("Range bounds must be within 2^53 of zero".to_string());
+                }
+                // Both are within 2^53 of zero, so the count fits a `u64`
+                // and each step is exact.
+                let count = (to - from).ceil().max(0.) as u64;
+                if count > MAX_RANGE {
+                    return self.fail("Range too long".to_string());
+                }
+                Ok((0..count).map(|i| Json::Number(from + i as f64)).collect())
+            }
+            _ => self.fail("Range bounds must be numeric".to_string()),
+        }
+    }
+}
+
+fn split(s: &str, sep: &str) -> Vec<String> {
+    if s.is_empty() {
+        Vec::new()
+    } else if sep.is_empty() {
+        s.chars().map(String::from).collect()
+    } else {
+        s.split(sep).map(String::from).collect()
+    }
+}
+
+fn deep_merge(l: &Json, r: &Json) -> Json {
+    match (l, r) {
+        (Json::Object(left), Json::Object(right)) => {
+            let mut m = left.clone();
+            for (k, v) in right {
+                let merged = match m.get(k) {
+                    Some(existing) => deep_merge(existing, v),
+                    None => v.clone(),
+                };
+                m.insert(k.clone(), merged);
+            }
+            Json::Object(m)
+        }
+        (_, r) => r.clone(),
+    }
+}
+
+// parsing
+
+/// Build a node spanning both operands.
+fn combine<F>(lhs: Expr, rhs: Expr, kind: F) -> Expr
+where
+    F: FnOnce(Box<Expr>, Box<Expr>) -> Kind,
+{
+    let span = lhs.span.start..rhs.span.end;
+    Expr {
+        kind: kind(Box::new(lhs), Box::new(rhs)),
+        span,
+    }
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    is_ident_start(c) || c.is_ascii_digit()
+}
+
+const KEYWORDS: &[&str] = &["and", "or", "if", "then", "elif", "else", "end", "as"];
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> FilterError {
+        let end = self.peek().map_or(self.pos, |c| self.pos + c.len_utf8());
+        FilterError {
+            message: message.to_string(),
+            span: self.pos..end,
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), FilterError> {
+        self.skip_ws();
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{s}'")))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => self.pos += c.len_utf8(),
+                Some('#') => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Consume a keyword, if the input continues with it as a whole word.
+    fn keyword(&mut self, word: &str) -> bool {
+        self.skip_ws();
+        let rest = self.rest();
+        if rest.starts_with(word) && !rest[word.len()..].chars().next().is_some_and(is_ident_char) {
+            self.pos += word.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        let start = self.pos;
+        if !self.peek().is_some_and(is_ident_start) {
+            return None;
+        }
+        while self.peek().is_some_and(is_ident_char) {
+            self.pos += 1;
+        }
+        Some(self.src[start..self.pos].to_string())
+    }
+
+    fn node(&self, kind: Kind, start: usize) -> Expr {
+        Expr {
+            kind,
+            span: start..self.pos,
+        }
+    }
+
+    fn pipe(&mut self) -> Result<Expr, FilterError> {
+        self.skip_ws();
+        let start = self.pos;
+        let lhs = self.comma()?;
+
+        if self.keyword("as") {
+            self.skip_ws();
+            if !self.eat("$") {
+                return Err(self.error("expected a variable name after 'as'"));
+            }
+            let name = self
+                .ident()
+                .ok_or_else(|| self.error("expected a variable name"))?;
+            self.expect("|")?;
+            let body = self.pipe()?;
+            return Ok(self.node(Kind::Bind(Box::new(lhs), name, Box::new(body)), start));
+        }
+
+        self.skip_ws();
+        if self.rest().starts_with('|') && !self.rest().starts_with("||") {
+            self.pos += 1;
+            let rhs = self.pipe()?;
+            return Ok(combine(lhs, rhs, Kind::Pipe));
+        }
+
+        Ok(lhs)
+    }
+
+    fn comma(&mut self) -> Result<Expr, FilterError> {
+        let mut lhs = self.alternative()?;
+        loop {
+            self.skip_ws();
+            if !self.eat(",") {
+                return Ok(lhs);
+            }
+            let rhs = self.alternative()?;
+            lhs = combine(lhs, rhs, Kind::Comma);
+        }
+    }
+
+    fn alternative(&mut self) -> Result<Expr, FilterError> {
+        let lhs = self.or()?;
+        self.skip_ws();
+        if self.eat("//") {
+            let rhs = self.alternative()?;
+            return Ok(combine(lhs, rhs, Kind::Alternative));
+        }
+        Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut lhs = self.and()?;
+        while self.keyword("or") {
+            let rhs = self.and()?;
+            lhs = combine(lhs, rhs, Kind::Or);
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut lhs = self.comparison()?;
+        while self.keyword("and") {
+            let rhs = self.comparison()?;
+            lhs = combine(lhs, rhs, Kind::And);
+        }
+        Ok(lhs)
+    }
+
+    fn operator(&mut self, ops: &[(&str, BinOp)]) -> Option<BinOp> {
+        self.skip_ws();
+        let rest = self.rest();
+        let found = ops
+            .iter()
+            .find(|(s, _)| rest.starts_with(s) && !(*s == "/" && rest.starts_with("//")))?;
+        self.pos += found.0.len();
+        Some(found.1)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FilterError> {
+        let lhs = self.additive()?;
+        let ops = [
+            ("==", BinOp::Eq),
+            ("!=", BinOp::Ne),
+            ("<=", BinOp::Le),
+            (">=", BinOp::Ge),
+            ("<", BinOp::Lt),
+            (">", BinOp::Gt),
+        ];
+        match self.operator(&ops) {
+            Some(op) => {
+                let rhs = self.additive()?;
+                Ok(combine(lhs, rhs, |l, r| Kind::Binary(op, l, r)))
+            }
+            None => Ok(lhs),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, FilterError> {
+        let mut lhs = self.multiplicative()?;
+        while let Some(op) = self.operator(&[("+", BinOp::Add), ("-", BinOp::Sub)]) {
+            let rhs = self.multiplicative()?;
+            lhs = combine(lhs, rhs, |l, r| Kind::Binary(op, l, r));
+        }
+        Ok(lhs)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, FilterError> {
+        let mut lhs = self.unary()?;
+        let ops = [("*", BinOp::Mul), ("/", BinOp::Div), ("%", BinOp::Rem)];
+        while let Some(op) = self.operator(&ops) {
+            let rhs = self.unary()?;
+            lhs = combine(lhs, rhs, |l, r| Kind::Binary(op, l, r));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr, FilterError> {
+        self.skip_ws();
+        let start = self.pos;
+        if self.eat("-") {
+            let e = self.unary()?;
+            return Ok(self.node(Kind::Negate(Box::new(e)), start));
+        }
+        self.postfix()
+    }
+
+    fn postfix(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+        let mut e = self.primary()?;
+
+        loop {
+            if self.rest().starts_with('.')
+                && !self.rest().starts_with("..")
+                && self.rest()[1..]
+                    .chars()
+                    .next()
+                    .is_some_and(|c| is_ident_start(c) || c == '"' || c == '[')
+            {
+                self.pos += 1;
+                if self.peek() != Some('[') {
+                    let key = self.field_name()?;
+                    e = self.node(Kind::Index(Box::new(e), Box::new(key)), start);
+                    continue;
+                }
+            }
+
+            if self.peek() == Some('[') {
+                e = self.bracket(e, start)?;
+            } else if self.eat("?") {
+                e = self.node(Kind::Optional(Box::new(e)), start);
+            } else {
+                return Ok(e);
+            }
+        }
+    }
+
+    fn field_name(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+        if self.peek() == Some('"') {
+            return self.string();
+        }
+        let name = self
+            .ident()
+            .ok_or_else(|| self.error("expected a field name"))?;
+        Ok(self.node(Kind::Literal(Json::String(name)), start))
+    }
+
+    fn bracket(&mut self, target: Expr, start: usize) -> Result<Expr, FilterError> {
+        self.pos += 1;
+        self.skip_ws();
+
+        if self.eat("]") {
+            return Ok(self.node(Kind::Iterate(Box::new(target)), start));
+        }
+
+        let from = if self.peek() == Some(':') {
+            None
+        } else {
+            Some(Box::new(self.pipe()?))
+        };
+
+        self.skip_ws();
+        if self.eat(":") {
+            self.skip_ws();
+            let to = if self.peek() == Some(']') {
+                None
+            } else {
+                Some(Box::new(self.pipe()?))
+            };
+            self.expect("]")?;
+            return Ok(self.node(Kind::Slice(Box::new(target), from, to), start));
+        }
+
+        self.expect("]")?;
+        let index = from.ok_or_else(|| self.error("expected an index"))?;
+        Ok(self.node(Kind::Index(Box::new(target), index), start))
+    }
+
+    fn primary(&mut self) -> Result<Expr, FilterError> {
+        self.skip_ws();
+        let start = self.pos;
+
+        match self.peek() {
+            Some('.') => {
+                if self.eat("..") {
+                    return Ok(self.node(Kind::Recurse, start));
+                }
+                self.pos += 1;
+                let identity = self.node(Kind::Identity, start);
+                match self.peek() {
+                    Some(c) if is_ident_start(c) || c == '"' => {
+                        let key = self.field_name()?;
+                        Ok(self.node(Kind::Index(Box::new(identity), Box::new(key)), start))
+                    }
+                    _ => Ok(identity),
+                }
+            }
+            Some('$') => {
+                self.pos += 1;
+                let name = self
+                    .ident()
+                    .ok_or_else(|| self.error("expected a variable name"))?;
+                Ok(self.node(Kind::Variable(name), start))
+            }
+            Some('"') => self.string(),
+            Some(c) if c.is_ascii_digit() => self.number(),
+            Some('(') => {
+                self.pos += 1;
+                let e = self.pipe()?;
+                self.expect(")")?;
+                Ok(Expr {
+                    kind: e.kind,
+                    span: start..self.pos,
+                })
+            }
+            Some('[') => {
+                self.pos += 1;
+                self.skip_ws();
+                if self.eat("]") {
+                    return Ok(self.node(Kind::Array(None), start));
+                }
+                let e = self.pipe()?;
+                self.expect("]")?;
+                Ok(self.node(Kind::Array(Some(Box::new(e))), start))
+            }
+            Some('{') => self.object(),
+            Some(c) if is_ident_start(c) => self.word(),
+            _ => Err(self.error("expected an expression")),
+        }
+    }
+
+    fn word(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+
+        if self.keyword("if") {
+            return self.conditional(start);
+        }
+
+        let name = self.ident().expect("word starts with an identifier");
+        let literal = match name.as_str() {
+            "true" => Some(Json::Boolean(true)),
+            "false" => Some(Json::Boolean(false)),
+            "null" => Some(Json::Null),
+            _ => None,
+        };
+        if let Some(j) = literal {
+            return Ok(self.node(Kind::Literal(j), start));
+        }
+        if KEYWORDS.contains(&name.as_str()) {
+            self.pos = start;
+            return Err(self.error(&format!("unexpected keyword '{name}'")));
+        }
+
+        let mut args = Vec::new();
+        if self.eat("(") {
+            loop {
+                args.push(self.pipe()?);
+                self.skip_ws();
+                if self.eat(")") {
+                    break;
+                }
+                if !self.eat(";") {
+                    return Err(self.error("expected ';' or ')'"));
+                }
+            }
+        }
+
+        if !BUILTINS.contains(&(name.as_str(), args.len())) {
+            return Err(FilterError {
+                message: format!("{}/{} is not defined", name, args.len()),
+                span: start..self.pos,
+            });
+        }
+
+        Ok(self.node(Kind::Call(name, args), start))
+    }
+
+    fn conditional(&mut self, start: usize) -> Result<Expr, FilterError> {
+        let cond = self.pipe()?;
+        if !self.keyword("then") {
+            return Err(self.error("expected 'then'"));
+        }
+        let then = self.pipe()?;
+
+        let otherwise = if self.keyword("elif") {
+            let nested_start = self.pos;
+            Some(Box::new(self.conditional(nested_start)?))
+        } else if self.keyword("else") {
+            let e = self.pipe()?;
+            if !self.keyword("end") {
+                return Err(self.error("expected 'end'"));
+            }
+            Some(Box::new(e))
+        } else if self.keyword("end") {
+            None
+        } else {
+            return Err(self.error("expected 'elif', 'else' or 'end'"));
+        };
+
+        Ok(self.node(Kind::If(Box::new(cond), Box::new(then), otherwise), start))
+    }
+
+    fn object(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut entries = Vec::new();
+
+        self.skip_ws();
+        if self.eat("}") {
+            return Ok(self.node(Kind::Object(entries), start));
+        }
+
+        loop {
+            self.skip_ws();
+            let key_start = self.pos;
+
+            let (key, shorthand) = match self.peek() {
+                Some('$') => {
+                    self.pos += 1;
+                    let name = self
+                        .ident()
+                        .ok_or_else(|| self.error("expected a variable name"))?;
+                    let value = self.node(Kind::Variable(name.clone()), key_start);
+                    (
+                        self.node(Kind::Literal(Json::String(name)), key_start),
+                        Some(value),
+                    )
+                }
+                Some('"') => {
+                    let key = self.string()?;
+                    let identity = self.node(Kind::Identity, key_start);
+                    let key_copy = Parser {
+                        src: self.src,
+                        pos: key_start,
+                    }
+                    .string()?;
+                    let value = self.node(
+                        Kind::Index(Box::new(identity), Box::new(key_copy)),
+                        key_start,
+                    );
+                    (key, Some(value))
+                }
+                Some('(') => {
+                    self.pos += 1;
+                    let key = self.pipe()?;
+                    self.expect(")")?;
+                    (key, None)
+                }
+                Some(c) if is_ident_start(c) => {
+                    let name = self.ident().expect("checked for an identifier");
+                    let key = self.node(Kind::Literal(Json::String(name.clone())), key_start);
+                    let identity = self.node(Kind::Identity, key_start);
+                    let field = self.node(Kind::Literal(Json::String(name)), key_start);
+                    let value =
+                        self.node(Kind::Index(Box::new(identity), Box::new(field)), key_start);
+                    (key, Some(value))
+                }
+                _ => return Err(self.error("expected an object key")),
+            };
+
+            self.skip_ws();
+            let value = if self.eat(":") {
+                self.alternative()?
+            } else if let Some(value) = shorthand {
+                value
+            } else {
+                return Err(self.error("expected ':' after a computed key"));
+            };
+            entries.push((key, value));
+
+            self.skip_ws();
+            if self.eat("}") {
+                return Ok(self.node(Kind::Object(entries), start));
+            }
+            if !self.eat(",") {
+                return Err(self.error("expected ',' or '}'"));
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        if self.rest().starts_with('.')
+            && self.rest()[1..].starts_with(|c: char| c.is_ascii_digit())
+        {
+            self.pos += 1;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+        if self.eat("e") || self.eat("E") {
+            let _ = self.eat("+") || self.eat("-");
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+        }
+
+        match self.src[start..self.pos].parse() {
+            Ok(n) => Ok(self.node(Kind::Literal(Json::Number(n)), start)),
+            Err(_) => Err(FilterError {
+                message: "invalid number".to_string(),
+                span: start..self.pos,
+            }),
+        }
+    }
+
+    fn string(&mut self) -> Result<Expr, FilterError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut parts = Vec::new();
+        let mut current = String::new();
+
+        loop {
+            match self.bump() {
+                None => {
+                    return Err(FilterError {
+                        message: "unterminated string".to_string(),
+                        span: start..self.pos,
+                    })
+                }
+                Some('"') => break,
+                Some('\\') => {
+                    let escape = self.pos - 1;
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let hex = self.rest().get(..4).unwrap_or_default();
+                            let c = u32::from_str_radix(hex, 16)
+                                .ok()
+                                .and_then(std::char::from_u32);
+                            match c {
+                                Some(c) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                                    self.pos += 4;
+                                    c
+                                }
+                                _ => {
+                                    return Err(FilterError {
+                                        message: "invalid unicode escape".to_string(),
+                                        span: escape..self.pos,
+                                    })
+                                }
+                            }
+                        }
+                        Some('(') => {
+                            if !current.is_empty() {
+                                parts.push(Part::Literal(std::mem::take(&mut current)));
+                            }
+                            let e = self.pipe()?;
+                            self.expect(")")?;
+                            parts.push(Part::Interpolated(e));
+                            continue;
+                        }
+                        _ => {
+                            return Err(FilterError {
+                                message: "invalid escape".to_string(),
+                                span: escape..self.pos,
+                            })
+                        }
+                    };
+                    current.push(c);
+                }
+                Some(c) => current.push(c),
+            }
+        }
+
+        if parts.is_empty() {
+            return Ok(self.node(Kind::Literal(Json::String(current)), start));
+        }
+        if !current.is_empty() {
+            parts.push(Part::Literal(current));
+        }
+        Ok(self.node(Kind::String(parts), start))
+    }
+}
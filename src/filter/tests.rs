@@ -0,0 +1,248 @@
+#![cfg(test)]
+
+use super::*;
+
+fn run(filter: &str, input: &Json) -> Vec<Json> {
+    filter.parse::<Filter>().unwrap().run(input).unwrap()
+}
+
+fn doc() -> Json {
+    json!({
+        "name": "widgets",
+        "items": [
+            {"id": 1, "price": 2.5, "tags": ["a", "b"]},
+            {"id": 2, "price": 10, "tags": []},
+            {"id": 3, "price": null, "tags": ["c"]}
+        ]
+    })
+}
+
+#[test]
+fn paths() {
+    let doc = doc();
+    assert_eq!(run(".", &doc), vec![doc.clone()]);
+    assert_eq!(run(".name", &doc), vec![json!("widgets")]);
+    assert_eq!(run(".items[0].id", &doc), vec![json!(1)]);
+    assert_eq!(run(".items[-1].id", &doc), vec![json!(3)]);
+    assert_eq!(run(".[\"name\"]", &doc), vec![json!("widgets")]);
+    assert_eq!(run(".\"name\"", &doc), vec![json!("widgets")]);
+    assert_eq!(run(".missing.deeper", &doc), vec![json!(null)]);
+    assert_eq!(run(".items[].id", &doc), vec![json!(1), json!(2), json!(3)]);
+    assert_eq!(run(".items[1:] | map(.id)", &doc), vec![json!([2, 3])]);
+    assert_eq!(run(".items[:1] | length", &doc), vec![json!(1)]);
+    assert_eq!(run(".name[1:3]", &doc), vec![json!("id")]);
+    assert_eq!(run("[..] | length", &json!([[1]])), vec![json!(3)]);
+}
+
+#[test]
+fn pipes_and_commas() {
+    let doc = doc();
+    assert_eq!(
+        run(".items[] | .id", &doc),
+        vec![json!(1), json!(2), json!(3)]
+    );
+    assert_eq!(
+        run(".name, .items[0].id", &doc),
+        vec![json!("widgets"), json!(1)]
+    );
+    assert_eq!(
+        run("[.items[] | .tags[]]", &doc),
+        vec![json!(["a", "b", "c"])]
+    );
+    assert_eq!(run(".items[0] as $x | $x.id", &doc), vec![json!(1)]);
+}
+
+#[test]
+fn construction() {
+    let doc = doc();
+    assert_eq!(
+        run(
+            ".items[0] | {id, cost: .price, \"n\": 1, (.tags[0]): true}",
+            &doc
+        ),
+        vec![json!({"id": 1, "cost": 2.5, "n": 1, "a": true})]
+    );
+    assert_eq!(
+        run("{a: (1, 2)}", &Json::Null),
+        vec![json!({"a": 1}), json!({"a": 2})]
+    );
+    assert_eq!(run("1 as $v | {$v}", &Json::Null), vec![json!({"v": 1})]);
+    assert_eq!(run("[]", &Json::Null), vec![json!([])]);
+    assert_eq!(run("{}", &Json::Null), vec![json!({})]);
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(run("1 + 2 * 3 - 4 / 2", &Json::Null), vec![json!(5)]);
+    assert_eq!(
+        run("7 % 3, -(1 + 1)", &Json::Null),
+        vec![json!(1), json!(-2)]
+    );
+    assert_eq!(run("\"a\" + \"b\"", &Json::Null), vec![json!("ab")]);
+    assert_eq!(run("[1, 2, 3] - [2]", &Json::Null), vec![json!([1, 3])]);
+    assert_eq!(
+        run("{a: 1} + {b: 2}", &Json::Null),
+        vec![json!({"a": 1, "b": 2})]
+    );
+    assert_eq!(
+        run("{a: {b: 1}} * {a: {c: 2}}", &Json::Null),
+        vec![json!({"a": {"b": 1, "c": 2}})]
+    );
+    assert_eq!(run("null + 1", &Json::Null), vec![json!(1)]);
+    assert_eq!(run("\"a,b\" / \",\"", &Json::Null), vec![json!(["a", "b"])]);
+}
+
+#[test]
+fn logic_and_alternative() {
+    let doc = doc();
+    assert_eq!(
+        run(".items[] | select(.price > 2 and .price < 5) | .id", &doc),
+        vec![json!(1)]
+    );
+    assert_eq!(
+        run("[.items[] | .price // 0]", &doc),
+        vec![json!([2.5, 10, 0])]
+    );
+    assert_eq!(
+        run("false or null, (1 | not)", &Json::Null),
+        vec![json!(false), json!(false)]
+    );
+    assert_eq!(
+        run("[1, \"1\", null] | sort", &Json::Null),
+        vec![json!([null, 1, "1"])]
+    );
+    assert_eq!(
+        run(
+            "if . == 1 then \"one\" elif . == 2 then \"two\" else \"many\" end",
+            &json!(2)
+        ),
+        vec![json!("two")]
+    );
+    assert_eq!(run(".a? // \"none\"", &json!(1)), vec![json!("none")]);
+}
+
+#[test]
+fn interpolation() {
+    let doc = doc();
+    assert_eq!(
+        run(".items[0] | \"#\\(.id): \\(.tags | length) tags\"", &doc),
+        vec![json!("#1: 2 tags")]
+    );
+    assert_eq!(
+        run("\"\\((1, 2))\"", &Json::Null),
+        vec![json!("1"), json!("2")]
+    );
+    assert_eq!(run("\"\\u00e9\\t\"", &Json::Null), vec![json!("\u{e9}\t")]);
+}
+
+#[test]
+fn builtins() {
+    let doc = doc();
+    assert_eq!(run("keys", &doc), vec![json!(["items", "name"])]);
+    assert_eq!(run(".items | length", &doc), vec![json!(3)]);
+    assert_eq!(run(".items | map(.id * 2)", &doc), vec![json!([2, 4, 6])]);
+    assert_eq!(run("[.items[].id] | add", &doc), vec![json!(6)]);
+    assert_eq!(run(".items[0] | has(\"tags\")", &doc), vec![json!(true)]);
+    assert_eq!(
+        run(".items | sort_by(.price) | map(.id)", &doc),
+        vec![json!([3, 1, 2])]
+    );
+    assert_eq!(
+        run("{a: 1} | to_entries", &Json::Null),
+        vec![json!([{"key": "a", "value": 1}])]
+    );
+    assert_eq!(
+        run(
+            "with_entries({key: .key, value: (.value + 1)})",
+            &json!({"a": 1})
+        ),
+        vec![json!({"a": 2})]
+    );
+    assert_eq!(run("[range(3)]", &Json::Null), vec![json!([0, 1, 2])]);
+    assert_eq!(
+        run("[range(0.5; 3)]", &Json::Null),
+        vec![json!([0.5, 1.5, 2.5])]
+    );
+    assert_eq!(
+        run("[.[] | tostring] | join(\"-\")", &json!([1, "x"])),
+        vec![json!("1-x")]
+    );
+    assert_eq!(
+        run("map(test(\"^a\"))", &json!(["ab", "ba"])),
+        vec![json!([true, false])]
+    );
+    assert_eq!(
+        run("[.[] | type]", &json!([null, {}])),
+        vec![json!(["null", "object"])]
+    );
+    assert_eq!(run("unique", &json!([2, 1, 2])), vec![json!([1, 2])]);
+    assert_eq!(run("[empty, 1]", &Json::Null), vec![json!([1])]);
+}
+
+#[test]
+fn runtime_errors() {
+    let filter: Filter = ".a | .[0]".parse().unwrap();
+    let err = filter.run(&json!({"a": {"b": 1}})).unwrap_err();
+    assert_eq!(err.message, "Cannot index object with number");
+    assert_eq!(err.span, 5..9);
+
+    let filter: Filter = "1 / (. - 1)".parse().unwrap();
+    assert_eq!(filter.run(&json!(1)).unwrap_err().span, 0..11);
+
+    let filter: Filter = ". * 1e19".parse().unwrap();
+    assert_eq!(
+        filter.run(&json!("ab")).unwrap_err().message,
+        "Repeat string result too long"
+    );
+    let filter: Filter = "[range(1e15)]".parse().unwrap();
+    assert_eq!(
+        filter.run(&Json::Null).unwrap_err().message,
+        "Range too long"
+    );
+    let filter: Filter = "[range(1e16; 1e16 + 2)]".parse().unwrap();
+    assert!(filter.run(&Json::Null).is_err());
+
+    let filter: Filter = ".[]".parse().unwrap();
+    assert!(filter.run(&json!(true)).is_err());
+    assert_eq!(
+        ".[]?".parse::<Filter>().unwrap().run(&json!(true)),
+        Ok(Vec::new())
+    );
+}
+
+#[test]
+fn reject_invalid() {
+    for bad in &[
+        "",
+        ".a |",
+        "[1, 2",
+        "{a: 1",
+        "{(1)}",
+        ".[",
+        "\"abc",
+        "\"\\q\"",
+        "nope",
+        "map",
+        "length(1)",
+        "if . then 1",
+        "1 +",
+        ". as x | x",
+        ")",
+    ] {
+        assert!(
+            bad.parse::<Filter>().is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
+
+#[test]
+fn error_spans() {
+    let err = ".a | nope(1)".parse::<Filter>().unwrap_err();
+    assert_eq!(err.message, "nope/1 is not defined");
+    assert_eq!(err.span, 5..12);
+
+    let err = "[1, 2 3]".parse::<Filter>().unwrap_err();
+    assert_eq!(err.span, 6..7);
+}
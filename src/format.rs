@@ -0,0 +1,768 @@
+//! Printing a [`Json`] as text, driven by [`FormatOptions`].
+//!
+//! ```
+//! use jval::{json, FormatOptions};
+//!
+//! let doc = json!({"name": "jval", "tags": ["json"], "sizes": [1, [2]]});
+//! let mut out = Vec::new();
+//! doc.print(&FormatOptions::spaces(2), &mut out).unwrap();
+//! assert_eq!(
+//!     String::from_utf8(out).unwrap(),
+//!     "{\n  \"name\": \"jval\",\n  \"sizes\": [\n    1,\n    [ 2 ]\n  ],\n  \"tags\": [ \"json\" ]\n}"
+//! );
+//!
+//! let options = FormatOptions::compact().space_after_comma(true);
+//! assert_eq!(doc.to_string_with(&options).unwrap(), r#"{"name":"jval", "sizes":[1, [2]], "tags":["json"]}"#);
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::{io, mem};
+
+use super::color::{Colors, Kind};
+use super::pointer;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// What to print for a `NaN` or infinite number, which JSON can't represent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonFinite {
+    /// Print `null`, as `JSON.stringify` does.
+    #[default]
+    Null,
+    /// Refuse to print the value.
+    Error,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Newline {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl Newline {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The order object members are printed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// By the UTF-8 bytes of their names, the order objects are stored in.
+    #[default]
+    Utf8,
+    /// By the UTF-16 code units of their names, as RFC 8785 and
+    /// JavaScript's sort do.
+    Utf16,
+}
+
+/// Which single-element arrays an indented printer keeps on one line, as
+/// `[ element ]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InlineSingle {
+    /// Break every non-empty array across lines.
+    Never,
+    /// Inline an array whose element is a scalar or an empty container.
+    #[default]
+    Scalar,
+    /// Inline every single-element array, printing its element compactly.
+    Always,
+}
+
+/// How [`Json::print`] lays out a value.
+///
+/// Start from [`FormatOptions::compact`], [`FormatOptions::tabs`] or
+/// [`FormatOptions::spaces`] and adjust the rest with the builder methods.
+/// The default is compact.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct FormatOptions {
+    indent: Option<String>,
+    pub(crate) newline: Newline,
+    space_after_colon: bool,
+    space_after_comma: bool,
+    key_order: KeyOrder,
+    pub(crate) trailing_newline: bool,
+    inline_single: InlineSingle,
+    max_width: Option<usize>,
+    table_align: bool,
+    colors: Option<Colors>,
+    ascii: bool,
+    pub(crate) non_finite: NonFinite,
+}
+
+impl FormatOptions {
+    /// Everything on one line, with no whitespace.
+    #[must_use]
+    pub fn compact() -> Self {
+        FormatOptions::default()
+    }
+
+    /// One element or member per line, indented with tabs.
+    #[must_use]
+    pub fn tabs() -> Self {
+        FormatOptions::compact().indent("\t")
+    }
+
+    /// One element or member per line, indented with `n` spaces.
+    #[must_use]
+    pub fn spaces(n: usize) -> Self {
+        FormatOptions::compact().indent(" ".repeat(n))
+    }
+
+    /// Put each element or member on its own line, prefixed by `indent`
+    /// once per level of nesting, with a space after each colon.
+    #[must_use]
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = Some(indent.into());
+        self.space_after_colon = true;
+        self
+    }
+
+    #[must_use]
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    #[must_use]
+    pub fn space_after_colon(mut self, space: bool) -> Self {
+        self.space_after_colon = space;
+        self
+    }
+
+    /// Put a space after each comma; only affects compact output, where
+    /// commas aren't followed by a newline.
+    #[must_use]
+    pub fn space_after_comma(mut self, space: bool) -> Self {
+        self.space_after_comma = space;
+        self
+    }
+
+    #[must_use]
+    pub fn key_order(mut self, order: KeyOrder) -> Self {
+        self.key_order = order;
+        self
+    }
+
+    /// End the output with a newline.
+    #[must_use]
+    pub fn trailing_newline(mut self, newline: bool) -> Self {
+        self.trailing_newline = newline;
+        self
+    }
+
+    #[must_use]
+    pub fn inline_single_element(mut self, inline: InlineSingle) -> Self {
+        self.inline_single = inline;
+        self
+    }
+
+    /// Keep an array or object on one line, as `[1, 2]` or `{"a": 1}`, when
+    /// it fits within `width` columns, and break it across lines otherwise.
+    /// Only affects indented output; a tab counts as 8 columns.
+    #[must_use]
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Print an array of similar objects as a table: one object per line,
+    /// with the members of each name lined up in a column. Objects are
+    /// similar when they share a name and each has at least half of all
+    /// the names. Only affects indented output, and gives way to
+    /// [`FormatOptions::max_width`] when the lines would be too long.
+    #[must_use]
+    pub fn table_align(mut self, align: bool) -> Self {
+        self.table_align = align;
+        self
+    }
+
+    /// Style each token for a terminal with escape sequences.
+    #[must_use]
+    pub fn colors(mut self, colors: Colors) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Escape every non-ASCII character in strings as `\uXXXX`.
+    #[must_use]
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    #[must_use]
+    pub fn non_finite(mut self, non_finite: NonFinite) -> Self {
+        self.non_finite = non_finite;
+        self
+    }
+}
+
+impl Json {
+    /// Print the value to `f`, laid out according to `options`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails, or with [`io::ErrorKind::InvalidData`] if the
+    /// value contains a `NaN` or infinite number and `options` don't allow
+    /// them.
+    pub fn print<W: io::Write>(&self, options: &FormatOptions, f: &mut W) -> io::Result<()> {
+        check_finite(self, options)?;
+        let mut buffered = io::BufWriter::new(f);
+        let mut out = IoWrite {
+            inner: &mut buffered,
+            error: None,
+        };
+        if write_formatted(&mut out, self, options).is_err() {
+            return Err(out
+                .error
+                .unwrap_or_else(|| io::Error::other("formatter error")));
+        }
+        io::Write::flush(&mut buffered)
+    }
+
+    /// Like [`Json::print`], but into a `String`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the value contains a
+    /// `NaN` or infinite number and `options` don't allow them.
+    pub fn to_string_with(&self, options: &FormatOptions) -> io::Result<String> {
+        check_finite(self, options)?;
+        let mut out = String::new();
+        let _ = write_formatted(&mut out, self, options);
+        Ok(out)
+    }
+}
+
+/// Fail if `value` contains a `NaN` or infinite number that `options`
+/// don't allow.
+pub(crate) fn check_finite(value: &Json, options: &FormatOptions) -> io::Result<()> {
+    if options.non_finite == NonFinite::Error {
+        if let Some(path) = find_non_finite(value) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("non-finite number at \"{path}\""),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Write `value` laid out according to `options`, printing any non-finite
+/// numbers as `null`.
+pub(crate) fn write_formatted<W: Write>(
+    out: &mut W,
+    value: &Json,
+    options: &FormatOptions,
+) -> fmt::Result {
+    let mut printer = Printer::new(out, options);
+    printer.value(value, 0)?;
+    if options.trailing_newline {
+        printer.out.write_str(options.newline.as_str())?;
+    }
+    Ok(())
+}
+
+/// Adapts an [`io::Write`] for the printer, keeping the error that stopped
+/// it.
+pub(crate) struct IoWrite<'a, W> {
+    pub(crate) inner: &'a mut W,
+    pub(crate) error: Option<io::Error>,
+}
+
+impl<W: io::Write> Write for IoWrite<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
+pub(crate) struct Printer<'a, W> {
+    pub(crate) out: &'a mut W,
+    options: &'a FormatOptions,
+    /// The indent, or `None` while printing on one line.
+    indent: Option<&'a str>,
+    space_after_comma: bool,
+    /// The colors, or `None` while measuring.
+    colors: Option<&'a Colors>,
+    /// The column the next value starts at.
+    pub(crate) column: usize,
+    /// The width of what follows the next value on its line: a comma, or
+    /// nothing before a line break.
+    pub(crate) suffix: usize,
+    /// A line break followed by the indent repeated for the deepest level
+    /// so far, so each line starts with a single write.
+    line: String,
+}
+
+/// The columns a tab takes up when fitting to a [`FormatOptions::max_width`].
+const TAB_WIDTH: usize = 8;
+
+fn width_of(s: &str) -> usize {
+    s.chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum()
+}
+
+/// A writer that only counts columns, failing once there are more than
+/// `limit`.
+struct Measure {
+    width: usize,
+    limit: usize,
+}
+
+impl Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += width_of(s);
+        if self.width > self.limit {
+            return Err(fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+/// The width of what `print` prints on one line, if it's at most `limit`.
+fn measure(
+    options: &FormatOptions,
+    limit: usize,
+    print: impl FnOnce(&mut Printer<Measure>) -> fmt::Result,
+) -> Option<usize> {
+    let mut measure = Measure { width: 0, limit };
+    let mut printer = Printer {
+        out: &mut measure,
+        options,
+        indent: None,
+        space_after_comma: true,
+        colors: None,
+        column: 0,
+        suffix: 0,
+        line: String::new(),
+    };
+    print(&mut printer).ok()?;
+    Some(measure.width)
+}
+
+impl<'a, W: Write> Printer<'a, W> {
+    pub(crate) fn new(out: &'a mut W, options: &'a FormatOptions) -> Self {
+        Printer {
+            out,
+            options,
+            indent: options.indent.as_deref(),
+            space_after_comma: options.space_after_comma,
+            colors: options.colors.as_ref(),
+            column: 0,
+            suffix: 0,
+            line: options.newline.as_str().to_owned(),
+        }
+    }
+
+    pub(crate) fn value(&mut self, value: &Json, depth: usize) -> fmt::Result {
+        match value {
+            Json::Null => self.punct(Kind::Null, "null"),
+            Json::Boolean(true) => self.punct(Kind::True, "true"),
+            Json::Boolean(false) => self.punct(Kind::False, "false"),
+            Json::Number(n) => self.paint(Kind::Number, |out| write_number(out, *n)),
+            Json::String(s) => {
+                let ascii = self.options.ascii;
+                self.paint(Kind::String, |out| write_string(out, s, ascii))
+            }
+            Json::Array(a) if a.is_empty() => self.punct(Kind::Array, "[]"),
+            Json::Object(o) if o.is_empty() => self.punct(Kind::Object, "{}"),
+            Json::Array(a) if self.options.table_align && self.indent.is_some() => {
+                match self.table_columns(a, depth) {
+                    Some(columns) => self.table(a, &columns, depth),
+                    None => self.container(value, depth),
+                }
+            }
+            Json::Array(_) | Json::Object(_) => self.container(value, depth),
+        }
+    }
+
+    /// Print a non-empty container, on one line if it fits.
+    fn container(&mut self, value: &Json, depth: usize) -> fmt::Result {
+        match value {
+            _ if self.fits(value) => self.flat(|p| p.value(value, depth)),
+            Json::Array(a) if a.len() == 1 && self.inline(&a[0]) => {
+                let indent = self.indent.take();
+                self.punct(Kind::Array, "[ ")?;
+                self.value(&a[0], depth)?;
+                self.indent = indent;
+                self.punct(Kind::Array, " ]")
+            }
+            Json::Array(a) => {
+                self.punct(Kind::Array, "[")?;
+                for (i, item) in a.iter().enumerate() {
+                    self.separator(i, depth + 1, Kind::Array)?;
+                    self.suffix = usize::from(i + 1 < a.len());
+                    self.value(item, depth + 1)?;
+                }
+                self.close(depth)?;
+                self.punct(Kind::Array, "]")
+            }
+            Json::Object(o) => {
+                self.punct(Kind::Object, "{")?;
+                match self.options.key_order {
+                    KeyOrder::Utf8 => self.members(o.iter(), o.len(), depth)?,
+                    KeyOrder::Utf16 => {
+                        let mut members: Vec<_> = o.iter().collect();
+                        members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+                        self.members(members.into_iter(), o.len(), depth)?;
+                    }
+                }
+                self.close(depth)?;
+                self.punct(Kind::Object, "}")
+            }
+            _ => self.value(value, depth),
+        }
+    }
+
+    /// Run `print` with everything on one line.
+    fn flat(&mut self, print: impl FnOnce(&mut Self) -> fmt::Result) -> fmt::Result {
+        let indent = self.indent.take();
+        let space_after_comma = mem::replace(&mut self.space_after_comma, true);
+        let result = print(self);
+        self.indent = indent;
+        self.space_after_comma = space_after_comma;
+        result
+    }
+
+    /// Write `text` in the style for `kind`.
+    pub(crate) fn punct(&mut self, kind: Kind, text: &str) -> fmt::Result {
+        self.paint(kind, |out| out.write_str(text))
+    }
+
+    /// Run `print` in the style for `kind`.
+    fn paint(&mut self, kind: Kind, print: impl FnOnce(&mut W) -> fmt::Result) -> fmt::Result {
+        let style = self.colors.map_or("", |c| c.style(kind));
+        if style.is_empty() {
+            return print(self.out);
+        }
+        write!(self.out, "\u{1b}[{style}m")?;
+        print(self.out)?;
+        self.out.write_str("\u{1b}[0m")
+    }
+
+    /// Write a member name and the colon after it.
+    pub(crate) fn key(&mut self, key: &str) -> fmt::Result {
+        let ascii = self.options.ascii;
+        self.paint(Kind::Key, |out| write_string(out, key, ascii))?;
+        self.punct(Kind::Object, ":")?;
+        if self.options.space_after_colon {
+            self.out.write_char(' ')?;
+        }
+        if self.options.max_width.is_some() && self.indent.is_some() {
+            self.column += measure(self.options, usize::MAX, |p| p.key(key)).unwrap_or(0);
+        }
+        Ok(())
+    }
+
+    fn members<'v>(
+        &mut self,
+        members: impl Iterator<Item = (&'v String, &'v Json)>,
+        len: usize,
+        depth: usize,
+    ) -> fmt::Result {
+        for (i, (key, member)) in members.enumerate() {
+            self.separator(i, depth + 1, Kind::Object)?;
+            self.key(key)?;
+            self.suffix = usize::from(i + 1 < len);
+            self.value(member, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `value`, starting at the current column, fits on the rest of
+    /// the line.
+    fn fits(&self, value: &Json) -> bool {
+        let (Some(width), Some(_)) = (self.options.max_width, self.indent) else {
+            return false;
+        };
+        width
+            .checked_sub(self.column + self.suffix)
+            .and_then(|limit| measure(self.options, limit, |p| p.value(value, 0)))
+            .is_some()
+    }
+
+    /// The names of the columns to print `records` in, with the width of
+    /// the widest member with each name, if they're similar enough objects
+    /// for a table.
+    fn table_columns<'v>(
+        &self,
+        records: &'v [Json],
+        depth: usize,
+    ) -> Option<Vec<(&'v String, usize)>> {
+        if records.len() < 2 {
+            return None;
+        }
+
+        let mut widths = BTreeMap::new();
+        for record in records {
+            let object = record.as_object().filter(|o| !o.is_empty())?;
+            for (key, value) in object {
+                let width = measure(self.options, usize::MAX, |p| {
+                    p.key(key)?;
+                    p.value(value, 0)
+                })?;
+                let widest = widths.entry(key).or_insert(0);
+                *widest = width.max(*widest);
+            }
+        }
+
+        let objects = || records.iter().filter_map(Json::as_object);
+        let shared = widths
+            .keys()
+            .any(|key| objects().all(|o| o.contains_key(*key)));
+        if !shared || objects().any(|o| o.len() * 2 < widths.len()) {
+            return None;
+        }
+
+        let mut columns: Vec<_> = widths.into_iter().collect();
+        if self.options.key_order == KeyOrder::Utf16 {
+            columns.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+        }
+
+        if let Some(max) = self.options.max_width {
+            let indent = self.indent.map_or(0, width_of);
+            let cells: usize = columns.iter().map(|(_, w)| w + 2).sum();
+            // The indent, braces, cells and their separators, and a comma.
+            if (depth + 1) * indent + cells + 1 > max {
+                return None;
+            }
+        }
+        Some(columns)
+    }
+
+    /// Print `records`, one per line, with their members in `columns`.
+    fn table(
+        &mut self,
+        records: &[Json],
+        columns: &[(&String, usize)],
+        depth: usize,
+    ) -> fmt::Result {
+        self.punct(Kind::Array, "[")?;
+        for (i, record) in records.iter().enumerate() {
+            self.separator(i, depth + 1, Kind::Array)?;
+            self.punct(Kind::Object, "{")?;
+            // Where the next column and the line so far end, after the `{`.
+            let (mut start, mut end) = (0, 0);
+            for (key, width) in columns {
+                if let Some(value) = record.get(key.as_str()) {
+                    if end > 0 {
+                        self.punct(Kind::Object, ",")?;
+                        end += 1;
+                    }
+                    (end..start).try_for_each(|_| self.out.write_char(' '))?;
+                    self.flat(|p| {
+                        p.key(key)?;
+                        p.value(value, depth + 1)
+                    })?;
+                    let width = measure(self.options, usize::MAX, |p| {
+                        p.key(key)?;
+                        p.value(value, 0)
+                    });
+                    end = start + width.unwrap_or(0);
+                }
+                start += width + 2;
+            }
+            self.punct(Kind::Object, "}")?;
+        }
+        self.close(depth)?;
+        self.punct(Kind::Array, "]")
+    }
+
+    /// Whether a single-element array holding `item` stays on one line.
+    fn inline(&self, item: &Json) -> bool {
+        if self.indent.is_none() {
+            return false;
+        }
+        match self.options.inline_single {
+            InlineSingle::Never => false,
+            InlineSingle::Scalar => match item {
+                Json::Array(a) => a.is_empty(),
+                Json::Object(o) => o.is_empty(),
+                _ => true,
+            },
+            InlineSingle::Always => true,
+        }
+    }
+
+    /// Start the `i`th element or member of a container of `kind`, at
+    /// `depth`.
+    pub(crate) fn separator(&mut self, i: usize, depth: usize, kind: Kind) -> fmt::Result {
+        if i > 0 {
+            self.punct(kind, ",")?;
+        }
+        match self.indent {
+            Some(indent) => {
+                self.column = depth * width_of(indent);
+                self.line_break(indent, depth)
+            }
+            None if i > 0 && self.space_after_comma => self.out.write_char(' '),
+            None => Ok(()),
+        }
+    }
+
+    /// End a non-empty container at `depth`.
+    pub(crate) fn close(&mut self, depth: usize) -> fmt::Result {
+        match self.indent {
+            Some(indent) => self.line_break(indent, depth),
+            None => Ok(()),
+        }
+    }
+
+    /// Start a new line indented to `depth`.
+    fn line_break(&mut self, indent: &str, depth: usize) -> fmt::Result {
+        let len = self.options.newline.as_str().len() + depth * indent.len();
+        while self.line.len() < len {
+            self.line.push_str(indent);
+        }
+        self.out.write_str(&self.line[..len])
+    }
+}
+
+/// JSON Pointer to the first `NaN` or infinite number in `value`, if any.
+pub(crate) fn find_non_finite(value: &Json) -> Option<String> {
+    match value {
+        Json::Number(n) if !n.is_finite() => Some(String::new()),
+        Json::Array(a) => a
+            .iter()
+            .enumerate()
+            .find_map(|(i, item)| find_non_finite(item).map(|path| format!("/{i}{path}"))),
+        Json::Object(o) => o.iter().find_map(|(key, member)| {
+            find_non_finite(member).map(|path| format!("/{}{path}", pointer::escape(key)))
+        }),
+        _ => None,
+    }
+}
+
+/// Write a number the way ECMAScript's `Number::toString` does, or `null`
+/// if it isn't finite.
+// There are at most 17 digits, so the casts between counts are lossless.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss
+)]
+pub(crate) fn write_number<W: Write>(out: &mut W, n: f64) -> fmt::Result {
+    if !n.is_finite() {
+        return out.write_str("null");
+    }
+    if n == 0. {
+        return out.write_char('0');
+    }
+    if n < 0. {
+        out.write_char('-')?;
+    }
+
+    // Rust's `{:e}` gives the shortest digits that round-trip, which are the
+    // digits ECMAScript picks too; only the layout differs.
+    let mut scientific = Buffer::default();
+    write!(scientific, "{:e}", n.abs())?;
+    let scientific = scientific.as_str();
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((scientific, "0"));
+    // The digits are `first` followed by `rest`.
+    let (first, rest) = mantissa.split_at(1);
+    let rest = rest.strip_prefix('.').unwrap_or(rest);
+    let k = mantissa.len() as i32 - i32::from(!rest.is_empty());
+    let e: i32 = exponent.parse().unwrap_or_default();
+    // The value is `0.digits × 10^point`.
+    let point = e + 1;
+
+    if k <= point && point <= 21 {
+        out.write_str(first)?;
+        out.write_str(rest)?;
+        out.write_str(&ZEROS[..(point - k) as usize])
+    } else if 0 < point && point <= 21 {
+        let (int, fract) = rest.split_at(point as usize - 1);
+        out.write_str(first)?;
+        out.write_str(int)?;
+        out.write_char('.')?;
+        out.write_str(fract)
+    } else if -6 < point && point <= 0 {
+        out.write_str("0.")?;
+        out.write_str(&ZEROS[..(-point) as usize])?;
+        out.write_str(first)?;
+        out.write_str(rest)
+    } else {
+        out.write_str(first)?;
+        if !rest.is_empty() {
+            out.write_char('.')?;
+            out.write_str(rest)?;
+        }
+        out.write_str(if e < 0 { "e-" } else { "e+" })?;
+        write!(out, "{}", e.abs())
+    }
+}
+
+/// Enough zeros to pad any number ECMAScript prints without an exponent.
+const ZEROS: &str = "000000000000000000000";
+
+/// A fixed-size buffer for the `{:e}` form of an `f64`: at most 17 digits,
+/// a point, and an exponent like `e-324`.
+#[derive(Default)]
+struct Buffer {
+    bytes: [u8; 32],
+    len: usize,
+}
+
+impl Buffer {
+    fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+}
+
+impl Write for Buffer {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Write a quoted string, escaping `"`, `\`, control characters and, if
+/// `ascii`, everything outside ASCII.
+pub(crate) fn write_string<W: Write>(out: &mut W, s: &str, ascii: bool) -> fmt::Result {
+    out.write_char('"')?;
+    // Where the run of characters that need no escape starts.
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        let escape = match c {
+            '"' => "\\\"",
+            '\\' => "\\\\",
+            '\n' => "\\n",
+            '\r' => "\\r",
+            '\t' => "\\t",
+            '\u{8}' => "\\b",
+            '\u{c}' => "\\f",
+            c if c < ' ' || (ascii && !c.is_ascii()) => "",
+            _ => continue,
+        };
+        out.write_str(&s[start..i])?;
+        start = i + c.len_utf8();
+        if escape.is_empty() {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                write!(out, "\\u{unit:04x}")?;
+            }
+        } else {
+            out.write_str(escape)?;
+        }
+    }
+    out.write_str(&s[start..])?;
+    out.write_char('"')
+}
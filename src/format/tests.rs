@@ -0,0 +1,308 @@
+#![cfg(test)]
+
+use super::*;
+use crate::Colors;
+
+fn number(n: f64) -> String {
+    let mut out = String::new();
+    write_number(&mut out, n).unwrap();
+    out
+}
+
+fn format(value: &Json, options: &FormatOptions) -> String {
+    value.to_string_with(options).unwrap()
+}
+
+#[test]
+fn numbers() {
+    // From the number samples in RFC 8785, Appendix B.
+    assert_eq!(number(0.), "0");
+    assert_eq!(number(-0.), "0");
+    assert_eq!(number(f64::from_bits(0x0000_0000_0000_0001)), "5e-324");
+    assert_eq!(number(f64::from_bits(0x8000_0000_0000_0001)), "-5e-324");
+    assert_eq!(number(f64::MAX), "1.7976931348623157e+308");
+    assert_eq!(number(-f64::MAX), "-1.7976931348623157e+308");
+    assert_eq!(number(9_007_199_254_740_992.), "9007199254740992");
+    assert_eq!(number(-9_007_199_254_740_992.), "-9007199254740992");
+    assert_eq!(
+        number(295_147_905_179_352_830_000.),
+        "295147905179352830000"
+    );
+    assert_eq!(number(1e21), "1e+21");
+    assert_eq!(
+        number(999_999_999_999_999_900_000.),
+        "999999999999999900000"
+    );
+    assert_eq!(number(0.000_001), "0.000001");
+    assert_eq!(number(1e-7), "1e-7");
+    assert_eq!(number(333_333_333.333_333_3), "333333333.3333333");
+    assert_eq!(number(-1.5e-10), "-1.5e-10");
+    assert_eq!(number(4.5), "4.5");
+    assert_eq!(number(0.002), "0.002");
+    assert_eq!(number(100.), "100");
+}
+
+#[test]
+fn non_finite() {
+    assert_eq!(number(f64::NAN), "null");
+    assert_eq!(Json::Number(f64::INFINITY).to_string(), "null");
+
+    let doc = Json::Array(vec![json!({"a": 1}), json!({"b/c": [true]})]);
+    assert_eq!(find_non_finite(&doc), None);
+    let doc = Json::Array(vec![
+        json!({"a": 1}),
+        Json::Object(
+            vec![(
+                "b/c".to_string(),
+                Json::Array(vec![Json::Boolean(true), Json::Number(-f64::INFINITY)]),
+            )]
+            .into_iter()
+            .collect(),
+        ),
+    ]);
+    assert_eq!(find_non_finite(&doc).as_deref(), Some("/1/b~1c/1"));
+
+    assert_eq!(
+        format(&doc, &FormatOptions::compact()),
+        r#"[{"a":1},{"b/c":[true,null]}]"#
+    );
+    let mut out = Vec::new();
+    let e = doc
+        .print(
+            &FormatOptions::tabs().non_finite(NonFinite::Error),
+            &mut out,
+        )
+        .unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(e.to_string(), "non-finite number at \"/1/b~1c/1\"");
+    assert!(out.is_empty());
+}
+
+#[test]
+fn layouts() {
+    let doc = json!({"a": [1e21, [], {}], "b": {"c": null}, "d": [[true]]});
+    assert_eq!(
+        doc.to_string(),
+        r#"{"a":[1e+21,[],{}],"b":{"c":null},"d":[[true]]}"#
+    );
+    assert_eq!(
+        format(&doc, &FormatOptions::tabs()),
+        "{\n\t\"a\": [\n\t\t1e+21,\n\t\t[],\n\t\t{}\n\t],\n\t\"b\": {\n\t\t\"c\": null\n\t},\n\t\"d\": [\n\t\t[ true ]\n\t]\n}"
+    );
+    assert_eq!(
+        format(
+            &doc,
+            &FormatOptions::compact()
+                .space_after_colon(true)
+                .space_after_comma(true)
+        ),
+        r#"{"a": [1e+21, [], {}], "b": {"c": null}, "d": [[true]]}"#
+    );
+
+    let doc = json!({"x": [[1, 2]]});
+    let options = FormatOptions::compact()
+        .indent("  ")
+        .space_after_colon(false);
+    assert_eq!(
+        format(&doc, &options),
+        "{\n  \"x\":[\n    [\n      1,\n      2\n    ]\n  ]\n}"
+    );
+    assert_eq!(
+        format(
+            &doc,
+            &options.clone().inline_single_element(InlineSingle::Always)
+        ),
+        "{\n  \"x\":[ [1,2] ]\n}"
+    );
+    assert_eq!(
+        format(
+            &json!([1]),
+            &options.inline_single_element(InlineSingle::Never)
+        ),
+        "[\n  1\n]"
+    );
+
+    let options = FormatOptions::spaces(1)
+        .newline(Newline::CrLf)
+        .trailing_newline(true);
+    assert_eq!(format(&json!([1, 2]), &options), "[\r\n 1,\r\n 2\r\n]\r\n");
+    assert_eq!(
+        format(&json!([]), &FormatOptions::compact().trailing_newline(true)),
+        "[]\n"
+    );
+}
+
+#[test]
+fn strings_and_keys() {
+    let doc = json!({"\u{e9}\"\\\n\u{1}": "\u{1f600}", "\u{ff61}": 1, "\u{1f600}": 2});
+    assert_eq!(
+        doc.to_string(),
+        "{\"\u{e9}\\\"\\\\\\n\\u0001\":\"\u{1f600}\",\"\u{ff61}\":1,\"\u{1f600}\":2}"
+    );
+    assert_eq!(
+        format(
+            &doc,
+            &FormatOptions::compact()
+                .ascii(true)
+                .key_order(KeyOrder::Utf16)
+        ),
+        r#"{"\u00e9\"\\\n\u0001":"\ud83d\ude00","\ud83d\ude00":2,"\uff61":1}"#
+    );
+
+    // What's escaped reads back as it was.
+    let ascii = format(&doc, &FormatOptions::compact().ascii(true));
+    assert_eq!(ascii.parse::<Json>().unwrap(), doc);
+    assert_eq!(Json::from_slice(ascii.as_bytes()).unwrap(), doc);
+    assert!(crate::validate(ascii.as_bytes()).is_ok());
+}
+
+#[test]
+fn max_width() {
+    let doc = json!({
+        "point": {"x": 1, "y": 2},
+        "list": [1, 2, 3],
+        "nested": [[1, 2], {"long name": "a long string value"}]
+    });
+    assert_eq!(
+        format(&doc, &FormatOptions::spaces(2).max_width(30)),
+        concat!(
+            "{\n",
+            "  \"list\": [1, 2, 3],\n",
+            "  \"nested\": [\n",
+            "    [1, 2],\n",
+            "    {\n",
+            "      \"long name\": \"a long string value\"\n",
+            "    }\n",
+            "  ],\n",
+            "  \"point\": {\"x\": 1, \"y\": 2}\n",
+            "}"
+        )
+    );
+    assert_eq!(
+        format(&doc, &FormatOptions::spaces(2).max_width(200)),
+        r#"{"list": [1, 2, 3], "nested": [[1, 2], {"long name": "a long string value"}], "point": {"x": 1, "y": 2}}"#
+    );
+
+    // The trailing comma counts, and a tab is 8 columns.
+    let doc = json!([[1, 2], [3, 4]]);
+    let options = FormatOptions::tabs().max_width(14);
+    assert_eq!(
+        format(&doc, &options),
+        "[\n\t[\n\t\t1,\n\t\t2\n\t],\n\t[3, 4]\n]"
+    );
+    assert_eq!(
+        format(&doc, &FormatOptions::compact().max_width(1)),
+        "[[1,2],[3,4]]"
+    );
+}
+
+#[test]
+fn table_align() {
+    let doc = json!({"rows": [
+        {"id": 1, "name": "ab", "tags": ["x"]},
+        {"id": 100, "name": "b"},
+        {"id": 2, "tags": []}
+    ]});
+    let options = FormatOptions::spaces(2).table_align(true);
+    assert_eq!(
+        format(&doc, &options),
+        concat!(
+            "{\n",
+            "  \"rows\": [\n",
+            "    {\"id\": 1,   \"name\": \"ab\", \"tags\": [\"x\"]},\n",
+            "    {\"id\": 100, \"name\": \"b\"},\n",
+            "    {\"id\": 2,                 \"tags\": []}\n",
+            "  ]\n",
+            "}"
+        )
+    );
+
+    // Records missing the first column still line up.
+    let doc = json!([{"a": 1, "b": 2}, {"b": 3}]);
+    assert_eq!(
+        format(&doc, &options),
+        "[\n  {\"a\": 1, \"b\": 2},\n  {        \"b\": 3}\n]"
+    );
+
+    // Dissimilar records, and tables too wide for `max_width`, aren't aligned.
+    let doc = json!([{"a": 1, "b": 2, "c": 3}, {"d": 4}]);
+    assert_eq!(
+        format(&doc, &options),
+        format(&doc, &FormatOptions::spaces(2))
+    );
+    let doc = json!([{"a": 1}, 2]);
+    assert_eq!(format(&doc, &options), "[\n  {\n    \"a\": 1\n  },\n  2\n]");
+    let doc = json!([{"a": 1, "b": "a long string"}, {"a": 2, "b": "c"}]);
+    assert_eq!(
+        format(&doc, &options.max_width(24)),
+        concat!(
+            "[\n",
+            "  {\n",
+            "    \"a\": 1,\n",
+            "    \"b\": \"a long string\"\n",
+            "  },\n",
+            "  {\"a\": 2, \"b\": \"c\"}\n",
+            "]"
+        )
+    );
+}
+
+#[test]
+fn colors() {
+    let colors = "31:32:33:34:35:36:37:1".parse::<Colors>().unwrap();
+    let doc = json!({"a": [null, false, true, 1, "s"], "b": {}});
+    let options = FormatOptions::compact().colors(colors.clone());
+    assert_eq!(
+        format(&doc, &options).replace('\u{1b}', "E"),
+        concat!(
+            "E[37m{E[0mE[1m\"a\"E[0mE[37m:E[0m",
+            "E[36m[E[0mE[31mnullE[0mE[36m,E[0mE[32mfalseE[0mE[36m,E[0m",
+            "E[33mtrueE[0mE[36m,E[0mE[34m1E[0mE[36m,E[0mE[35m\"s\"E[0mE[36m]E[0m",
+            "E[37m,E[0mE[1m\"b\"E[0mE[37m:E[0mE[37m{}E[0mE[37m}E[0m"
+        )
+    );
+
+    // Escapes don't count towards the width, and empty styles aren't written.
+    let colors = Colors {
+        key: String::new(),
+        object: String::new(),
+        ..colors
+    };
+    let options = FormatOptions::spaces(2).max_width(13).colors(colors);
+    assert_eq!(
+        format(&json!({"a": [1, 2]}), &options).replace('\u{1b}', "E"),
+        "{\"a\": E[36m[E[0mE[34m1E[0mE[36m,E[0m E[34m2E[0mE[36m]E[0m}"
+    );
+}
+
+/// A writer that's always out of space.
+struct Full;
+
+impl io::Write for Full {
+    fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+        Err(io::Error::other("disk full"))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn print() {
+    let doc = json!([[[["deep"]], "back"], {"a": [{"b": [1, 2]}]}, "top \"q\""]);
+    let options = FormatOptions::spaces(2).inline_single_element(InlineSingle::Never);
+    let mut out = Vec::new();
+    doc.print(&options, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), format(&doc, &options));
+    assert_eq!(
+        format(
+            &json!([[[1]], 2]),
+            &FormatOptions::tabs().newline(Newline::CrLf)
+        ),
+        "[\r\n\t[\r\n\t\t[ 1 ]\r\n\t],\r\n\t2\r\n]"
+    );
+
+    let err = doc.print(&FormatOptions::compact(), &mut Full).unwrap_err();
+    assert_eq!(err.to_string(), "disk full");
+}
@@ -2,15 +2,57 @@
 
 use std::collections::BTreeMap;
 use std::fmt;
-use std::io;
 use std::ops::Range;
 use std::str::FromStr;
 
+// Lets `jval-derive`'s output, which names `::jval`, be used in tests.
+#[cfg(test)]
+extern crate self as jval;
+
+#[macro_use]
+mod macros;
+
+mod canonical;
+mod color;
+mod convert;
+mod diff;
+mod format;
 mod json;
+mod merge;
 mod number;
+mod parse;
+mod regex;
+mod scan;
+#[cfg(feature = "serde")]
+mod serde;
 mod string;
-
-#[derive(Debug)]
+mod validate;
+mod value;
+mod writer;
+
+pub mod codegen;
+pub mod filter;
+pub mod patch;
+pub mod path;
+pub mod pointer;
+pub mod schema;
+
+#[cfg(feature = "serde")]
+pub use self::serde::{from_str, from_value, to_string, to_value, SerdeError};
+pub use canonical::CanonicalError;
+pub use color::{Colors, ColorsError};
+pub use convert::{FromJson, FromJsonError, ToJson};
+pub use diff::{diff, diff_with, Change, DiffOptions};
+pub use format::{FormatOptions, InlineSingle, KeyOrder, Newline, NonFinite};
+#[cfg(feature = "derive")]
+pub use jval_derive::{FromJson, ToJson};
+pub use parse::{Limit, Limits, ParseOptions};
+pub use pointer::PointerError;
+pub use validate::validate;
+pub use value::JsonIndex;
+pub use writer::Writer;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum ErrorKind {
     InvalidUnicode,
     UnterminatedString,
@@ -19,8 +61,16 @@ pub enum ErrorKind {
     UnterminatedObject,
     UnterminatedArray,
     TrailingComma,
+    InvalidUtf8,
+    /// The input went past one of the [`Limits`] it was parsed with.
+    LimitExceeded {
+        which: Limit,
+    },
 }
 
+/// The errors found in a JSON document, with the byte ranges they cover.
+pub type Errors = Vec<(ErrorKind, Range<usize>)>;
+
 #[derive(Debug, PartialEq)]
 enum Token {
     OpenCurly,
@@ -39,66 +89,113 @@ enum Token {
 type TokenRecord = (Token, usize, usize);
 type Error = (ErrorKind, usize, usize);
 
-fn try_get_string(s: &str) -> Result<(TokenRecord, &str), Error> {
-    let s_len = s.len();
+fn try_get_string(s: &str, max_len: Option<usize>) -> Result<(TokenRecord, &str), Error> {
     let mut out = String::new();
+    let mut too_long = false;
+    let len = read_string(s, |c| {
+        out.push(c);
+        // Past the limit, keep going to find where the string ends, but
+        // without keeping any of it.
+        if max_len.is_some_and(|max| out.len() > max) {
+            too_long = true;
+            out.clear();
+        }
+    })?;
+
+    if too_long {
+        let which = Limit::StringLength;
+        return Err((ErrorKind::LimitExceeded { which }, len, s.len()));
+    }
+    Ok(((Token::StringLiteral(out), len, s.len()), &s[len..]))
+}
+
+/// Read the string `s` starts with, passing each character of its value to
+/// `push`, and return its length with the quotes. A `\u` escape of a high
+/// surrogate must be followed straight away by one of a low surrogate, and
+/// the two make one character.
+fn read_string(s: &str, mut push: impl FnMut(char)) -> Result<usize, Error> {
+    let s_len = s.len();
     let mut escape = false;
     let mut unic = None;
+    // A high surrogate waiting for its low half, and where its digits are.
+    let mut high: Option<(u32, usize)> = None;
 
     for (i, c) in s.char_indices().skip(1) {
+        if let Some((_, at)) = high {
+            let pairing = match c {
+                '\\' => !escape && unic.is_none(),
+                'u' => escape,
+                d => d.is_ascii_hexdigit() && unic.is_some(),
+            };
+            if !pairing {
+                return Err((ErrorKind::InvalidUnicode, 4, at));
+            }
+        }
+
         match c {
             '\\' if !escape => escape = true,
             '\\' => {
-                out.push(c);
+                push(c);
+                escape = false;
+            }
+            '"' if !escape => return Ok(i + 1),
+            '"' | '/' if escape => {
+                push(c);
                 escape = false;
             }
-            '"' if !escape => return Ok(((Token::StringLiteral(out), i + 1, s_len), &s[i + 1..])),
             'b' if escape => {
-                out.push(8 as char);
+                push(8 as char);
                 escape = false;
             }
             'f' if escape => {
-                out.push(12 as char);
+                push(12 as char);
                 escape = false;
             }
             'n' if escape => {
-                out.push('\n');
+                push('\n');
                 escape = false;
             }
             'r' if escape => {
-                out.push('\r');
+                push('\r');
                 escape = false;
             }
             't' if escape => {
-                out.push('\t');
+                push('\t');
                 escape = false;
             }
             'u' if escape => {
-                unic = Some(String::new());
+                unic = Some((0, 0));
                 escape = false;
             }
             d if d.is_ascii_hexdigit() && unic.is_some() => {
-                if let Some(u) = unic.iter_mut().next() {
-                    match u.len() {
-                        0..=2 => u.push(d),
-                        3 => {
-                            u.push(d);
-
-                            if let Some(uc) = std::char::from_u32(
-                                u32::from_str_radix(&u, 16)
-                                    .expect("failed to parse code point from hex number"),
-                            ) {
-                                out.push(uc);
+                if let Some((digits, code)) = &mut unic {
+                    *code = *code << 4 | d.to_digit(16).unwrap_or_default();
+                    *digits += 1;
+                    if *digits == 4 {
+                        let at = s_len - i + 3;
+                        let code = match (high.take(), *code) {
+                            (Some((high, _)), low @ 0xdc00..=0xdfff) => {
+                                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                            }
+                            (Some((_, at)), _) => {
+                                return Err((ErrorKind::InvalidUnicode, 4, at));
+                            }
+                            (None, code @ 0xd800..=0xdbff) => {
+                                high = Some((code, at));
                                 unic = None;
-                            } else {
-                                return Err((ErrorKind::InvalidUnicode, u.len(), s_len - i + 3));
+                                continue;
                             }
-                        }
-                        _ => return Err((ErrorKind::InvalidUnicode, u.len(), s_len - i + u.len())),
+                            (None, code) => code,
+                        };
+                        let Some(uc) = std::char::from_u32(code) else {
+                            return Err((ErrorKind::InvalidUnicode, 4, at));
+                        };
+                        push(uc);
+                        unic = None;
                     }
                 }
             }
-            _ => out.push(c),
+            _ => push(c),
         }
     }
 
@@ -160,7 +257,7 @@ fn try_get_number(s: &str) -> Result<(TokenRecord, &str), Error> {
 
 type TokenResult<'a> = Result<(Option<TokenRecord>, &'a str), Error>;
 
-fn next_token(mut s: &str) -> TokenResult {
+fn next_token<'a>(mut s: &'a str, limits: &Limits) -> TokenResult<'a> {
     fn split_slice(slice: &str, idx: usize, token: Token) -> TokenResult {
         Ok((Some((token, idx, slice.len())), &slice[idx..]))
     }
@@ -171,18 +268,25 @@ fn next_token(mut s: &str) -> TokenResult {
         return Ok((None, s));
     }
 
-    match &s[..1] {
-        "{" => split_slice(s, 1, Token::OpenCurly),
-        "}" => split_slice(s, 1, Token::CloseCurly),
-        "[" => split_slice(s, 1, Token::OpenSquare),
-        "]" => split_slice(s, 1, Token::CloseSquare),
-        "," => split_slice(s, 1, Token::Comma),
-        ":" => split_slice(s, 1, Token::Colon),
-        "\"" => try_get_string(s).map(|(t, s)| (Some(t), s)),
+    match s.chars().next().unwrap_or_default() {
+        '{' => split_slice(s, 1, Token::OpenCurly),
+        '}' => split_slice(s, 1, Token::CloseCurly),
+        '[' => split_slice(s, 1, Token::OpenSquare),
+        ']' => split_slice(s, 1, Token::CloseSquare),
+        ',' => split_slice(s, 1, Token::Comma),
+        ':' => split_slice(s, 1, Token::Colon),
+        '"' => try_get_string(s, limits.string_length).map(|(t, s)| (Some(t), s)),
         _ if s.starts_with("null") => split_slice(s, 4, Token::Null),
         _ if s.starts_with("true") => split_slice(s, 4, Token::True),
         _ if s.starts_with("false") => split_slice(s, 5, Token::False),
-        _ => try_get_number(s).map(|(t, s)| (Some(t), s)),
+        c if !c.is_ascii() => Err((ErrorKind::UnexpectedToken, c.len_utf8(), s.len())),
+        _ => match try_get_number(s)? {
+            ((_, len, rest), _) if limits.exceeded(Limit::NumberLength, len) => {
+                let which = Limit::NumberLength;
+                Err((ErrorKind::LimitExceeded { which }, len, rest))
+            }
+            (t, s) => Ok((Some(t), s)),
+        },
     }
 }
 
@@ -190,8 +294,30 @@ type MoreToParse<'a> = (Option<Json>, &'a [TokenRecord]);
 type ParseError<'a> = (Error, &'a [TokenRecord]);
 type ParseResult<'a> = Result<MoreToParse<'a>, ParseError<'a>>;
 
-fn next_value(tokens: &[TokenRecord]) -> ParseResult<'_> {
+/// The error for `token` going past the limit on `which`. It ends the
+/// parse, so there are no more tokens to continue with.
+fn limit_exceeded<'a>(which: Limit, (_, l, f): &TokenRecord) -> ParseResult<'a> {
+    Err(((ErrorKind::LimitExceeded { which }, *l, *f), &[]))
+}
+
+/// Build the next value from `tokens`, counting it and what's in it towards
+/// `nodes`, with `depth` arrays and objects around it.
+fn next_value<'a>(
+    tokens: &'a [TokenRecord],
+    limits: &Limits,
+    nodes: &mut usize,
+    depth: usize,
+) -> ParseResult<'a> {
     if let Some(tup) = tokens.split_first() {
+        *nodes += 1;
+        if limits.exceeded(Limit::Nodes, *nodes) {
+            return limit_exceeded(Limit::Nodes, tup.0);
+        }
+        let container = matches!(tup.0 .0, Token::OpenCurly | Token::OpenSquare);
+        if container && limits.exceeded(Limit::Depth, depth + 1) {
+            return limit_exceeded(Limit::Depth, tup.0);
+        }
+
         match tup {
             ((Token::Null, _, _), rest) => Ok((Some(Json::Null), rest)),
             ((Token::True, _, _), rest) => Ok((Some(Json::Boolean(true)), rest)),
@@ -200,371 +326,172 @@ fn next_value(tokens: &[TokenRecord]) -> ParseResult<'_> {
             ((Token::StringLiteral(n), _, _), rest) => {
                 Ok((Some(Json::String(n.to_string())), rest))
             }
-            ((Token::OpenCurly, tok_len, tok_rest), mut rest) => {
-                let mut map = BTreeMap::new();
-
-                if let Some(((Token::CloseCurly, _, _), more)) = rest.split_first() {
-                    return Ok((Some(Json::Object(map)), more));
-                }
-
-                let mut last_comma = (tok_len, tok_rest);
-                while let Some((token, more)) = rest.split_first() {
-                    match token {
-                        (Token::StringLiteral(key), token_len, token_rest) => {
-                            if let Some(((Token::Colon, _, f), even_more)) = more.split_first() {
-                                match next_value(even_more) {
-                                    Err((e, mut still_more)) => {
-                                        // find closing curly brace
-                                        while let Some((head, tail)) = still_more.split_first() {
-                                            if let (Token::CloseCurly, _, _) = head {
-                                                return Err((e, tail));
-                                            }
-
-                                            still_more = tail;
-                                        }
-
-                                        // if it's not there, return no more tokens to continue
-                                        return Err((e, &[]));
-                                    }
-                                    Ok((Some(value), still_more)) => {
-                                        map.insert(key.to_string(), value);
-
-                                        match still_more.split_first() {
-                                            Some((
-                                                (Token::Comma, comma_len, comma_start),
-                                                please_stop,
-                                            )) => {
-                                                last_comma = (comma_len, comma_start);
-                                                rest = please_stop
-                                            }
-                                            Some(((Token::CloseCurly, _, _), please_stop)) => {
-                                                return Ok((Some(Json::Object(map)), please_stop));
-                                            }
-                                            Some(((_, l, f), more_rest)) => {
-                                                return Err((
-                                                    (ErrorKind::UnexpectedToken, *l, *f),
-                                                    more_rest,
-                                                ))
-                                            }
-                                            None => {
-                                                return Err((
-                                                    (ErrorKind::UnterminatedObject, 0, *f),
-                                                    still_more,
-                                                ))
-                                            }
-                                        }
-                                    }
-                                    Ok((None, still_more)) => {
-                                        return Err((
-                                            (ErrorKind::UnterminatedObject, 0, *f),
-                                            still_more,
-                                        ));
-                                    }
-                                }
-                            } else {
-                                return Err((
-                                    (ErrorKind::UnexpectedToken, *token_len, *token_rest),
-                                    more,
-                                ));
-                            }
-                        }
-                        (Token::CloseCurly, _, _) => {
-                            return Err((
-                                (ErrorKind::TrailingComma, *last_comma.0, *last_comma.1),
-                                more,
-                            ))
-                        }
-                        _ => return Err(((ErrorKind::UnexpectedToken, *tok_len, *tok_rest), rest)),
-                    }
-                }
-
-                Err(((ErrorKind::UnexpectedToken, *tok_len, *tok_rest), rest))
-            }
-            ((Token::OpenSquare, _, f), mut rest) => {
-                let mut vec = Vec::new();
+            ((Token::OpenCurly, _, _), rest) => next_object(tup.0, rest, limits, nodes, depth),
+            ((Token::OpenSquare, _, _), rest) => next_array(tup.0, rest, limits, nodes, depth),
+            ((_, l, f), rest) => Err(((ErrorKind::UnexpectedToken, *l, *f), rest)),
+        }
+    } else {
+        Ok((None, tokens))
+    }
+}
 
-                if let Some(((Token::CloseSquare, _, _), more)) = rest.split_first() {
-                    return Ok((Some(Json::Array(vec)), more));
-                }
+/// Build the object opened by `open` from the tokens after it, `rest`.
+fn next_object<'a>(
+    open: &'a TokenRecord,
+    mut rest: &'a [TokenRecord],
+    limits: &Limits,
+    nodes: &mut usize,
+    depth: usize,
+) -> ParseResult<'a> {
+    let (_, tok_len, tok_rest) = open;
+    let mut map = BTreeMap::new();
+
+    if let Some(((Token::CloseCurly, _, _), more)) = rest.split_first() {
+        return Ok((Some(Json::Object(map)), more));
+    }
 
-                while !rest.is_empty() {
-                    match next_value(rest) {
-                        Err((e, mut more)) => {
-                            // find closing square brace
-                            while let Some((head, tail)) = more.split_first() {
-                                if let (Token::CloseSquare, _, _) = head {
+    let mut last_comma = (tok_len, tok_rest);
+    while let Some((token, more)) = rest.split_first() {
+        match token {
+            (Token::StringLiteral(key), token_len, token_rest) => {
+                if let Some(((Token::Colon, _, f), even_more)) = more.split_first() {
+                    match next_value(even_more, limits, nodes, depth + 1) {
+                        Err((e, mut still_more)) => {
+                            // find closing curly brace
+                            while let Some((head, tail)) = still_more.split_first() {
+                                if let (Token::CloseCurly, _, _) = head {
                                     return Err((e, tail));
                                 }
 
-                                more = tail;
+                                still_more = tail;
                             }
 
                             // if it's not there, return no more tokens to continue
                             return Err((e, &[]));
                         }
-                        Ok((Some(value), more)) => {
-                            vec.push(value);
+                        Ok((Some(value), still_more)) => {
+                            map.insert(key.to_string(), value);
+                            if limits.exceeded(Limit::ObjectLength, map.len()) {
+                                return limit_exceeded(Limit::ObjectLength, open);
+                            }
 
-                            match more.split_first() {
-                                Some(((Token::Comma, _, _), even_more)) => rest = even_more,
-                                Some(((Token::CloseSquare, _, _), even_more)) => {
-                                    return Ok((Some(Json::Array(vec)), even_more))
+                            match still_more.split_first() {
+                                Some(((Token::Comma, comma_len, comma_start), please_stop)) => {
+                                    last_comma = (comma_len, comma_start);
+                                    rest = please_stop
+                                }
+                                Some(((Token::CloseCurly, _, _), please_stop)) => {
+                                    return Ok((Some(Json::Object(map)), please_stop));
                                 }
-                                Some(((_, l, f), even_more)) => {
-                                    return Err(((ErrorKind::UnexpectedToken, *l, *f), even_more))
+                                Some(((_, l, f), more_rest)) => {
+                                    return Err(((ErrorKind::UnexpectedToken, *l, *f), more_rest))
+                                }
+                                None => {
+                                    return Err((
+                                        (ErrorKind::UnterminatedObject, 0, *f),
+                                        still_more,
+                                    ))
                                 }
-                                None => return Err(((ErrorKind::UnterminatedArray, 0, *f), more)),
                             }
                         }
-                        Ok((None, more)) => {
-                            return Err(((ErrorKind::UnterminatedArray, 0, *f), more));
+                        Ok((None, still_more)) => {
+                            return Err(((ErrorKind::UnterminatedObject, 0, *f), still_more));
                         }
                     }
+                } else {
+                    return Err(((ErrorKind::UnexpectedToken, *token_len, *token_rest), more));
                 }
-
-                Err(((ErrorKind::UnterminatedArray, 0, *f), rest))
-            }
-            ((_, l, f), rest) => Err(((ErrorKind::UnexpectedToken, *l, *f), rest)),
-        }
-    } else {
-        Ok((None, tokens))
-    }
-}
-
-#[derive(Debug, PartialEq)]
-pub enum Json {
-    Null,
-    Boolean(bool),
-    Number(f64),
-    String(String),
-    Array(Vec<Self>),
-    Object(BTreeMap<String, Self>),
-}
-
-impl FromStr for Json {
-    type Err = Vec<(ErrorKind, Range<usize>)>;
-
-    fn from_str(mut s: &str) -> Result<Self, Self::Err> {
-        let s_len = s.len();
-        let calc_range = |(e, l, f)| {
-            (
-                e,
-                Range {
-                    start: s_len - f,
-                    end: s_len - f + l,
-                },
-            )
-        };
-
-        let mut tokens = Vec::new();
-        let mut errvec = Vec::new();
-
-        while !s.is_empty() {
-            match next_token(s) {
-                Err(e) => {
-                    s = &s[s.len() - e.2 + e.1..];
-                    errvec.push(calc_range(e));
-                }
-                Ok((Some(t), s_)) => {
-                    tokens.push(t);
-                    s = s_;
-                }
-                Ok((None, _)) => break,
             }
-        }
-
-        if !errvec.is_empty() {
-            return Err(errvec);
-        }
-
-        let mut toks = &tokens[..];
-        let mut values = Vec::new();
-
-        while !toks.is_empty() {
-            match next_value(toks) {
-                Err((e, t_)) => {
-                    errvec.push(calc_range(e));
-                    toks = t_;
-                }
-                Ok((Some(v), t_)) => {
-                    values.push(v);
-                    toks = t_;
-                }
-                Ok((None, _)) => break,
+            (Token::CloseCurly, _, _) => {
+                return Err((
+                    (ErrorKind::TrailingComma, *last_comma.0, *last_comma.1),
+                    more,
+                ))
             }
-        }
-
-        if !errvec.is_empty() {
-            return Err(errvec);
-        }
-
-        if values.len() == 1 {
-            Ok(values.remove(0))
-        } else {
-            Err(vec![calc_range((
-                ErrorKind::UnexpectedToken,
-                s.len(),
-                s.len(),
-            ))])
+            _ => return Err(((ErrorKind::UnexpectedToken, *tok_len, *tok_rest), rest)),
         }
     }
-}
 
-impl fmt::Display for Json {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Json::Null => write!(f, "null"),
-            Json::Boolean(b) => write!(f, "{}", b),
-            Json::Number(n) => write!(f, "{}", n),
-            Json::String(s) => write!(f, "\"{}\"", s),
-            Json::Array(a) => write!(
-                f,
-                "[{}]",
-                a.iter()
-                    .map(|e| format!("{}", e))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            ),
-            Json::Object(o) => write!(
-                f,
-                "{{{}}}",
-                o.iter()
-                    .map(|(k, v)| format!("\"{}\":{}", k, v))
-                    .collect::<Vec<_>>()
-                    .join(",")
-            ),
-        }
-    }
+    Err(((ErrorKind::UnexpectedToken, *tok_len, *tok_rest), rest))
 }
 
-pub enum Spacing {
-    None,
-    Tab,
-    Space(usize),
-}
-
-impl Json {
-    pub fn print<W: io::Write>(&self, spacing: &Spacing, f: &mut W) -> io::Result<()> {
-        match spacing {
-            Spacing::None => f.write_fmt(format_args!("{}", self)),
-            Spacing::Tab => self.print_tabs(0, f),
-            Spacing::Space(n) => self.print_indented(*n, 0, f),
-        }
+/// Build the array opened by `open` from the tokens after it, `rest`.
+fn next_array<'a>(
+    open: &'a TokenRecord,
+    mut rest: &'a [TokenRecord],
+    limits: &Limits,
+    nodes: &mut usize,
+    depth: usize,
+) -> ParseResult<'a> {
+    let (_, _, f) = open;
+    let mut vec = Vec::new();
+
+    if let Some(((Token::CloseSquare, _, _), more)) = rest.split_first() {
+        return Ok((Some(Json::Array(vec)), more));
     }
 
-    fn print_indented<W: io::Write>(
-        &self,
-        spacing: usize,
-        start: usize,
-        f: &mut W,
-    ) -> io::Result<()> {
-        match self {
-            Json::Array(a) if a.is_empty() => f.write_fmt(format_args!("[]")),
-            Json::Array(a) if a.len() == 1 => f.write_fmt(format_args!("[ {} ]", a[0])),
-            Json::Object(o) if o.is_empty() => f.write_fmt(format_args!("{{}}")),
-
-            Json::Array(a) => {
-                f.write_fmt(format_args!("[\n"))?;
-
-                for (i, el) in a.iter().enumerate() {
-                    for _ in 0..start + spacing {
-                        f.write_fmt(format_args!(" ",))?;
+    while !rest.is_empty() {
+        match next_value(rest, limits, nodes, depth + 1) {
+            Err((e, mut more)) => {
+                // find closing square brace
+                while let Some((head, tail)) = more.split_first() {
+                    if let (Token::CloseSquare, _, _) = head {
+                        return Err((e, tail));
                     }
 
-                    el.print_indented(spacing, start + spacing, f)?;
-
-                    if i != a.len() - 1 {
-                        f.write_fmt(format_args!(","))?;
-                    }
-
-                    writeln!(f)?;
+                    more = tail;
                 }
 
-                for _ in 0..start {
-                    f.write_fmt(format_args!(" ",))?;
-                }
-                f.write_fmt(format_args!("]"))
+                // if it's not there, return no more tokens to continue
+                return Err((e, &[]));
             }
-            Json::Object(o) => {
-                f.write_fmt(format_args!("{{\n"))?;
+            Ok((Some(value), more)) => {
+                vec.push(value);
+                if limits.exceeded(Limit::ArrayLength, vec.len()) {
+                    return limit_exceeded(Limit::ArrayLength, open);
+                }
 
-                for (i, (k, v)) in o.iter().enumerate() {
-                    for _ in 0..start + spacing {
-                        f.write_fmt(format_args!(" ",))?;
+                match more.split_first() {
+                    Some(((Token::Comma, _, _), even_more)) => rest = even_more,
+                    Some(((Token::CloseSquare, _, _), even_more)) => {
+                        return Ok((Some(Json::Array(vec)), even_more))
                     }
-
-                    f.write_fmt(format_args!("\"{}\": ", k))?;
-                    v.print_indented(spacing, start + spacing, f)?;
-
-                    if i != o.len() - 1 {
-                        f.write_fmt(format_args!(","))?;
+                    Some(((_, l, f), even_more)) => {
+                        return Err(((ErrorKind::UnexpectedToken, *l, *f), even_more))
                     }
-                    writeln!(f)?;
-                }
-
-                for _ in 0..start {
-                    f.write_fmt(format_args!(" ",))?;
+                    None => return Err(((ErrorKind::UnterminatedArray, 0, *f), more)),
                 }
-                f.write_fmt(format_args!("}}"))
             }
-
-            _ => f.write_fmt(format_args!("{}", self)),
+            Ok((None, more)) => {
+                return Err(((ErrorKind::UnterminatedArray, 0, *f), more));
+            }
         }
     }
 
-    fn print_tabs<W: io::Write>(&self, start: usize, f: &mut W) -> io::Result<()> {
-        match self {
-            Json::Array(a) if a.is_empty() => f.write_fmt(format_args!("[]")),
-            Json::Array(a) if a.len() == 1 => f.write_fmt(format_args!("[ {} ]", a[0])),
-            Json::Object(o) if o.is_empty() => f.write_fmt(format_args!("{{}}")),
-
-            Json::Array(a) => {
-                f.write_fmt(format_args!("[\n"))?;
-
-                for (i, el) in a.iter().enumerate() {
-                    for _ in 0..=start {
-                        f.write_fmt(format_args!("\t",))?;
-                    }
-
-                    el.print_tabs(start + 1, f)?;
-
-                    if i != a.len() - 1 {
-                        f.write_fmt(format_args!(","))?;
-                    }
-
-                    f.write_fmt(format_args!("\n"))?;
-                }
-
-                for _ in 0..start {
-                    f.write_fmt(format_args!("\t",))?;
-                }
-                f.write_fmt(format_args!("]"))
-            }
-            Json::Object(o) => {
-                f.write_fmt(format_args!("{{\n"))?;
-
-                for (i, (k, v)) in o.iter().enumerate() {
-                    for _ in 0..=start {
-                        f.write_fmt(format_args!("\t",))?;
-                    }
-
-                    f.write_fmt(format_args!("\"{}\": ", k))?;
-                    v.print_tabs(start + 1, f)?;
+    Err(((ErrorKind::UnterminatedArray, 0, *f), rest))
+}
 
-                    if i != o.len() - 1 {
-                        f.write_fmt(format_args!(","))?;
-                    }
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Json {
+    #[default]
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Self>),
+    Object(BTreeMap<String, Self>),
+}
 
-                    f.write_fmt(format_args!("\n"))?;
-                }
+impl FromStr for Json {
+    type Err = Errors;
 
-                for _ in 0..start {
-                    f.write_fmt(format_args!("\t",))?;
-                }
-                f.write_fmt(format_args!("}}"))
-            }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Json::from_str_with(s, &ParseOptions::default())
+    }
+}
 
-            _ => f.write_fmt(format_args!("{}", self)),
-        }
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format::write_formatted(f, self, &FormatOptions::compact())
     }
 }
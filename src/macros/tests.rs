@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+use crate::Json;
+
+#[test]
+fn literals() {
+    assert_eq!(json!(null), Json::Null);
+    assert_eq!(json!(true), Json::Boolean(true));
+    assert_eq!(json!(false), Json::Boolean(false));
+    assert_eq!(json!(-2.5), Json::Number(-2.5));
+    assert_eq!(json!("hi"), Json::String("hi".into()));
+    assert_eq!(json!([]), Json::Array(vec![]));
+    assert_eq!(json!({}), Json::Object(std::collections::BTreeMap::new()));
+}
+
+#[test]
+fn nested() {
+    assert_eq!(
+        json!({
+            "three": 3,
+            "null": null,
+            "list": [false, 3, "hello", {}, [null]],
+            "nested": {"deep": {"deeper": true}}
+        }),
+        r#"{
+            "three": 3,
+            "null": null,
+            "list": [false, 3, "hello", {}, [null]],
+            "nested": {"deep": {"deeper": true}}
+        }"#
+        .parse::<Json>()
+        .unwrap()
+    );
+}
+
+#[test]
+fn interpolation() {
+    let id = 42;
+    let name = String::from("jval");
+    let tags = vec!["a", "b"];
+    let key = "dynamic";
+    let missing: Option<bool> = None;
+
+    let j = json!({
+        "id": id,
+        "name": name,
+        "tags": tags.clone(),
+        "first": tags[0],
+        "sum": 1 + 2,
+        (key): json!([id, missing]),
+        "neg": -id,
+    });
+
+    assert_eq!(j["id"], Json::Number(42.));
+    assert_eq!(j["name"], Json::String("jval".into()));
+    assert_eq!(j["tags"][1], Json::String("b".into()));
+    assert_eq!(j["first"], Json::String("a".into()));
+    assert_eq!(j["sum"], Json::Number(3.));
+    assert_eq!(j["dynamic"], json!([42, null]));
+    assert_eq!(j["neg"], Json::Number(-42.));
+}
+
+#[test]
+fn trailing_commas() {
+    assert_eq!(json!([1, 2,]), json!([1, 2]));
+    assert_eq!(
+        json!({"a": [true,], "b": {"c": null,},}),
+        json!({"a": [true], "b": {"c": null}})
+    );
+}
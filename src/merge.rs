@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+impl Json {
+    /// Apply an RFC 7386 merge patch: members of an object patch are merged
+    /// recursively, `null` members delete, and any other patch value replaces
+    /// `self` entirely.
+    pub fn merge_patch(&mut self, patch: &Self) {
+        let Json::Object(members) = patch else {
+            *self = patch.clone();
+            return;
+        };
+
+        if !self.is_object() {
+            *self = Json::Object(BTreeMap::new());
+        }
+        let Json::Object(target) = self else {
+            unreachable!("target was just made an object");
+        };
+
+        for (key, value) in members {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                target
+                    .entry(key.clone())
+                    .or_insert(Json::Null)
+                    .merge_patch(value);
+            }
+        }
+    }
+
+    /// Compute a merge patch that turns `from` into `to`.
+    ///
+    /// Merge patches cannot set a member to `null`, so any `null` members of
+    /// `to` that differ from `from` are removed rather than kept.
+    #[must_use]
+    pub fn merge_patch_diff(from: &Self, to: &Self) -> Self {
+        let (Json::Object(a), Json::Object(b)) = (from, to) else {
+            return to.clone();
+        };
+
+        let removed = a
+            .keys()
+            .filter(|k| !b.contains_key(*k))
+            .map(|k| (k.clone(), Json::Null));
+        let changed = b.iter().filter_map(|(k, new)| match a.get(k) {
+            Some(old) if old == new => None,
+            Some(old) => Some((k.clone(), Json::merge_patch_diff(old, new))),
+            None => Some((k.clone(), new.clone())),
+        });
+
+        Json::Object(removed.chain(changed).collect())
+    }
+}
@@ -0,0 +1,77 @@
+#![cfg(test)]
+
+use super::*;
+
+#[test]
+fn rfc_examples() {
+    let cases = [
+        (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+        (
+            json!({"a": "b"}),
+            json!({"b": "c"}),
+            json!({"a": "b", "b": "c"}),
+        ),
+        (json!({"a": "b"}), json!({"a": null}), json!({})),
+        (
+            json!({"a": "b", "b": "c"}),
+            json!({"a": null}),
+            json!({"b": "c"}),
+        ),
+        (json!({"a": ["b"]}), json!({"a": "c"}), json!({"a": "c"})),
+        (json!({"a": "c"}), json!({"a": ["b"]}), json!({"a": ["b"]})),
+        (
+            json!({"a": {"b": "c"}}),
+            json!({"a": {"b": "d", "c": null}}),
+            json!({"a": {"b": "d"}}),
+        ),
+        (
+            json!({"a": [{"b": "c"}]}),
+            json!({"a": [1]}),
+            json!({"a": [1]}),
+        ),
+        (json!(["a", "b"]), json!(["c", "d"]), json!(["c", "d"])),
+        (json!({"a": "b"}), json!(["c"]), json!(["c"])),
+        (json!({"a": "foo"}), json!(null), json!(null)),
+        (json!({"a": "foo"}), json!("bar"), json!("bar")),
+        (
+            json!({"e": null}),
+            json!({"a": 1}),
+            json!({"e": null, "a": 1}),
+        ),
+        (
+            json!([1, 2]),
+            json!({"a": "b", "c": null}),
+            json!({"a": "b"}),
+        ),
+        (
+            json!({}),
+            json!({"a": {"bb": {"ccc": null}}}),
+            json!({"a": {"bb": {}}}),
+        ),
+    ];
+
+    for (target, patch, expected) in &cases {
+        let mut merged = target.clone();
+        merged.merge_patch(patch);
+        assert_eq!(&merged, expected, "{target:?} + {patch:?}");
+    }
+}
+
+#[test]
+fn diff() {
+    let from = json!({"title": "Goodbye!", "author": {"given": "John", "family": "Doe"}, "tags": ["a", "b"], "content": "x"});
+    let to = json!({"title": "Hello!", "author": {"given": "John"}, "tags": ["a"], "content": "x", "phone": "555"});
+
+    let patch = Json::merge_patch_diff(&from, &to);
+    assert_eq!(
+        patch,
+        json!({"title": "Hello!", "author": {"family": null}, "tags": ["a"], "phone": "555"})
+    );
+
+    let mut merged = from.clone();
+    merged.merge_patch(&patch);
+    assert_eq!(merged, to);
+
+    assert_eq!(Json::merge_patch_diff(&to, &to), json!({}));
+    assert_eq!(Json::merge_patch_diff(&json!(1), &json!([2])), json!([2]));
+}
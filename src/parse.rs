@@ -0,0 +1,441 @@
+//! Reading JSON text, as a `&str` or as bytes in any encoding RFC 4627
+//! allows.
+//!
+//! ```
+//! use jval::{json, Json, ParseOptions};
+//!
+//! assert_eq!(Json::from_slice(b"\xef\xbb\xbf[1]").unwrap(), json!([1]));
+//!
+//! let options = ParseOptions {
+//!     detect_encoding: true,
+//!     ..ParseOptions::default()
+//! };
+//! let utf16: Vec<u8> = "[\"\u{e9}\"]".encode_utf16().flat_map(u16::to_le_bytes).collect();
+//! assert_eq!(Json::from_slice_with(&utf16, &options).unwrap(), json!(["\u{e9}"]));
+//! ```
+
+use std::ops::Range;
+use std::str::{self, Utf8Error};
+
+use super::{next_token, next_value, scan, Error, ErrorKind, Errors, Json, TokenRecord};
+
+#[cfg(test)]
+mod tests;
+
+/// How [`Json::from_str_with`] and [`Json::from_slice_with`] read their
+/// input.
+#[derive(Clone, Debug)]
+pub struct ParseOptions {
+    /// Skip a byte order mark at the start of the input. On by default.
+    pub skip_bom: bool,
+    /// Tell UTF-16 and UTF-32 from UTF-8 by the zero bytes among the first
+    /// four, per RFC 4627 section 3, and read the input in that encoding.
+    /// Off by default.
+    pub detect_encoding: bool,
+    /// Caps on how big the input and what's in it may be. None by default.
+    pub limits: Limits,
+    /// Find where the tokens are in bulk, with vector instructions where
+    /// the CPU has them, before reading them. The result is the same
+    /// either way, and it's faster on most input. On by default.
+    pub bulk_scan: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            skip_bom: true,
+            detect_encoding: false,
+            limits: Limits::default(),
+            bulk_scan: true,
+        }
+    }
+}
+
+/// Caps on the size of a document, for reading input that isn't trusted.
+/// Each is unlimited when `None`.
+///
+/// Parsing stops at the first limit exceeded, with an
+/// [`ErrorKind::LimitExceeded`] saying which it was. The input length is
+/// checked before anything else, and the others as the input is tokenized
+/// and the tree built, so no more than about the limits is ever held in
+/// memory.
+///
+/// ```
+/// use jval::{ErrorKind, Json, Limit, Limits, ParseOptions};
+///
+/// let options = ParseOptions {
+///     limits: Limits {
+///         array_length: Some(2),
+///         ..Limits::default()
+///     },
+///     ..ParseOptions::default()
+/// };
+/// assert_eq!(
+///     Json::from_str_with("[[1, 2], [1, 2, 3]]", &options).unwrap_err(),
+///     vec![(ErrorKind::LimitExceeded { which: Limit::ArrayLength }, 9..10)]
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Limits {
+    /// The length of the input in bytes.
+    pub input_length: Option<usize>,
+    /// The length of a string, member names included, in bytes once its
+    /// escapes are read.
+    pub string_length: Option<usize>,
+    /// The length of a number as written, in bytes.
+    pub number_length: Option<usize>,
+    /// The number of elements in an array.
+    pub array_length: Option<usize>,
+    /// The number of members in an object.
+    pub object_length: Option<usize>,
+    /// The number of values in the document, counting each array, object
+    /// and scalar in it.
+    pub nodes: Option<usize>,
+    /// How deeply arrays and objects nest. A scalar at the top level is at
+    /// depth 0, and what's in an array or object one deeper than it.
+    pub depth: Option<usize>,
+}
+
+/// Which of the [`Limits`] was exceeded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    /// [`Limits::input_length`]. The error covers the input past the limit.
+    InputLength,
+    /// [`Limits::string_length`]. The error covers the string.
+    StringLength,
+    /// [`Limits::number_length`]. The error covers the number.
+    NumberLength,
+    /// [`Limits::array_length`]. The error covers the array's `[`.
+    ArrayLength,
+    /// [`Limits::object_length`]. The error covers the object's `{`.
+    ObjectLength,
+    /// [`Limits::nodes`]. The error covers the first token of the value
+    /// past the limit.
+    Nodes,
+    /// [`Limits::depth`]. The error covers the `[` or `{` past the limit.
+    Depth,
+}
+
+impl Limits {
+    /// Whether `count` is past the limit on `which`.
+    pub(crate) fn exceeded(&self, which: Limit, count: usize) -> bool {
+        let max = match which {
+            Limit::InputLength => self.input_length,
+            Limit::StringLength => self.string_length,
+            Limit::NumberLength => self.number_length,
+            Limit::ArrayLength => self.array_length,
+            Limit::ObjectLength => self.object_length,
+            Limit::Nodes => self.nodes,
+            Limit::Depth => self.depth,
+        };
+        max.is_some_and(|max| count > max)
+    }
+
+    /// Whether the first `tokens` tokens must start more values than
+    /// allowed, so tokenizing can stop. A value takes at most five: itself
+    /// or its brackets, and a member name, a colon and a comma. Up to three
+    /// of those may come before the value starts.
+    pub(crate) fn too_many_tokens(&self, tokens: usize) -> bool {
+        self.exceeded(Limit::Nodes, tokens.saturating_sub(3).div_ceil(5))
+    }
+}
+
+/// The encodings of RFC 4627 section 3.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    Utf16Be,
+    Utf16Le,
+    Utf32Be,
+    Utf32Le,
+}
+
+impl Encoding {
+    /// Detect the encoding from the byte order mark, or else from which of
+    /// the first four bytes are zero: the first two characters of JSON text
+    /// are ASCII, so they're padded with zeros in UTF-16 and UTF-32.
+    fn detect(bytes: &[u8]) -> Self {
+        match bytes {
+            [0, 0, 0xfe, 0xff, ..] | [0, 0, 0, _, ..] => Encoding::Utf32Be,
+            [0xff, 0xfe, 0, 0, ..] | [_, 0, 0, 0, ..] => Encoding::Utf32Le,
+            [0xfe, 0xff, ..] | [0, _, ..] => Encoding::Utf16Be,
+            [0xff, 0xfe, ..] | [_, 0, ..] => Encoding::Utf16Le,
+            _ => Encoding::Utf8,
+        }
+    }
+
+    /// The bytes `c` takes up in this encoding.
+    fn width(self, c: char) -> usize {
+        match self {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16Be | Encoding::Utf16Le => 2 * c.len_utf16(),
+            Encoding::Utf32Be | Encoding::Utf32Le => 4,
+        }
+    }
+
+    /// Decode the character `bytes` starts with, and the bytes it takes up.
+    fn decode(self, bytes: &[u8]) -> Option<(char, usize)> {
+        let unit = |i: usize| match (self, bytes.get(i..i + 2)?) {
+            (Encoding::Utf16Be, &[a, b]) => Some(u16::from_be_bytes([a, b])),
+            (_, &[a, b]) => Some(u16::from_le_bytes([a, b])),
+            _ => None,
+        };
+        match self {
+            Encoding::Utf8 => None,
+            Encoding::Utf16Be | Encoding::Utf16Le => match unit(0)? {
+                high @ 0xd800..=0xdbff => match unit(2)? {
+                    low @ 0xdc00..=0xdfff => {
+                        let c = 0x10000
+                            + ((u32::from(high) - 0xd800) << 10)
+                            + (u32::from(low) - 0xdc00);
+                        Some((char::from_u32(c)?, 4))
+                    }
+                    _ => None,
+                },
+                u => Some((char::from_u32(u.into())?, 2)),
+            },
+            Encoding::Utf32Be | Encoding::Utf32Le => {
+                let &[a, b, c, d] = bytes.get(..4)? else {
+                    return None;
+                };
+                let c = if self == Encoding::Utf32Be {
+                    u32::from_be_bytes([a, b, c, d])
+                } else {
+                    u32::from_le_bytes([a, b, c, d])
+                };
+                Some((char::from_u32(c)?, 4))
+            }
+        }
+    }
+}
+
+impl Json {
+    /// Parse JSON text encoded as UTF-8, skipping a byte order mark.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`str::parse`], or with [`ErrorKind::InvalidUtf8`] where
+    /// the input stops being UTF-8.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, Errors> {
+        Json::from_slice_with(bytes, &ParseOptions::default())
+    }
+
+    /// Like [`Json::from_slice`], reading the input according to `options`.
+    /// Error spans are offsets into `bytes` whatever the encoding.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Json::from_slice`], with [`ErrorKind::InvalidUnicode`]
+    /// where UTF-16 or UTF-32 input has a code unit that doesn't decode, or
+    /// with [`ErrorKind::LimitExceeded`] at the first of `options.limits`
+    /// exceeded.
+    pub fn from_slice_with(bytes: &[u8], options: &ParseOptions) -> Result<Self, Errors> {
+        check_length(bytes.len(), &options.limits)?;
+        let encoding = if options.detect_encoding {
+            Encoding::detect(bytes)
+        } else {
+            Encoding::Utf8
+        };
+        if encoding == Encoding::Utf8 {
+            return match str::from_utf8(bytes) {
+                Ok(s) => parse(s, options),
+                Err(e) => Err(invalid_utf8(bytes, &e, options)),
+            };
+        }
+
+        let text = transcode(bytes, encoding)?;
+        // Map the spans in the UTF-8 back onto the input.
+        let offset = |end: usize| -> usize {
+            text.char_indices()
+                .take_while(|&(i, _)| i < end)
+                .map(|(_, c)| encoding.width(c))
+                .sum()
+        };
+        parse(&text, options).map_err(|errors| {
+            errors
+                .into_iter()
+                .map(|(kind, span)| (kind, offset(span.start)..offset(span.end)))
+                .collect()
+        })
+    }
+
+    /// Like [`str::parse`], reading the input according to `options`.
+    ///
+    /// # Errors
+    ///
+    /// Fails with each error found and the byte range it covers, or with
+    /// [`ErrorKind::LimitExceeded`] at the first of `options.limits`
+    /// exceeded.
+    pub fn from_str_with(s: &str, options: &ParseOptions) -> Result<Self, Errors> {
+        check_length(s.len(), &options.limits)?;
+        parse(s, options)
+    }
+}
+
+/// Fail if an input of `len` bytes is longer than `limits` allow.
+fn check_length(len: usize, limits: &Limits) -> Result<(), Errors> {
+    match limits.input_length {
+        Some(max) if len > max => Err(vec![(
+            ErrorKind::LimitExceeded {
+                which: Limit::InputLength,
+            },
+            max..len,
+        )]),
+        _ => Ok(()),
+    }
+}
+
+/// Parse `s`, with its length already checked.
+fn parse(s: &str, options: &ParseOptions) -> Result<Json, Errors> {
+    parse_tokens(s, options).map(|(json, _)| json)
+}
+
+/// Like [`parse`], also returning the tokens the value was built from, with
+/// their offsets from the end of `s`.
+pub(crate) fn parse_tokens(
+    mut s: &str,
+    options: &ParseOptions,
+) -> Result<(Json, Vec<TokenRecord>), Errors> {
+    let s_len = s.len();
+    let calc_range = |(e, l, f)| {
+        (
+            e,
+            Range {
+                start: s_len - f,
+                end: s_len - f + l,
+            },
+        )
+    };
+
+    s = skip_bom(s, options);
+    let mut tokens = Vec::new();
+    let mut errvec = Vec::new();
+
+    // Scan for where the tokens are in bulk, falling back to finding
+    // them a character at a time if that gives up.
+    let scanned = if options.bulk_scan {
+        scan::tokens(s, &options.limits)
+    } else {
+        None
+    };
+    if let Some((scanned, rest)) = scanned {
+        tokens = scanned;
+        s = rest;
+    } else {
+        let mut errors = Vec::new();
+        s = tokenize(s, &options.limits, &mut tokens, &mut errors);
+        errvec.extend(errors.into_iter().map(calc_range));
+    }
+
+    if !errvec.is_empty() {
+        return Err(errvec);
+    }
+
+    let mut toks = &tokens[..];
+    let mut values = Vec::new();
+    let mut nodes = 0;
+
+    while !toks.is_empty() {
+        match next_value(toks, &options.limits, &mut nodes, 0) {
+            Err((e, t_)) => {
+                errvec.push(calc_range(e));
+                toks = t_;
+            }
+            Ok((Some(v), t_)) => {
+                values.push(v);
+                toks = t_;
+            }
+            Ok((None, _)) => break,
+        }
+    }
+
+    if !errvec.is_empty() {
+        return Err(errvec);
+    }
+
+    if values.len() == 1 {
+        Ok((values.remove(0), tokens))
+    } else {
+        Err(vec![calc_range((
+            ErrorKind::UnexpectedToken,
+            s.len(),
+            s.len(),
+        ))])
+    }
+}
+
+fn skip_bom<'a>(s: &'a str, options: &ParseOptions) -> &'a str {
+    if options.skip_bom {
+        s.strip_prefix('\u{feff}').unwrap_or(s)
+    } else {
+        s
+    }
+}
+
+/// Tokenize `s` a character at a time into `tokens`, skipping past
+/// `errors`, and return what's left after the last token. Stops once there
+/// must be more values than `limits` allow, leaving building the tree to
+/// find the first one past the limit.
+fn tokenize<'a>(
+    mut s: &'a str,
+    limits: &Limits,
+    tokens: &mut Vec<TokenRecord>,
+    errors: &mut Vec<Error>,
+) -> &'a str {
+    while !s.is_empty() {
+        match next_token(s, limits) {
+            Err(e) => {
+                s = &s[s.len() - e.2 + e.1..];
+                errors.push(e);
+            }
+            Ok((Some(_), _)) if limits.too_many_tokens(tokens.len() + 1) => break,
+            Ok((Some(t), s_)) => {
+                tokens.push(t);
+                s = s_;
+            }
+            Ok((None, _)) => break,
+        }
+    }
+    s
+}
+
+/// The errors in `bytes` up to where `error` says it stops being UTF-8,
+/// and that.
+fn invalid_utf8(bytes: &[u8], error: &Utf8Error, options: &ParseOptions) -> Errors {
+    let valid = error.valid_up_to();
+    let text = str::from_utf8(&bytes[..valid]).unwrap_or_default();
+    let mut errors = Vec::new();
+    tokenize(
+        skip_bom(text, options),
+        &options.limits,
+        &mut Vec::new(),
+        &mut errors,
+    );
+
+    // Errors that run up to where the text stops may only be there because
+    // it stops, like a string missing its closing quote.
+    let mut errors: Errors = errors
+        .into_iter()
+        .map(|(kind, len, rest)| (kind, valid - rest..valid - rest + len))
+        .filter(|(_, span)| span.end < valid)
+        .collect();
+    let len = error.error_len().unwrap_or(bytes.len() - valid);
+    errors.push((ErrorKind::InvalidUtf8, valid..valid + len));
+    errors
+}
+
+/// Decode `bytes` in `encoding` into UTF-8.
+fn transcode(bytes: &[u8], encoding: Encoding) -> Result<String, Errors> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let Some((c, len)) = encoding.decode(&bytes[pos..]) else {
+            // The code unit that doesn't decode, or what's left of one.
+            let end = bytes.len().min(pos + encoding.width('\0'));
+            return Err(vec![(ErrorKind::InvalidUnicode, pos..end)]);
+        };
+        text.push(c);
+        pos += len;
+    }
+    Ok(text)
+}
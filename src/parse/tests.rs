@@ -0,0 +1,324 @@
+#![cfg(test)]
+
+use super::*;
+
+fn detecting() -> ParseOptions {
+    ParseOptions {
+        detect_encoding: true,
+        ..ParseOptions::default()
+    }
+}
+
+fn utf16(s: &str, big_endian: bool) -> Vec<u8> {
+    s.encode_utf16()
+        .flat_map(|u| {
+            if big_endian {
+                u.to_be_bytes()
+            } else {
+                u.to_le_bytes()
+            }
+        })
+        .collect()
+}
+
+fn utf32(s: &str, big_endian: bool) -> Vec<u8> {
+    s.chars()
+        .flat_map(|c| {
+            if big_endian {
+                u32::from(c).to_be_bytes()
+            } else {
+                u32::from(c).to_le_bytes()
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn byte_order_marks() {
+    assert_eq!("\u{feff}[1]".parse::<Json>().unwrap(), json!([1]));
+    assert_eq!(Json::from_slice(b"\xef\xbb\xbf {}").unwrap(), json!({}));
+    assert_eq!(
+        Json::from_slice(b"\xef\xbb\xbf[1,]").unwrap_err(),
+        vec![(ErrorKind::UnexpectedToken, 6..7)]
+    );
+
+    let strict = ParseOptions {
+        skip_bom: false,
+        ..ParseOptions::default()
+    };
+    assert_eq!(
+        Json::from_slice_with(b"\xef\xbb\xbf[1]", &strict).unwrap_err(),
+        vec![(ErrorKind::UnexpectedToken, 0..3)]
+    );
+    // Only at the start.
+    assert!(Json::from_slice(b"[\xef\xbb\xbf1]").is_err());
+}
+
+#[test]
+fn invalid_utf8() {
+    assert_eq!(
+        Json::from_slice(b"[\"a\xffb\"]").unwrap_err(),
+        vec![(ErrorKind::InvalidUtf8, 3..4)]
+    );
+    assert_eq!(
+        Json::from_slice(b"[1\xe2\x82]").unwrap_err(),
+        vec![(ErrorKind::InvalidUtf8, 2..4)]
+    );
+    // Truncated at the end.
+    assert_eq!(
+        Json::from_slice(b"\"\xe2\x82").unwrap_err(),
+        vec![(ErrorKind::InvalidUtf8, 1..3)]
+    );
+    // Errors before it are still reported.
+    assert_eq!(
+        Json::from_slice(b"[1.,\n\"\xc0\"]").unwrap_err(),
+        vec![
+            (ErrorKind::InvalidNumber, 2..3),
+            (ErrorKind::InvalidUtf8, 6..7)
+        ]
+    );
+    assert_eq!(
+        Json::from_slice("{\"\u{e9}\": [1, 2]}".as_bytes()).unwrap(),
+        json!({"\u{e9}": [1, 2]})
+    );
+}
+
+#[test]
+fn encodings() {
+    let doc = "{\"a\": [\"\u{e9}\u{1f600}\", 1]}";
+    let expected = json!({"a": ["\u{e9}\u{1f600}", 1]});
+    for &big_endian in &[true, false] {
+        for bytes in &[utf16(doc, big_endian), utf32(doc, big_endian)] {
+            assert_eq!(
+                Json::from_slice_with(bytes, &detecting()).unwrap(),
+                expected
+            );
+        }
+        // A byte order mark, and a single character.
+        let bytes = utf16("\u{feff}1", big_endian);
+        assert_eq!(
+            Json::from_slice_with(&bytes, &detecting()).unwrap(),
+            json!(1)
+        );
+        let bytes = utf32("\u{feff}\"x\"", big_endian);
+        assert_eq!(
+            Json::from_slice_with(&bytes, &detecting()).unwrap(),
+            json!("x")
+        );
+    }
+    assert_eq!(
+        Json::from_slice_with(doc.as_bytes(), &detecting()).unwrap(),
+        expected
+    );
+
+    // Without detection, the zero bytes are just errors.
+    assert!(Json::from_slice(&utf16("[1]", false)).is_err());
+}
+
+#[test]
+fn encoding_errors() {
+    // Spans are offsets into the input, not the UTF-8 it's read as.
+    assert_eq!(
+        Json::from_slice_with(&utf16("[\"\u{1f600}\", 1,]", true), &detecting()).unwrap_err(),
+        vec![(ErrorKind::UnexpectedToken, 18..20)]
+    );
+    assert_eq!(
+        Json::from_slice_with(&utf32("[\u{e9}]", false), &detecting()).unwrap_err(),
+        vec![(ErrorKind::UnexpectedToken, 4..8)]
+    );
+    // A lone surrogate, a code point past U+10FFFF and a dangling byte.
+    assert_eq!(
+        Json::from_slice_with(b"[\0\"\0\x00\xdc\"\0]\0", &detecting()).unwrap_err(),
+        vec![(ErrorKind::InvalidUnicode, 4..6)]
+    );
+    assert_eq!(
+        Json::from_slice_with(b"\"\0\0\0\0\0\x11\0\"\0\0\0", &detecting()).unwrap_err(),
+        vec![(ErrorKind::InvalidUnicode, 4..8)]
+    );
+    assert_eq!(
+        Json::from_slice_with(b"1\0\0", &detecting()).unwrap_err(),
+        vec![(ErrorKind::InvalidUnicode, 2..3)]
+    );
+}
+
+fn limited(limits: Limits) -> ParseOptions {
+    ParseOptions {
+        limits,
+        ..ParseOptions::default()
+    }
+}
+
+fn exceeded(which: Limit, span: Range<usize>) -> Errors {
+    vec![(ErrorKind::LimitExceeded { which }, span)]
+}
+
+#[test]
+fn input_length() {
+    let options = limited(Limits {
+        input_length: Some(4),
+        ..Limits::default()
+    });
+    assert_eq!(
+        Json::from_str_with("[1,2]", &options).unwrap_err(),
+        exceeded(Limit::InputLength, 4..5)
+    );
+    assert_eq!(Json::from_str_with("[12]", &options).unwrap(), json!([12]));
+    // The bytes are counted, whatever they're encoded in.
+    let options = ParseOptions {
+        detect_encoding: true,
+        ..options
+    };
+    assert_eq!(
+        Json::from_slice_with(&utf16("[1]", false), &options).unwrap_err(),
+        exceeded(Limit::InputLength, 4..6)
+    );
+    assert_eq!(
+        Json::from_slice_with(b"[1,\xff]", &options).unwrap_err(),
+        exceeded(Limit::InputLength, 4..5)
+    );
+}
+
+#[test]
+fn string_and_number_length() {
+    let options = limited(Limits {
+        string_length: Some(3),
+        number_length: Some(4),
+        ..Limits::default()
+    });
+    assert_eq!(
+        Json::from_str_with("{\"abc\": [\"\\u00e9a\", -1.5]}", &options).unwrap(),
+        json!({"abc": ["\u{e9}a", -1.5]})
+    );
+    assert_eq!(
+        Json::from_str_with("[\"abc\", \"abcd\", 1]", &options).unwrap_err(),
+        exceeded(Limit::StringLength, 8..14)
+    );
+    // Counted once the escapes are read, and for member names too.
+    assert_eq!(
+        Json::from_str_with("{\"\\u00e9\\u00e9\": 1}", &options).unwrap_err(),
+        exceeded(Limit::StringLength, 1..15)
+    );
+    assert_eq!(
+        Json::from_str_with("[1, -1.25]", &options).unwrap_err(),
+        exceeded(Limit::NumberLength, 4..9)
+    );
+}
+
+#[test]
+fn member_counts() {
+    let options = limited(Limits {
+        array_length: Some(2),
+        object_length: Some(1),
+        ..Limits::default()
+    });
+    assert_eq!(
+        Json::from_str_with("[{\"a\": [1, 2]}, {}]", &options).unwrap(),
+        json!([{"a": [1, 2]}, {}])
+    );
+    assert_eq!(
+        Json::from_str_with("[1, [1, 2, 3], 4]", &options).unwrap_err(),
+        exceeded(Limit::ArrayLength, 4..5)
+    );
+    assert_eq!(
+        Json::from_str_with("[{\"a\": 1, \"b\": 2}]", &options).unwrap_err(),
+        exceeded(Limit::ObjectLength, 1..2)
+    );
+}
+
+#[test]
+fn nodes() {
+    let options = limited(Limits {
+        nodes: Some(4),
+        ..Limits::default()
+    });
+    assert_eq!(
+        Json::from_str_with("{\"a\": [1, {}]}", &options).unwrap(),
+        json!({"a": [1, {}]})
+    );
+    assert_eq!(
+        Json::from_str_with("[1, [2, 3]]", &options).unwrap_err(),
+        exceeded(Limit::Nodes, 8..9)
+    );
+    // Tokenizing stops once there must be too many, and the value past the
+    // limit is still the one reported.
+    let many = format!("[{}0]", "0,".repeat(100));
+    assert_eq!(
+        Json::from_str_with(&many, &options).unwrap_err(),
+        exceeded(Limit::Nodes, 7..8)
+    );
+    let nested = format!("{}1{}", "[".repeat(100), "]".repeat(100));
+    assert_eq!(
+        Json::from_str_with(&nested, &options).unwrap_err(),
+        exceeded(Limit::Nodes, 4..5)
+    );
+    let members = format!("{{{}\"z\": 1}}", "\"a\": {},".repeat(100));
+    assert_eq!(
+        Json::from_str_with(&members, &options).unwrap_err(),
+        exceeded(Limit::Nodes, 30..31)
+    );
+    // The same without the bulk scan.
+    let slow = ParseOptions {
+        bulk_scan: false,
+        ..options
+    };
+    for (doc, span) in [(&many, 7..8), (&nested, 4..5), (&members, 30..31)] {
+        assert_eq!(
+            Json::from_str_with(doc, &slow).unwrap_err(),
+            exceeded(Limit::Nodes, span)
+        );
+    }
+}
+
+#[test]
+fn depth() {
+    let options = limited(Limits {
+        depth: Some(2),
+        ..Limits::default()
+    });
+    assert_eq!(
+        Json::from_str_with("[{\"a\": 1}, []]", &options).unwrap(),
+        json!([{"a": 1}, []])
+    );
+    assert_eq!(
+        Json::from_str_with("{\"a\": [[1]]}", &options).unwrap_err(),
+        exceeded(Limit::Depth, 7..8)
+    );
+    // Deep enough to overflow the stack without the limit.
+    let deep = "[".repeat(1_000_000);
+    assert_eq!(
+        Json::from_str_with(&deep, &options).unwrap_err(),
+        exceeded(Limit::Depth, 2..3)
+    );
+
+    let options = limited(Limits {
+        depth: Some(0),
+        ..Limits::default()
+    });
+    assert_eq!(Json::from_str_with("\"a\"", &options).unwrap(), json!("a"));
+    assert!(Json::from_str_with("[]", &options).is_err());
+}
+
+#[test]
+fn without_bulk_scan() {
+    let options = ParseOptions {
+        bulk_scan: false,
+        ..ParseOptions::default()
+    };
+    let documents = [
+        "{\"a\": [1, -2.5e3, \"b\\\"\\u00e9\"], \"c\": {}, \"d\": null}",
+        "\u{feff} [true, false, \"\u{1f600}\"]  \n",
+        "[1,\u{a0}2]",
+        "",
+        "1 2",
+        "[1,]",
+        "[truex]",
+        "\"abc",
+    ];
+    for doc in &documents {
+        assert_eq!(
+            Json::from_str_with(doc, &options),
+            doc.parse::<Json>(),
+            "{doc:?}"
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! JSON Patch (RFC 6902): parsing, atomic application and generation.
+//!
+//! ```
+//! # use jval::{json, patch::{self, Patch}};
+//! let mut doc = json!({"name": "svc", "replicas": 1});
+//! let patch = Patch::from_json(&json!([
+//!     {"op": "replace", "path": "/replicas", "value": 3},
+//!     {"op": "add", "path": "/tags", "value": ["web"]}
+//! ])).unwrap();
+//! patch.apply(&mut doc).unwrap();
+//! assert_eq!(doc, json!({"name": "svc", "replicas": 3, "tags": ["web"]}));
+//!
+//! let back = patch::generate(&doc, &json!({"name": "svc", "replicas": 1}));
+//! back.apply(&mut doc).unwrap();
+//! assert_eq!(doc, json!({"name": "svc", "replicas": 1}));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::pointer::{self, PointerError};
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    Add { path: String, value: Json },
+    Remove { path: String },
+    Replace { path: String, value: Json },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Json },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchErrorKind {
+    /// The patch document itself is not a valid RFC 6902 patch.
+    Malformed(String),
+    /// A `path` or `from` pointer could not be resolved.
+    Pointer(PointerError),
+    /// A `test` operation found a different value.
+    TestFailed,
+    /// A `move` whose `path` is inside its own `from`.
+    MoveIntoChild,
+}
+
+/// An error in a patch, and the index of the operation that caused it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchError {
+    pub index: usize,
+    pub kind: PatchErrorKind,
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation {}: ", self.index)?;
+        match &self.kind {
+            PatchErrorKind::Malformed(why) => write!(f, "{why}"),
+            PatchErrorKind::Pointer(e) => write!(f, "{e}"),
+            PatchErrorKind::TestFailed => write!(f, "test failed"),
+            PatchErrorKind::MoveIntoChild => {
+                write!(f, "cannot move a value into one of its children")
+            }
+        }
+    }
+}
+
+impl Operation {
+    fn name(&self) -> &'static str {
+        match self {
+            Operation::Add { .. } => "add",
+            Operation::Remove { .. } => "remove",
+            Operation::Replace { .. } => "replace",
+            Operation::Move { .. } => "move",
+            Operation::Copy { .. } => "copy",
+            Operation::Test { .. } => "test",
+        }
+    }
+
+    fn from_json(op: &Json) -> Result<Self, PatchErrorKind> {
+        let malformed = |why: &str| PatchErrorKind::Malformed(why.to_string());
+
+        let Json::Object(members) = op else {
+            return Err(malformed("operation must be an object"));
+        };
+
+        let pointer = |name: &str| match members.get(name) {
+            Some(Json::String(p)) => pointer::parse(p)
+                .map(|_| p.clone())
+                .map_err(PatchErrorKind::Pointer),
+            Some(_) => Err(malformed(&format!("\"{name}\" must be a string"))),
+            None => Err(malformed(&format!("missing \"{name}\""))),
+        };
+        let value = || {
+            members
+                .get("value")
+                .cloned()
+                .ok_or_else(|| malformed("missing \"value\""))
+        };
+
+        let name = match members.get("op") {
+            Some(Json::String(name)) => name.as_str(),
+            Some(_) => return Err(malformed("\"op\" must be a string")),
+            None => return Err(malformed("missing \"op\"")),
+        };
+
+        Ok(match name {
+            "add" => Operation::Add {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            "remove" => Operation::Remove {
+                path: pointer("path")?,
+            },
+            "replace" => Operation::Replace {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "test" => Operation::Test {
+                path: pointer("path")?,
+                value: value()?,
+            },
+            other => return Err(malformed(&format!("unknown op \"{other}\""))),
+        })
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> Json {
+        let mut out = BTreeMap::new();
+        out.insert("op".to_string(), Json::from(self.name()));
+
+        match self {
+            Operation::Add { path, value }
+            | Operation::Replace { path, value }
+            | Operation::Test { path, value } => {
+                out.insert("path".to_string(), Json::from(path.as_str()));
+                out.insert("value".to_string(), value.clone());
+            }
+            Operation::Remove { path } => {
+                out.insert("path".to_string(), Json::from(path.as_str()));
+            }
+            Operation::Move { from, path } | Operation::Copy { from, path } => {
+                out.insert("from".to_string(), Json::from(from.as_str()));
+                out.insert("path".to_string(), Json::from(path.as_str()));
+            }
+        }
+
+        Json::Object(out)
+    }
+
+    fn apply(&self, target: &mut Json) -> Result<(), PatchErrorKind> {
+        match self {
+            Operation::Add { path, value } => {
+                target.pointer_insert(path, value.clone())?;
+            }
+            Operation::Remove { path } => {
+                target.pointer_remove(path)?;
+            }
+            Operation::Replace { path, value } => {
+                *target.try_pointer_mut(path)? = value.clone();
+            }
+            Operation::Move { from, path } => {
+                if from == path {
+                    target.try_pointer(from)?;
+                    return Ok(());
+                }
+                if path.starts_with(from.as_str()) && path[from.len()..].starts_with('/') {
+                    return Err(PatchErrorKind::MoveIntoChild);
+                }
+                let value = target.pointer_remove(from)?;
+                target.pointer_insert(path, value)?;
+            }
+            Operation::Copy { from, path } => {
+                let value = target.try_pointer(from)?.clone();
+                target.pointer_insert(path, value)?;
+            }
+            Operation::Test { path, value } => {
+                if target.try_pointer(path)? != value {
+                    return Err(PatchErrorKind::TestFailed);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl From<PointerError> for PatchErrorKind {
+    fn from(e: PointerError) -> Self {
+        PatchErrorKind::Pointer(e)
+    }
+}
+
+/// A sequence of operations, applied in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Patch(pub Vec<Operation>);
+
+impl Patch {
+    /// Read a patch document: an array of operation objects.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the document is not an array, or if an operation is missing
+    /// a member, has an unknown `op`, or contains a malformed pointer.
+    pub fn from_json(doc: &Json) -> Result<Self, PatchError> {
+        let Json::Array(ops) = doc else {
+            return Err(PatchError {
+                index: 0,
+                kind: PatchErrorKind::Malformed("patch must be an array".to_string()),
+            });
+        };
+
+        ops.iter()
+            .enumerate()
+            .map(|(index, op)| Operation::from_json(op).map_err(|kind| PatchError { index, kind }))
+            .collect::<Result<_, _>>()
+            .map(Patch)
+    }
+
+    #[must_use]
+    pub fn to_json(&self) -> Json {
+        self.0.iter().map(Operation::to_json).collect()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Apply every operation to `target`. If any operation fails, `target`
+    /// is left unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the index of the first operation that could not be applied.
+    pub fn apply(&self, target: &mut Json) -> Result<(), PatchError> {
+        let mut working = target.clone();
+
+        for (index, op) in self.0.iter().enumerate() {
+            op.apply(&mut working)
+                .map_err(|kind| PatchError { index, kind })?;
+        }
+
+        *target = working;
+        Ok(())
+    }
+}
+
+/// Generate a patch that turns `from` into `to`.
+///
+/// Objects are compared member by member and arrays by their longest common
+/// subsequence, so unchanged elements are kept and only the differences are
+/// written out. Arrays too long to compare that way, once what they start
+/// and end with in common is set aside, are replaced whole.
+#[must_use]
+pub fn generate(from: &Json, to: &Json) -> Patch {
+    let mut ops = Vec::new();
+    generate_into(from, to, &mut Vec::new(), &mut ops);
+    Patch(ops)
+}
+
+fn generate_into(from: &Json, to: &Json, path: &mut Vec<String>, ops: &mut Vec<Operation>) {
+    if from == to {
+        return;
+    }
+
+    match (from, to) {
+        (Json::Object(a), Json::Object(b)) => {
+            for key in a.keys().filter(|k| !b.contains_key(*k)) {
+                ops.push(Operation::Remove {
+                    path: child(path, key),
+                });
+            }
+            for (key, new) in b {
+                match a.get(key) {
+                    Some(old) => {
+                        path.push(key.clone());
+                        generate_into(old, new, path, ops);
+                        path.pop();
+                    }
+                    None => ops.push(Operation::Add {
+                        path: child(path, key),
+                        value: new.clone(),
+                    }),
+                }
+            }
+        }
+        (Json::Array(a), Json::Array(b)) => generate_array(a, b, path, ops),
+        _ => ops.push(Operation::Replace {
+            path: pointer::join(path.iter()),
+            value: to.clone(),
+        }),
+    }
+}
+
+fn child(path: &[String], token: &str) -> String {
+    pointer::join(path.iter().map(String::as_str).chain(Some(token)))
+}
+
+enum Edit {
+    Keep,
+    Delete(usize),
+    Insert(usize),
+}
+
+/// The most cells the LCS table in [`edit_script`] may have, so that
+/// diffing long arrays that have little in common doesn't take quadratic
+/// time and memory.
+const MAX_LCS_CELLS: usize = 1 << 22;
+
+/// The shortest edit script between two arrays, from an LCS table of what's
+/// between their common prefix and suffix, or `None` if that table would
+/// have more than [`MAX_LCS_CELLS`].
+fn edit_script(a: &[Json], b: &[Json]) -> Option<Vec<Edit>> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a, b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    if (a.len() + 1).saturating_mul(b.len() + 1) > MAX_LCS_CELLS {
+        return None;
+    }
+
+    let mut lcs = vec![vec![0_usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut script: Vec<_> = (0..prefix).map(|_| Edit::Keep).collect();
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            script.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            script.push(Edit::Delete(prefix + i));
+            i += 1;
+        } else {
+            script.push(Edit::Insert(prefix + j));
+            j += 1;
+        }
+    }
+    script.extend((0..suffix).map(|_| Edit::Keep));
+    Some(script)
+}
+
+fn generate_array(a: &[Json], b: &[Json], path: &mut Vec<String>, ops: &mut Vec<Operation>) {
+    let Some(script) = edit_script(a, b) else {
+        ops.push(Operation::Replace {
+            path: pointer::join(path.iter()),
+            value: Json::Array(b.to_vec()),
+        });
+        return;
+    };
+    let mut index = 0;
+    let mut rest = &script[..];
+
+    while let Some(first) = rest.first() {
+        if let Edit::Keep = first {
+            index += 1;
+            rest = &rest[1..];
+            continue;
+        }
+
+        let run = rest
+            .iter()
+            .position(|e| matches!(e, Edit::Keep))
+            .unwrap_or(rest.len());
+        let (deleted, inserted): (Vec<_>, Vec<_>) = rest[..run]
+            .iter()
+            .partition(|e| matches!(e, Edit::Delete(_)));
+        rest = &rest[run..];
+
+        // an element that was deleted and another inserted in its place is
+        // better expressed as a change to that element
+        let paired = deleted.len().min(inserted.len());
+        for (d, n) in deleted.iter().zip(&inserted) {
+            if let (Edit::Delete(i), Edit::Insert(j)) = (d, n) {
+                path.push(index.to_string());
+                generate_into(&a[*i], &b[*j], path, ops);
+                path.pop();
+                index += 1;
+            }
+        }
+        for _ in &deleted[paired..] {
+            ops.push(Operation::Remove {
+                path: child(path, &index.to_string()),
+            });
+        }
+        for n in &inserted[paired..] {
+            if let Edit::Insert(j) = n {
+                ops.push(Operation::Add {
+                    path: child(path, &index.to_string()),
+                    value: b[*j].clone(),
+                });
+                index += 1;
+            }
+        }
+    }
+}
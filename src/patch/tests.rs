@@ -0,0 +1,237 @@
+#![cfg(test)]
+
+use super::*;
+
+fn patched(mut doc: Json, patch: &Json) -> Result<Json, PatchError> {
+    Patch::from_json(patch)?.apply(&mut doc)?;
+    Ok(doc)
+}
+
+#[test]
+fn rfc_examples() {
+    assert_eq!(
+        patched(
+            json!({"foo": "bar"}),
+            &json!([{"op": "add", "path": "/baz", "value": "qux"}])
+        ),
+        Ok(json!({"baz": "qux", "foo": "bar"}))
+    );
+    assert_eq!(
+        patched(
+            json!({"foo": ["bar", "baz"]}),
+            &json!([{"op": "add", "path": "/foo/1", "value": "qux"}])
+        ),
+        Ok(json!({"foo": ["bar", "qux", "baz"]}))
+    );
+    assert_eq!(
+        patched(
+            json!({"baz": "qux", "foo": "bar"}),
+            &json!([{"op": "remove", "path": "/baz"}])
+        ),
+        Ok(json!({"foo": "bar"}))
+    );
+    assert_eq!(
+        patched(
+            json!({"baz": "qux", "foo": "bar"}),
+            &json!([{"op": "replace", "path": "/baz", "value": "boo"}])
+        ),
+        Ok(json!({"baz": "boo", "foo": "bar"}))
+    );
+    assert_eq!(
+        patched(
+            json!({"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}),
+            &json!([{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}])
+        ),
+        Ok(json!({"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}))
+    );
+    assert_eq!(
+        patched(
+            json!({"foo": ["all", "grass", "cows", "eat"]}),
+            &json!([{"op": "move", "from": "/foo/1", "path": "/foo/3"}])
+        ),
+        Ok(json!({"foo": ["all", "cows", "eat", "grass"]}))
+    );
+    assert_eq!(
+        patched(
+            json!({"foo": ["bar"]}),
+            &json!([{"op": "copy", "from": "/foo/0", "path": "/foo/-"}])
+        ),
+        Ok(json!({"foo": ["bar", "bar"]}))
+    );
+    assert_eq!(
+        patched(
+            json!({"/": 9, "~1": 10}),
+            &json!([{"op": "test", "path": "/~01", "value": 10}])
+        ),
+        Ok(json!({"/": 9, "~1": 10}))
+    );
+    assert_eq!(
+        patched(
+            json!({"foo": "bar"}),
+            &json!([{"op": "add", "path": "/child", "value": {"grandchild": {}}}])
+        ),
+        Ok(json!({"foo": "bar", "child": {"grandchild": {}}}))
+    );
+}
+
+#[test]
+fn failures_are_atomic() {
+    let original = json!({"a": 1, "b": [1, 2]});
+    let mut doc = original.clone();
+    let patch = Patch::from_json(&json!([
+        {"op": "remove", "path": "/a"},
+        {"op": "test", "path": "/b/0", "value": 1},
+        {"op": "test", "path": "/b/1", "value": "2"}
+    ]))
+    .unwrap();
+
+    assert_eq!(
+        patch.apply(&mut doc),
+        Err(PatchError {
+            index: 2,
+            kind: PatchErrorKind::TestFailed
+        })
+    );
+    assert_eq!(doc, original);
+}
+
+#[test]
+fn application_errors() {
+    let error = |doc: Json, patch: Json| patched(doc, &patch).unwrap_err();
+
+    assert_eq!(
+        error(json!({}), json!([{"op": "remove", "path": "/x"}])).kind,
+        PatchErrorKind::Pointer(PointerError::NoSuchKey("x".into()))
+    );
+    assert_eq!(
+        error(
+            json!({}),
+            json!([{"op": "replace", "path": "/x", "value": 1}])
+        )
+        .kind,
+        PatchErrorKind::Pointer(PointerError::NoSuchKey("x".into()))
+    );
+    assert_eq!(
+        error(json!([1]), json!([{"op": "add", "path": "/2", "value": 1}])).kind,
+        PatchErrorKind::Pointer(PointerError::IndexOutOfBounds(2))
+    );
+    assert_eq!(
+        error(
+            json!({"a": {"b": 1}}),
+            json!([{"op": "move", "from": "/a", "path": "/a/b/c"}])
+        )
+        .kind,
+        PatchErrorKind::MoveIntoChild
+    );
+    assert_eq!(
+        error(
+            json!({}),
+            json!([{"op": "add", "path": "/a/b", "value": 1}])
+        )
+        .to_string(),
+        "operation 0: object has no member \"a\""
+    );
+}
+
+#[test]
+fn malformed_documents() {
+    let malformed = |patch: Json| Patch::from_json(&patch).unwrap_err();
+
+    assert_eq!(malformed(json!({})).index, 0);
+    assert_eq!(
+        malformed(json!([{"op": "remove", "path": "/a"}, {"op": "add", "path": "/a"}])),
+        PatchError {
+            index: 1,
+            kind: PatchErrorKind::Malformed("missing \"value\"".into())
+        }
+    );
+    assert!(matches!(
+        malformed(json!([{"op": "frob", "path": ""}])).kind,
+        PatchErrorKind::Malformed(_)
+    ));
+    assert!(matches!(
+        malformed(json!([{"op": "move", "path": "/a"}])).kind,
+        PatchErrorKind::Malformed(_)
+    ));
+    assert_eq!(
+        malformed(json!([{"op": "remove", "path": "a"}])).kind,
+        PatchErrorKind::Pointer(PointerError::MissingSlash)
+    );
+    assert_eq!(
+        Patch::from_json(&json!([{"op": "add", "path": "/a", "value": null}]))
+            .unwrap()
+            .0,
+        vec![Operation::Add {
+            path: "/a".into(),
+            value: Json::Null
+        }]
+    );
+}
+
+#[test]
+fn round_trip() {
+    let doc = json!([
+        {"op": "copy", "from": "/a", "path": "/b"},
+        {"op": "test", "path": "/b", "value": [1]}
+    ]);
+    assert_eq!(Patch::from_json(&doc).unwrap().to_json(), doc);
+}
+
+#[test]
+fn generation() {
+    let cases = [
+        (json!(1), json!(1)),
+        (json!(1), json!("1")),
+        (json!({"a": 1, "b": 2}), json!({"b": 3, "c": 4})),
+        (
+            json!({"a": {"x": [1, 2, 3]}}),
+            json!({"a": {"x": [0, 1, 3, 4]}}),
+        ),
+        (json!([1, 2, 3, 4]), json!([4, 3, 2, 1])),
+        (json!([]), json!([1, 2])),
+        (json!([{"id": 1, "v": 1}, 2]), json!([{"id": 1, "v": 2}])),
+        (json!({"a/b": {"~": 1}}), json!({"a/b": {"~": 2}})),
+    ];
+
+    for (from, to) in &cases {
+        let patch = generate(from, to);
+        let mut doc = from.clone();
+        patch.apply(&mut doc).unwrap();
+        assert_eq!(&doc, to, "patch {patch:?}");
+    }
+
+    assert!(generate(&cases[0].0, &cases[0].1).is_empty());
+    assert_eq!(
+        generate(&json!([1, 2, 3]), &json!([1, 3])).to_json(),
+        json!([{"op": "remove", "path": "/1"}])
+    );
+    assert_eq!(
+        generate(&json!({"a": [{"v": 1}]}), &json!({"a": [{"v": 2}]})).to_json(),
+        json!([{"op": "replace", "path": "/a/0/v", "value": 2}])
+    );
+}
+
+#[test]
+#[allow(clippy::cast_precision_loss)]
+fn long_arrays() {
+    let numbers = |range: std::ops::Range<usize>| -> Json {
+        Json::Array(range.map(|i| Json::Number(i as f64)).collect())
+    };
+
+    // Far more than fits in the LCS table, but only one element apart once
+    // what they start and end with is set aside.
+    let from = numbers(0..100_000);
+    let mut to = from.clone();
+    *to.get_mut(50_000).unwrap() = json!("x");
+    assert_eq!(
+        generate(&from, &to).to_json(),
+        json!([{"op": "replace", "path": "/50000", "value": "x"}])
+    );
+
+    // Nothing in common, so the arrays are replaced whole.
+    let to = numbers(100_000..200_000);
+    assert_eq!(
+        generate(&json!({"a": from}), &json!({"a": to.clone()})).to_json(),
+        json!([{"op": "replace", "path": "/a", "value": to}])
+    );
+}
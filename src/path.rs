@@ -0,0 +1,1116 @@
+//! JSON path queries (RFC 9535) over `Json` values.
+//!
+//! ```
+//! # use jval::{json, path::JsonPath};
+//! let doc = json!({"store": {"book": [{"price": 8}, {"price": 23}]}});
+//! let path: JsonPath = "$..book[?@.price < 10]".parse().unwrap();
+//! let nodes = path.query(&doc);
+//! assert_eq!(nodes[0].path.to_string(), "$['store']['book'][0]");
+//! ```
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use super::regex::Regex;
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// Largest magnitude an index or slice bound may have (I-JSON integers).
+const MAX_INT: i64 = (1 << 53) - 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at position {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathElement {
+    Name(String),
+    Index(usize),
+}
+
+/// The location of a node within the queried document, displayed in the
+/// normalized form of RFC 9535 section 2.7, e.g. `$['store']['book'][0]`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizedPath(pub Vec<PathElement>);
+
+impl NormalizedPath {
+    /// The same location as an RFC 6901 JSON Pointer.
+    #[must_use]
+    pub fn to_pointer(&self) -> String {
+        super::pointer::join(self.0.iter().map(|e| match e {
+            PathElement::Name(n) => Cow::Borrowed(n.as_str()),
+            PathElement::Index(i) => Cow::Owned(i.to_string()),
+        }))
+    }
+}
+
+impl fmt::Display for NormalizedPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "$")?;
+
+        for element in &self.0 {
+            match element {
+                PathElement::Index(i) => write!(f, "[{i}]")?,
+                PathElement::Name(n) => {
+                    write!(f, "['")?;
+                    for c in n.chars() {
+                        match c {
+                            '\'' => write!(f, "\\'")?,
+                            '\\' => write!(f, "\\\\")?,
+                            '\u{8}' => write!(f, "\\b")?,
+                            '\u{c}' => write!(f, "\\f")?,
+                            '\n' => write!(f, "\\n")?,
+                            '\r' => write!(f, "\\r")?,
+                            '\t' => write!(f, "\\t")?,
+                            c if c < ' ' => write!(f, "\\u{:04x}", c as u32)?,
+                            c => write!(f, "{c}")?,
+                        }
+                    }
+                    write!(f, "']")?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A node selected by a query: a value and where it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<'a> {
+    pub path: NormalizedPath,
+    pub value: &'a Json,
+}
+
+/// A parsed query, e.g. `$.store.book[?@.price < 10].title`.
+#[derive(Debug)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+impl FromStr for JsonPath {
+    type Err = PathError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { src: s, pos: 0 };
+        parser.expect('$')?;
+        let segments = parser.segments()?;
+
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected character"));
+        }
+
+        Ok(JsonPath { segments })
+    }
+}
+
+impl JsonPath {
+    /// Run the query against a document, returning the selected nodes in
+    /// order.
+    #[must_use]
+    pub fn query<'a>(&self, root: &'a Json) -> Vec<Node<'a>> {
+        select(
+            &self.segments,
+            root,
+            vec![(NormalizedPath::default(), root)],
+        )
+        .into_iter()
+        .map(|(path, value)| Node { path, value })
+        .collect()
+    }
+}
+
+/// Parse and run a query in one step.
+///
+/// # Errors
+///
+/// Fails if `path` is not a well-formed, well-typed query.
+pub fn query<'a>(path: &str, root: &'a Json) -> Result<Vec<Node<'a>>, PathError> {
+    path.parse::<JsonPath>().map(|p| p.query(root))
+}
+
+// evaluation
+
+#[derive(Clone, Copy)]
+enum Step<'a> {
+    Name(&'a str),
+    Index(usize),
+}
+
+/// Where a node came from. Filter expressions don't need locations, so they
+/// evaluate with `()` and skip building paths altogether.
+trait Trail: Clone {
+    fn child(&self, step: Step) -> Self;
+}
+
+impl Trail for () {
+    fn child(&self, _: Step) -> Self {}
+}
+
+impl Trail for NormalizedPath {
+    fn child(&self, step: Step) -> Self {
+        let mut path = self.clone();
+        path.0.push(match step {
+            Step::Name(n) => PathElement::Name(n.to_string()),
+            Step::Index(i) => PathElement::Index(i),
+        });
+        path
+    }
+}
+
+type Nodes<'a, T> = Vec<(T, &'a Json)>;
+
+fn select<'a, T: Trail>(segments: &[Segment], root: &'a Json, start: Nodes<'a, T>) -> Nodes<'a, T> {
+    segments.iter().fold(start, |nodes, segment| {
+        let mut out = Vec::new();
+        for (trail, value) in nodes {
+            segment.apply(root, &trail, value, &mut out);
+        }
+        out
+    })
+}
+
+#[derive(Debug)]
+struct Segment {
+    descendant: bool,
+    selectors: Vec<Selector>,
+}
+
+impl Segment {
+    fn apply<'a, T: Trail>(
+        &self,
+        root: &'a Json,
+        trail: &T,
+        value: &'a Json,
+        out: &mut Nodes<'a, T>,
+    ) {
+        for selector in &self.selectors {
+            selector.apply(root, trail, value, out);
+        }
+
+        if self.descendant {
+            match value {
+                Json::Array(a) => {
+                    for (i, child) in a.iter().enumerate() {
+                        self.apply(root, &trail.child(Step::Index(i)), child, out);
+                    }
+                }
+                Json::Object(o) => {
+                    for (k, child) in o {
+                        self.apply(root, &trail.child(Step::Name(k)), child, out);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn is_singular(&self) -> bool {
+        !self.descendant
+            && matches!(
+                self.selectors.as_slice(),
+                [Selector::Name(_) | Selector::Index(_)]
+            )
+    }
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Wildcard,
+    Index(i64),
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    Filter(Expr),
+}
+
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn normalize(i: i64, len: usize) -> i64 {
+    if i >= 0 {
+        i
+    } else {
+        len as i64 + i
+    }
+}
+
+#[allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+fn slice_indices(
+    start: Option<i64>,
+    end: Option<i64>,
+    step: Option<i64>,
+    len: usize,
+) -> Vec<usize> {
+    let step = step.unwrap_or(1);
+    let n = len as i64;
+    let mut out = Vec::new();
+
+    if step > 0 {
+        let lower = normalize(start.unwrap_or(0), len).clamp(0, n);
+        let upper = normalize(end.unwrap_or(n), len).clamp(0, n);
+        let mut i = lower;
+        while i < upper {
+            out.push(i as usize);
+            i += step;
+        }
+    } else if step < 0 {
+        let upper = normalize(start.unwrap_or(n - 1), len).clamp(-1, n - 1);
+        let lower = normalize(end.unwrap_or(-n - 1), len).clamp(-1, n - 1);
+        let mut i = upper;
+        while lower < i {
+            out.push(i as usize);
+            i += step;
+        }
+    }
+
+    out
+}
+
+impl Selector {
+    #[allow(clippy::cast_sign_loss, clippy::cast_possible_truncation)]
+    fn apply<'a, T: Trail>(
+        &self,
+        root: &'a Json,
+        trail: &T,
+        value: &'a Json,
+        out: &mut Nodes<'a, T>,
+    ) {
+        match (self, value) {
+            (Selector::Name(n), Json::Object(o)) => {
+                if let Some((k, v)) = o.get_key_value(n) {
+                    out.push((trail.child(Step::Name(k)), v));
+                }
+            }
+            (Selector::Wildcard, Json::Array(a)) => {
+                for (i, v) in a.iter().enumerate() {
+                    out.push((trail.child(Step::Index(i)), v));
+                }
+            }
+            (Selector::Wildcard, Json::Object(o)) => {
+                for (k, v) in o {
+                    out.push((trail.child(Step::Name(k)), v));
+                }
+            }
+            (Selector::Index(i), Json::Array(a)) => {
+                let i = normalize(*i, a.len());
+                if i >= 0 {
+                    if let Some(v) = a.get(i as usize) {
+                        out.push((trail.child(Step::Index(i as usize)), v));
+                    }
+                }
+            }
+            (Selector::Slice(start, end, step), Json::Array(a)) => {
+                for i in slice_indices(*start, *end, *step, a.len()) {
+                    out.push((trail.child(Step::Index(i)), &a[i]));
+                }
+            }
+            (Selector::Filter(e), Json::Array(a)) => {
+                for (i, v) in a.iter().enumerate() {
+                    if e.test(root, v) {
+                        out.push((trail.child(Step::Index(i)), v));
+                    }
+                }
+            }
+            (Selector::Filter(e), Json::Object(o)) => {
+                for (k, v) in o {
+                    if e.test(root, v) {
+                        out.push((trail.child(Step::Name(k)), v));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Query {
+    absolute: bool,
+    segments: Vec<Segment>,
+}
+
+impl Query {
+    fn nodes<'a>(&'a self, root: &'a Json, current: &'a Json) -> Vec<&'a Json> {
+        let start = if self.absolute { root } else { current };
+        select(&self.segments, root, vec![((), start)])
+            .into_iter()
+            .map(|((), v)| v)
+            .collect()
+    }
+
+    fn is_singular(&self) -> bool {
+        self.segments.iter().all(Segment::is_singular)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Or(Vec<Expr>),
+    And(Vec<Expr>),
+    Not(Box<Expr>),
+    Compare(Comparable, CmpOp, Comparable),
+    Exists(Query),
+    Test(Call),
+}
+
+#[derive(Debug)]
+enum Comparable {
+    Literal(Json),
+    Query(Query),
+    Call(Call),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Length,
+    Count,
+    Match,
+    Search,
+    Value,
+}
+
+#[derive(Debug)]
+struct Call {
+    function: Function,
+    args: Vec<Comparable>,
+    pattern: Pattern,
+}
+
+/// The regex of a `match` or `search` call, compiled once when the query is
+/// parsed if it's a literal.
+#[derive(Debug)]
+enum Pattern {
+    /// Not a literal, or not a call that takes one: it's compiled each time.
+    Dynamic,
+    Compiled(Regex),
+    /// A literal that isn't a valid regex, which matches nothing.
+    Invalid,
+}
+
+type Value<'a> = Option<Cow<'a, Json>>;
+
+impl Expr {
+    fn test(&self, root: &Json, current: &Json) -> bool {
+        match self {
+            Expr::Or(es) => es.iter().any(|e| e.test(root, current)),
+            Expr::And(es) => es.iter().all(|e| e.test(root, current)),
+            Expr::Not(e) => !e.test(root, current),
+            Expr::Compare(a, op, b) => {
+                compare(&a.value(root, current), *op, &b.value(root, current))
+            }
+            Expr::Exists(q) => !q.nodes(root, current).is_empty(),
+            Expr::Test(c) => c.logical(root, current),
+        }
+    }
+}
+
+impl Comparable {
+    fn value<'a>(&'a self, root: &'a Json, current: &'a Json) -> Value<'a> {
+        match self {
+            Comparable::Literal(j) => Some(Cow::Borrowed(j)),
+            Comparable::Query(q) => match q.nodes(root, current).as_slice() {
+                [v] => Some(Cow::Borrowed(*v)),
+                _ => None,
+            },
+            Comparable::Call(c) => c.value(root, current),
+        }
+    }
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "length" => Some(Function::Length),
+            "count" => Some(Function::Count),
+            "match" => Some(Function::Match),
+            "search" => Some(Function::Search),
+            "value" => Some(Function::Value),
+            _ => None,
+        }
+    }
+
+    fn is_logical(self) -> bool {
+        matches!(self, Function::Match | Function::Search)
+    }
+}
+
+impl Call {
+    fn nodes<'a>(&'a self, root: &'a Json, current: &'a Json) -> Vec<&'a Json> {
+        match &self.args[0] {
+            Comparable::Query(q) => q.nodes(root, current),
+            _ => Vec::new(),
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn value<'a>(&'a self, root: &'a Json, current: &'a Json) -> Value<'a> {
+        match self.function {
+            Function::Length => {
+                let len = match self.args[0].value(root, current)?.as_ref() {
+                    Json::String(s) => s.chars().count(),
+                    Json::Array(a) => a.len(),
+                    Json::Object(o) => o.len(),
+                    _ => return None,
+                };
+                Some(Cow::Owned(Json::Number(len as f64)))
+            }
+            Function::Count => Some(Cow::Owned(Json::Number(
+                self.nodes(root, current).len() as f64
+            ))),
+            Function::Value => match self.nodes(root, current).as_slice() {
+                [v] => Some(Cow::Borrowed(*v)),
+                _ => None,
+            },
+            Function::Match | Function::Search => None,
+        }
+    }
+
+    fn logical(&self, root: &Json, current: &Json) -> bool {
+        let subject = self.args[0].value(root, current);
+        let Some(Json::String(s)) = subject.as_deref() else {
+            return false;
+        };
+        let test = |re: &Regex| {
+            if self.function == Function::Match {
+                re.is_full_match(s)
+            } else {
+                re.is_match(s)
+            }
+        };
+
+        match &self.pattern {
+            Pattern::Compiled(re) => test(re),
+            Pattern::Invalid => false,
+            Pattern::Dynamic => match self.args[1].value(root, current).as_deref() {
+                Some(Json::String(p)) => Regex::new(p).is_ok_and(|re| test(&re)),
+                _ => false,
+            },
+        }
+    }
+}
+
+fn compare(a: &Value, op: CmpOp, b: &Value) -> bool {
+    fn eq(a: &Value, b: &Value) -> bool {
+        match (a.as_deref(), b.as_deref()) {
+            (None, None) => true,
+            (Some(x), Some(y)) => x == y,
+            _ => false,
+        }
+    }
+
+    fn lt(a: &Value, b: &Value) -> bool {
+        match (a.as_deref(), b.as_deref()) {
+            (Some(Json::Number(x)), Some(Json::Number(y))) => x < y,
+            (Some(Json::String(x)), Some(Json::String(y))) => x < y,
+            _ => false,
+        }
+    }
+
+    match op {
+        CmpOp::Eq => eq(a, b),
+        CmpOp::Ne => !eq(a, b),
+        CmpOp::Lt => lt(a, b),
+        CmpOp::Le => lt(a, b) || eq(a, b),
+        CmpOp::Gt => lt(b, a),
+        CmpOp::Ge => lt(b, a) || eq(a, b),
+    }
+}
+
+// parsing
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+fn is_name_first(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c >= '\u{80}'
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_first(c) || c.is_ascii_digit()
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> PathError {
+        let end = self.peek().map_or(self.pos, |c| self.pos + c.len_utf8());
+        PathError {
+            message: message.to_string(),
+            span: self.pos..end,
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), PathError> {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{c}'")))
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn segments(&mut self) -> Result<Vec<Segment>, PathError> {
+        let mut segments = Vec::new();
+
+        loop {
+            let save = self.pos;
+            self.skip_ws();
+
+            if self.eat("..") {
+                let selectors = match self.peek() {
+                    Some('[') => self.bracketed()?,
+                    Some('*') => {
+                        self.pos += 1;
+                        vec![Selector::Wildcard]
+                    }
+                    Some(c) if is_name_first(c) => vec![Selector::Name(self.shorthand())],
+                    _ => return Err(self.error("expected a selector after '..'")),
+                };
+                segments.push(Segment {
+                    descendant: true,
+                    selectors,
+                });
+            } else if self.eat(".") {
+                let selector = match self.peek() {
+                    Some('*') => {
+                        self.pos += 1;
+                        Selector::Wildcard
+                    }
+                    Some(c) if is_name_first(c) => Selector::Name(self.shorthand()),
+                    _ => return Err(self.error("expected a member name after '.'")),
+                };
+                segments.push(Segment {
+                    descendant: false,
+                    selectors: vec![selector],
+                });
+            } else if self.peek() == Some('[') {
+                let selectors = self.bracketed()?;
+                segments.push(Segment {
+                    descendant: false,
+                    selectors,
+                });
+            } else {
+                self.pos = save;
+                return Ok(segments);
+            }
+        }
+    }
+
+    fn shorthand(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(is_name_char) {
+            self.bump();
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    fn bracketed(&mut self) -> Result<Vec<Selector>, PathError> {
+        self.expect('[')?;
+        let mut selectors = Vec::new();
+
+        loop {
+            self.skip_ws();
+            selectors.push(self.selector()?);
+            self.skip_ws();
+
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(selectors);
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn selector(&mut self) -> Result<Selector, PathError> {
+        match self.peek() {
+            Some('\'' | '"') => self.string().map(Selector::Name),
+            Some('*') => {
+                self.pos += 1;
+                Ok(Selector::Wildcard)
+            }
+            Some('?') => {
+                self.pos += 1;
+                self.skip_ws();
+                self.logical_or().map(Selector::Filter)
+            }
+            _ => {
+                let start = self.int_opt()?;
+                self.skip_ws();
+
+                if !self.eat(":") {
+                    return start
+                        .map(Selector::Index)
+                        .ok_or_else(|| self.error("expected a selector"));
+                }
+
+                self.skip_ws();
+                let end = self.int_opt()?;
+                self.skip_ws();
+
+                let step = if self.eat(":") {
+                    self.skip_ws();
+                    self.int_opt()?
+                } else {
+                    None
+                };
+
+                Ok(Selector::Slice(start, end, step))
+            }
+        }
+    }
+
+    fn int_opt(&mut self) -> Result<Option<i64>, PathError> {
+        match self.peek() {
+            Some('-' | '0'..='9') => self.int().map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn int(&mut self) -> Result<i64, PathError> {
+        let start = self.pos;
+        let negative = self.eat("-");
+        let digits_start = self.pos;
+
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+
+        let digits = &self.src[digits_start..self.pos];
+        let bad = digits.is_empty() || (digits.starts_with('0') && (digits.len() > 1 || negative));
+
+        let error = || PathError {
+            message: "invalid integer".to_string(),
+            span: start..self.pos.max(start + 1),
+        };
+
+        if bad {
+            return Err(error());
+        }
+
+        self.src[start..self.pos]
+            .parse::<i64>()
+            .ok()
+            .filter(|n| (-MAX_INT..=MAX_INT).contains(n))
+            .ok_or_else(error)
+    }
+
+    fn string(&mut self) -> Result<String, PathError> {
+        let quote = self.bump().expect("string starts with a quote");
+        let mut out = String::new();
+
+        loop {
+            match self.bump() {
+                None => return Err(self.error("unterminated string")),
+                Some(c) if c == quote => return Ok(out),
+                Some('\\') => {
+                    let escape = self.pos;
+                    let c = match self.bump() {
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some(c @ ('/' | '\\')) => c,
+                        Some(c) if c == quote => c,
+                        Some('u') => self.unicode_escape()?,
+                        _ => {
+                            self.pos = escape;
+                            return Err(self.error("invalid escape"));
+                        }
+                    };
+                    out.push(c);
+                }
+                Some(c) if c < ' ' => {
+                    self.pos -= 1;
+                    return Err(self.error("unescaped control character"));
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, PathError> {
+        let hex = self.rest().get(..4).unwrap_or_default();
+        match u32::from_str_radix(hex, 16) {
+            Ok(n) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                self.pos += 4;
+                Ok(n)
+            }
+            _ => Err(self.error("invalid unicode escape")),
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, PathError> {
+        let hi = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&hi) {
+            if !self.eat("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            let lo = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&lo) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((hi - 0xD800) << 10) + (lo - 0xDC00)
+        } else {
+            hi
+        };
+
+        std::char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn logical_or(&mut self) -> Result<Expr, PathError> {
+        let mut terms = vec![self.logical_and()?];
+
+        loop {
+            let save = self.pos;
+            self.skip_ws();
+            if self.eat("||") {
+                self.skip_ws();
+                terms.push(self.logical_and()?);
+            } else {
+                self.pos = save;
+                break;
+            }
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::Or(terms)
+        })
+    }
+
+    fn logical_and(&mut self) -> Result<Expr, PathError> {
+        let mut terms = vec![self.basic()?];
+
+        loop {
+            let save = self.pos;
+            self.skip_ws();
+            if self.eat("&&") {
+                self.skip_ws();
+                terms.push(self.basic()?);
+            } else {
+                self.pos = save;
+                break;
+            }
+        }
+
+        Ok(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Expr::And(terms)
+        })
+    }
+
+    fn basic(&mut self) -> Result<Expr, PathError> {
+        if self.eat("!") {
+            self.skip_ws();
+            let negated = if self.peek() == Some('(') {
+                self.paren()?
+            } else {
+                let start = self.pos;
+                let operand = self.comparable()?;
+                self.test(operand, start)?
+            };
+            return Ok(Expr::Not(Box::new(negated)));
+        }
+
+        if self.peek() == Some('(') {
+            return self.paren();
+        }
+
+        let start = self.pos;
+        let left = self.comparable()?;
+        let save = self.pos;
+        self.skip_ws();
+
+        let op = [
+            ("==", CmpOp::Eq),
+            ("!=", CmpOp::Ne),
+            ("<=", CmpOp::Le),
+            (">=", CmpOp::Ge),
+            ("<", CmpOp::Lt),
+            (">", CmpOp::Gt),
+        ]
+        .iter()
+        .find(|(s, _)| self.eat(s))
+        .map(|(_, op)| *op);
+
+        if let Some(op) = op {
+            self.check_comparable(&left, start)?;
+            self.skip_ws();
+            let right_start = self.pos;
+            let right = self.comparable()?;
+            self.check_comparable(&right, right_start)?;
+            Ok(Expr::Compare(left, op, right))
+        } else {
+            self.pos = save;
+            self.test(left, start)
+        }
+    }
+
+    fn paren(&mut self) -> Result<Expr, PathError> {
+        self.expect('(')?;
+        self.skip_ws();
+        let e = self.logical_or()?;
+        self.skip_ws();
+        self.expect(')')?;
+        Ok(e)
+    }
+
+    fn type_error(&self, start: usize, message: &str) -> PathError {
+        PathError {
+            message: message.to_string(),
+            span: start..self.pos,
+        }
+    }
+
+    fn test(&self, operand: Comparable, start: usize) -> Result<Expr, PathError> {
+        match operand {
+            Comparable::Query(q) => Ok(Expr::Exists(q)),
+            Comparable::Call(c) if c.function.is_logical() => Ok(Expr::Test(c)),
+            Comparable::Call(_) => Err(self.type_error(
+                start,
+                "function result must be compared to be used as a test",
+            )),
+            Comparable::Literal(_) => {
+                Err(self.type_error(start, "a literal cannot be used as a test"))
+            }
+        }
+    }
+
+    /// Comparisons need a single value on each side: a literal, a singular
+    /// query or a function returning a value.
+    fn check_comparable(&self, operand: &Comparable, start: usize) -> Result<(), PathError> {
+        match operand {
+            Comparable::Query(q) if !q.is_singular() => {
+                Err(self.type_error(start, "only singular queries can be compared"))
+            }
+            Comparable::Call(c) if c.function.is_logical() => {
+                Err(self.type_error(start, "logical function results cannot be compared"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn comparable(&mut self) -> Result<Comparable, PathError> {
+        match self.peek() {
+            Some('@') => {
+                self.pos += 1;
+                Ok(Comparable::Query(Query {
+                    absolute: false,
+                    segments: self.segments()?,
+                }))
+            }
+            Some('$') => {
+                self.pos += 1;
+                Ok(Comparable::Query(Query {
+                    absolute: true,
+                    segments: self.segments()?,
+                }))
+            }
+            Some('\'' | '"') => self.string().map(|s| Comparable::Literal(Json::String(s))),
+            Some('-' | '0'..='9') => self.number().map(Comparable::Literal),
+            Some('a'..='z') => {
+                for (word, value) in &[
+                    ("true", Json::Boolean(true)),
+                    ("false", Json::Boolean(false)),
+                    ("null", Json::Null),
+                ] {
+                    if self.rest().starts_with(word)
+                        && !self.rest()[word.len()..]
+                            .chars()
+                            .next()
+                            .is_some_and(is_name_char)
+                    {
+                        self.pos += word.len();
+                        return Ok(Comparable::Literal(value.clone()));
+                    }
+                }
+
+                self.call().map(Comparable::Call)
+            }
+            _ => Err(self.error("expected a query, literal or function")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, PathError> {
+        let start = self.pos;
+        let number_error = |pos| PathError {
+            message: "invalid number".to_string(),
+            span: start..pos,
+        };
+
+        let _ = self.eat("-");
+        let digits = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let int = &self.src[digits..self.pos];
+        if int.is_empty() || (int.starts_with('0') && int.len() > 1) {
+            return Err(number_error(self.pos));
+        }
+
+        if self.eat(".") {
+            let digits = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if digits == self.pos {
+                return Err(number_error(self.pos));
+            }
+        }
+
+        if self.eat("e") || self.eat("E") {
+            let _ = self.eat("-") || self.eat("+");
+            let digits = self.pos;
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            if digits == self.pos {
+                return Err(number_error(self.pos));
+            }
+        }
+
+        self.src[start..self.pos]
+            .parse()
+            .map(Json::Number)
+            .map_err(|_| number_error(self.pos))
+    }
+
+    fn call(&mut self) -> Result<Call, PathError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            self.pos += 1;
+        }
+
+        let function = Function::from_name(&self.src[start..self.pos])
+            .ok_or_else(|| self.type_error(start, "unknown function"))?;
+
+        self.expect('(')?;
+        let mut args = Vec::new();
+
+        loop {
+            self.skip_ws();
+            if args.is_empty() && self.eat(")") {
+                break;
+            }
+
+            let arg_start = self.pos;
+            if let Some('(' | '!') = self.peek() {
+                return Err(self.error("logical expressions are not valid function arguments"));
+            }
+            let arg = self.comparable()?;
+
+            let nodes_param = matches!(function, Function::Count | Function::Value);
+            match &arg {
+                Comparable::Query(_) if nodes_param => {}
+                _ if nodes_param => {
+                    return Err(self.type_error(arg_start, "argument must be a query"));
+                }
+                _ => self.check_comparable(&arg, arg_start)?,
+            }
+            args.push(arg);
+
+            self.skip_ws();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    break;
+                }
+                _ => return Err(self.error("expected ',' or ')'")),
+            }
+        }
+
+        let arity = match function {
+            Function::Match | Function::Search => 2,
+            _ => 1,
+        };
+
+        if args.len() == arity {
+            let pattern = match args.get(1) {
+                Some(Comparable::Literal(Json::String(p))) => match Regex::new(p) {
+                    Ok(re) => Pattern::Compiled(re),
+                    Err(_) => Pattern::Invalid,
+                },
+                _ => Pattern::Dynamic,
+            };
+            Ok(Call {
+                function,
+                args,
+                pattern,
+            })
+        } else {
+            Err(self.type_error(start, "wrong number of arguments"))
+        }
+    }
+}
@@ -0,0 +1,242 @@
+#![cfg(test)]
+
+use super::*;
+
+fn store() -> Json {
+    json!({ "store": {
+        "book": [
+            { "category": "reference",
+              "author": "Nigel Rees",
+              "title": "Sayings of the Century",
+              "price": 8.95
+            },
+            { "category": "fiction",
+              "author": "Evelyn Waugh",
+              "title": "Sword of Honour",
+              "price": 12.99
+            },
+            { "category": "fiction",
+              "author": "Herman Melville",
+              "title": "Moby Dick",
+              "isbn": "0-553-21311-3",
+              "price": 8.99
+            },
+            { "category": "fiction",
+              "author": "J. R. R. Tolkien",
+              "title": "The Lord of the Rings",
+              "isbn": "0-395-19395-8",
+              "price": 22.99
+            }
+        ],
+        "bicycle": {
+            "color": "red",
+            "price": 399
+        }
+    }})
+}
+
+fn values<'a>(path: &str, doc: &'a Json) -> Vec<&'a Json> {
+    query(path, doc)
+        .unwrap()
+        .into_iter()
+        .map(|n| n.value)
+        .collect()
+}
+
+fn paths(path: &str, doc: &Json) -> Vec<String> {
+    query(path, doc)
+        .unwrap()
+        .into_iter()
+        .map(|n| n.path.to_string())
+        .collect()
+}
+
+#[test]
+fn child_segments() {
+    let doc = store();
+    assert_eq!(values("$.store.bicycle.color", &doc), vec![&json!("red")]);
+    assert_eq!(
+        values("$['store'][\"bicycle\"]['color']", &doc),
+        vec![&json!("red")]
+    );
+    assert_eq!(values("$.store.book[*].author", &doc).len(), 4);
+    assert_eq!(values("$", &doc), vec![&doc]);
+    assert!(values("$.nope", &doc).is_empty());
+}
+
+#[test]
+fn descendants() {
+    let doc = store();
+    assert_eq!(
+        values("$..author", &doc),
+        vec![
+            &json!("Nigel Rees"),
+            &json!("Evelyn Waugh"),
+            &json!("Herman Melville"),
+            &json!("J. R. R. Tolkien")
+        ]
+    );
+    assert_eq!(values("$.store..price", &doc).len(), 5);
+    assert_eq!(values("$..book[2].title", &doc), vec![&json!("Moby Dick")]);
+    assert_eq!(
+        values("$..book[-1].title", &doc),
+        vec![&json!("The Lord of the Rings")]
+    );
+}
+
+#[test]
+fn slices() {
+    let doc = json!(["a", "b", "c", "d", "e", "f", "g"]);
+    assert_eq!(values("$[1:3]", &doc), vec![&json!("b"), &json!("c")]);
+    assert_eq!(values("$[5:]", &doc), vec![&json!("f"), &json!("g")]);
+    assert_eq!(values("$[1:5:2]", &doc), vec![&json!("b"), &json!("d")]);
+    assert_eq!(values("$[5:1:-2]", &doc), vec![&json!("f"), &json!("d")]);
+    assert_eq!(values("$[::-1]", &doc).len(), 7);
+    assert_eq!(values("$[::-1]", &doc)[0], &json!("g"));
+    assert!(values("$[::0]", &doc).is_empty());
+    assert_eq!(
+        values("$[0, 3, 0]", &doc),
+        vec![&json!("a"), &json!("d"), &json!("a")]
+    );
+}
+
+#[test]
+fn filters() {
+    let doc = store();
+    assert_eq!(
+        values("$..book[?@.isbn].title", &doc),
+        vec![&json!("Moby Dick"), &json!("The Lord of the Rings")]
+    );
+    assert_eq!(
+        values("$..book[?@.price < 10].title", &doc),
+        vec![&json!("Sayings of the Century"), &json!("Moby Dick")]
+    );
+    assert_eq!(
+        values("$..book[?@.price > $.store.bicycle.price]", &doc).len(),
+        0
+    );
+    assert_eq!(
+        values(
+            "$.store.book[?@.category == 'fiction' && !(@.price > 20)].title",
+            &doc
+        ),
+        vec![&json!("Sword of Honour"), &json!("Moby Dick")]
+    );
+    assert_eq!(values("$..*[?@ == 'red']", &doc), vec![&json!("red")]);
+    assert_eq!(values("$.store[?@.color || @[0]]", &doc).len(), 2);
+}
+
+#[test]
+fn comparisons() {
+    let doc = json!([{"a": 1}, {"a": "1"}, {"a": null}, {"b": 2}, {"a": [1]}]);
+    assert_eq!(values("$[?@.a == 1]", &doc).len(), 1);
+    assert_eq!(values("$[?@.a == null]", &doc).len(), 1);
+    assert_eq!(values("$[?@.a == @.c]", &doc).len(), 1);
+    assert_eq!(values("$[?@.a != 1]", &doc).len(), 4);
+    assert_eq!(values("$[?@.a >= 1]", &doc).len(), 1);
+    assert_eq!(values("$[?@.a <= '1']", &doc).len(), 1);
+    assert_eq!(values("$[?@.a == 1.0e0]", &doc).len(), 1);
+}
+
+#[test]
+fn functions() {
+    let doc = json!([
+        {"name": "alpha", "tags": ["x", "y"]},
+        {"name": "beta", "tags": []},
+        {"name": "bravo", "tags": ["z"]}
+    ]);
+    assert_eq!(
+        values("$[?length(@.tags) == 2].name", &doc),
+        vec![&json!("alpha")]
+    );
+    assert_eq!(
+        values("$[?count(@.tags[*]) == 0].name", &doc),
+        vec![&json!("beta")]
+    );
+    assert_eq!(
+        values("$[?match(@.name, 'b.*')].name", &doc),
+        vec![&json!("beta"), &json!("bravo")]
+    );
+    assert_eq!(values("$[?match(@.name, 'b')].name", &doc).len(), 0);
+    assert_eq!(
+        values("$[?search(@.name, 'av')].name", &doc),
+        vec![&json!("bravo")]
+    );
+    assert_eq!(
+        values("$[?value(@.tags[0]) == 'z'].name", &doc),
+        vec![&json!("bravo")]
+    );
+    assert_eq!(
+        values("$[?length(@.name) == 4].name", &doc),
+        vec![&json!("beta")]
+    );
+
+    // Patterns that aren't literals are compiled for each node, and ones
+    // that don't compile match nothing.
+    let patterns = json!({"p": "b.*", "items": [{"name": "beta", "p": "[b]eta"}, {"name": "x"}]});
+    assert_eq!(
+        values("$.items[?match(@.name, @.p)].name", &patterns),
+        vec![&json!("beta")]
+    );
+    assert_eq!(
+        values("$.items[?search(@.name, $.p)].name", &patterns),
+        vec![&json!("beta")]
+    );
+    assert_eq!(values("$[?match(@.name, 'b(')]", &doc).len(), 0);
+
+    let long = json!(["a".repeat(100_000)]);
+    assert_eq!(values("$[?match(@, 'a*')]", &long).len(), 1);
+}
+
+#[test]
+fn normalized_paths() {
+    let doc = json!({"a": [{"it's": 1}, {"b\\c": 2}], "\u{1}": 3});
+    assert_eq!(
+        paths("$..*[?@ > 0]", &doc),
+        vec!["$['a'][0]['it\\'s']", "$['a'][1]['b\\\\c']"]
+    );
+    assert_eq!(paths("$[*]", &doc)[0], "$['\\u0001']");
+
+    let nodes = query("$.a[1]", &doc).unwrap();
+    assert_eq!(nodes[0].path.to_pointer(), "/a/1");
+}
+
+#[test]
+fn reject_invalid() {
+    for bad in &[
+        "",
+        "store",
+        "$.",
+        "$..",
+        "$[",
+        "$[]",
+        "$[01]",
+        "$[-0]",
+        "$[9007199254740992]",
+        "$['a'",
+        "$.a ",
+        " $.a",
+        "$[?@.a == ]",
+        "$[?@..a == 1]",
+        "$[?@.* == 1]",
+        "$[?length(@.a)]",
+        "$[?match(@.a, 'x') == true]",
+        "$[?count(1) == 1]",
+        "$[?nope(@)]",
+        "$[?length(@.a, @.b) == 1]",
+        "$[?1]",
+        "$['\\x']",
+    ] {
+        assert!(
+            bad.parse::<JsonPath>().is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
+
+#[test]
+fn error_spans() {
+    let err = "$.a[01]".parse::<JsonPath>().unwrap_err();
+    assert_eq!(err.span, 4..6);
+}
@@ -0,0 +1,242 @@
+use std::fmt;
+
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PointerError {
+    /// A non-empty pointer must start with `/`.
+    MissingSlash,
+    /// A `~` not followed by `0` or `1`.
+    InvalidEscape,
+    /// A token used on an array that is not `-` or a canonical decimal index.
+    InvalidIndex(String),
+    IndexOutOfBounds(usize),
+    NoSuchKey(String),
+    /// A token was applied to a value that is neither an array nor an object.
+    NotAContainer(String),
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PointerError::MissingSlash => write!(f, "pointer must be empty or start with '/'"),
+            PointerError::InvalidEscape => write!(f, "'~' must be followed by '0' or '1'"),
+            PointerError::InvalidIndex(t) => write!(f, "\"{t}\" is not a valid array index"),
+            PointerError::IndexOutOfBounds(i) => write!(f, "array index {i} is out of bounds"),
+            PointerError::NoSuchKey(k) => write!(f, "object has no member \"{k}\""),
+            PointerError::NotAContainer(t) => {
+                write!(f, "cannot look up \"{t}\" in a scalar value")
+            }
+        }
+    }
+}
+
+/// Split a pointer into its unescaped reference tokens.
+///
+/// # Errors
+///
+/// Fails if the pointer is not empty and does not start with `/`, or if it
+/// contains a `~` that is not part of `~0` or `~1`.
+pub fn parse(pointer: &str) -> Result<Vec<String>, PointerError> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if !pointer.starts_with('/') {
+        return Err(PointerError::MissingSlash);
+    }
+
+    pointer[1..].split('/').map(unescape).collect()
+}
+
+fn unescape(token: &str) -> Result<String, PointerError> {
+    let mut out = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => out.push('~'),
+                Some('1') => out.push('/'),
+                _ => return Err(PointerError::InvalidEscape),
+            },
+            _ => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Escape a single reference token so it can be appended to a pointer.
+#[must_use]
+pub fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Build a pointer from unescaped reference tokens.
+pub fn join<I, S>(tokens: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    tokens.into_iter().fold(String::new(), |mut acc, t| {
+        acc.push('/');
+        acc.push_str(&escape(t.as_ref()));
+        acc
+    })
+}
+
+enum ArrayIndex {
+    At(usize),
+    End,
+}
+
+fn array_index(token: &str) -> Result<ArrayIndex, PointerError> {
+    let canonical = token == "0"
+        || (!token.is_empty()
+            && !token.starts_with('0')
+            && token.bytes().all(|b| b.is_ascii_digit()));
+
+    if token == "-" {
+        Ok(ArrayIndex::End)
+    } else if canonical {
+        token
+            .parse()
+            .map(ArrayIndex::At)
+            .map_err(|_| PointerError::InvalidIndex(token.to_string()))
+    } else {
+        Err(PointerError::InvalidIndex(token.to_string()))
+    }
+}
+
+fn step<'a>(value: &'a Json, token: &str) -> Result<&'a Json, PointerError> {
+    match value {
+        Json::Object(o) => o
+            .get(token)
+            .ok_or_else(|| PointerError::NoSuchKey(token.to_string())),
+        Json::Array(a) => match array_index(token)? {
+            ArrayIndex::At(i) => a.get(i).ok_or(PointerError::IndexOutOfBounds(i)),
+            ArrayIndex::End => Err(PointerError::IndexOutOfBounds(a.len())),
+        },
+        _ => Err(PointerError::NotAContainer(token.to_string())),
+    }
+}
+
+fn step_mut<'a>(value: &'a mut Json, token: &str) -> Result<&'a mut Json, PointerError> {
+    match value {
+        Json::Object(o) => o
+            .get_mut(token)
+            .ok_or_else(|| PointerError::NoSuchKey(token.to_string())),
+        Json::Array(a) => match array_index(token)? {
+            ArrayIndex::At(i) => a.get_mut(i).ok_or(PointerError::IndexOutOfBounds(i)),
+            ArrayIndex::End => Err(PointerError::IndexOutOfBounds(a.len())),
+        },
+        _ => Err(PointerError::NotAContainer(token.to_string())),
+    }
+}
+
+impl Json {
+    /// Look up a value by RFC 6901 JSON Pointer, e.g. `/servers/0/host`.
+    #[must_use]
+    pub fn pointer(&self, pointer: &str) -> Option<&Self> {
+        self.try_pointer(pointer).ok()
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Self> {
+        self.try_pointer_mut(pointer).ok()
+    }
+
+    /// Like `pointer`, but reports why the lookup failed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pointer is malformed or does not refer to an existing value.
+    pub fn try_pointer(&self, pointer: &str) -> Result<&Self, PointerError> {
+        parse(pointer)?
+            .iter()
+            .try_fold(self, |v, token| step(v, token))
+    }
+
+    /// Like `pointer_mut`, but reports why the lookup failed.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pointer is malformed or does not refer to an existing value.
+    pub fn try_pointer_mut(&mut self, pointer: &str) -> Result<&mut Self, PointerError> {
+        parse(pointer)?
+            .iter()
+            .try_fold(self, |v, token| step_mut(v, token))
+    }
+
+    /// Add a value at the location named by the pointer, with the semantics of
+    /// a JSON Patch `add`: object members are created or replaced, array
+    /// elements are inserted before the given index, and `-` appends. Returns
+    /// the value that was replaced, if any.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pointer is malformed, if its parent does not exist, or if
+    /// an array index is past the end of the array.
+    pub fn pointer_insert(
+        &mut self,
+        pointer: &str,
+        value: Self,
+    ) -> Result<Option<Self>, PointerError> {
+        let mut tokens = parse(pointer)?;
+
+        let Some(last) = tokens.pop() else {
+            return Ok(Some(std::mem::replace(self, value)));
+        };
+
+        let parent = tokens
+            .iter()
+            .try_fold(self, |v, token| step_mut(v, token))?;
+
+        match parent {
+            Json::Object(o) => Ok(o.insert(last, value)),
+            Json::Array(a) => match array_index(&last)? {
+                ArrayIndex::At(i) if i > a.len() => Err(PointerError::IndexOutOfBounds(i)),
+                ArrayIndex::At(i) => {
+                    a.insert(i, value);
+                    Ok(None)
+                }
+                ArrayIndex::End => {
+                    a.push(value);
+                    Ok(None)
+                }
+            },
+            _ => Err(PointerError::NotAContainer(last)),
+        }
+    }
+
+    /// Remove and return the value named by the pointer. Removing the root
+    /// leaves `null` in its place.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the pointer is malformed or does not refer to an existing value.
+    pub fn pointer_remove(&mut self, pointer: &str) -> Result<Self, PointerError> {
+        let mut tokens = parse(pointer)?;
+
+        let Some(last) = tokens.pop() else {
+            return Ok(self.take());
+        };
+
+        let parent = tokens
+            .iter()
+            .try_fold(self, |v, token| step_mut(v, token))?;
+
+        match parent {
+            Json::Object(o) => o.remove(&last).ok_or(PointerError::NoSuchKey(last)),
+            Json::Array(a) => match array_index(&last)? {
+                ArrayIndex::At(i) if i < a.len() => Ok(a.remove(i)),
+                ArrayIndex::At(i) => Err(PointerError::IndexOutOfBounds(i)),
+                ArrayIndex::End => Err(PointerError::IndexOutOfBounds(a.len())),
+            },
+            _ => Err(PointerError::NotAContainer(last)),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+#![cfg(test)]
+
+use super::*;
+
+fn sample() -> Json {
+    json!({
+        "servers": [{"host": "a.example"}, {"host": "b.example"}],
+        "a/b": 1,
+        "m~n": 2,
+        "": 3,
+    })
+}
+
+#[test]
+fn lookup() {
+    let j = sample();
+    assert_eq!(j.pointer(""), Some(&j));
+    assert_eq!(j.pointer("/servers/1/host"), Some(&json!("b.example")));
+    assert_eq!(j.pointer("/a~1b"), Some(&json!(1)));
+    assert_eq!(j.pointer("/m~0n"), Some(&json!(2)));
+    assert_eq!(j.pointer("/"), Some(&json!(3)));
+    assert_eq!(j.pointer("/servers/2"), None);
+    assert_eq!(j.pointer("/servers/-"), None);
+}
+
+#[test]
+fn lookup_errors() {
+    let j = sample();
+    assert_eq!(j.try_pointer("servers"), Err(PointerError::MissingSlash));
+    assert_eq!(j.try_pointer("/m~2n"), Err(PointerError::InvalidEscape));
+    assert_eq!(
+        j.try_pointer("/servers/01"),
+        Err(PointerError::InvalidIndex("01".into()))
+    );
+    assert_eq!(
+        j.try_pointer("/servers/+1"),
+        Err(PointerError::InvalidIndex("+1".into()))
+    );
+    assert_eq!(
+        j.try_pointer("/nope"),
+        Err(PointerError::NoSuchKey("nope".into()))
+    );
+    assert_eq!(
+        j.try_pointer("/a~1b/c"),
+        Err(PointerError::NotAContainer("c".into()))
+    );
+}
+
+#[test]
+fn escaping() {
+    assert_eq!(escape("a/b~c"), "a~1b~0c");
+    assert_eq!(join(["servers", "0", "a/b"]), "/servers/0/a~1b");
+    assert_eq!(parse("/x~01/y").unwrap(), vec!["x~1", "y"]);
+}
+
+#[test]
+fn mutate() {
+    let mut j = sample();
+    *j.pointer_mut("/servers/0/host").unwrap() = json!("c.example");
+    assert_eq!(j["servers"][0]["host"], json!("c.example"));
+}
+
+#[test]
+fn insert() {
+    let mut j = sample();
+    assert_eq!(
+        j.pointer_insert("/servers/0", json!({"host": "z"})),
+        Ok(None)
+    );
+    assert_eq!(j.pointer("/servers/0/host"), Some(&json!("z")));
+    assert_eq!(j.pointer_insert("/servers/-", json!(null)), Ok(None));
+    assert_eq!(j["servers"][3], json!(null));
+    assert_eq!(j.pointer_insert("/a~1b", json!(9)), Ok(Some(json!(1))));
+    assert_eq!(
+        j.pointer_insert("/servers/9", json!(0)),
+        Err(PointerError::IndexOutOfBounds(9))
+    );
+    assert_eq!(
+        j.pointer_insert("/missing/key", json!(0)),
+        Err(PointerError::NoSuchKey("missing".into()))
+    );
+
+    let before = j.clone();
+    assert_eq!(j.pointer_insert("", json!(true)), Ok(Some(before)));
+    assert_eq!(j, json!(true));
+}
+
+#[test]
+fn remove() {
+    let mut j = sample();
+    assert_eq!(
+        j.pointer_remove("/servers/0"),
+        Ok(json!({"host": "a.example"}))
+    );
+    assert_eq!(j["servers"].as_array().map(Vec::len), Some(1));
+    assert_eq!(j.pointer_remove("/m~0n"), Ok(json!(2)));
+    assert_eq!(
+        j.pointer_remove("/servers/-"),
+        Err(PointerError::IndexOutOfBounds(1))
+    );
+
+    let before = j.clone();
+    assert_eq!(j.pointer_remove(""), Ok(before));
+    assert!(j.is_null());
+}
@@ -0,0 +1,488 @@
+//! A small regular expression engine, covering the I-Regexp (RFC 9485)
+//! syntax used by `path` filters plus the `^`/`$` anchors that JSON Schema
+//! patterns commonly rely on.
+//!
+//! Patterns are compiled to a program for a Pike VM, which runs every way
+//! of matching at once, a character at a time. That takes time linear in
+//! the input and a fixed amount of memory per program instruction, however
+//! the pattern and input are crafted.
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug)]
+enum Node {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    Group(Vec<Vec<Node>>),
+    Repeat(Box<Node>, usize, Option<usize>),
+}
+
+#[derive(Clone, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Digit(bool),
+    Word(bool),
+    Space(bool),
+    Property(Property, bool),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Property {
+    Letter,
+    Upper,
+    Lower,
+    Number,
+    Digit,
+    Punct,
+    Space,
+    Control,
+}
+
+impl Property {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "L" => Some(Property::Letter),
+            "Lu" => Some(Property::Upper),
+            "Ll" => Some(Property::Lower),
+            "N" => Some(Property::Number),
+            "Nd" => Some(Property::Digit),
+            "P" => Some(Property::Punct),
+            "Z" | "Zs" => Some(Property::Space),
+            "C" | "Cc" => Some(Property::Control),
+            _ => None,
+        }
+    }
+
+    fn matches(self, c: char) -> bool {
+        match self {
+            Property::Letter => c.is_alphabetic(),
+            Property::Upper => c.is_uppercase(),
+            Property::Lower => c.is_lowercase(),
+            Property::Number => c.is_numeric(),
+            Property::Digit => c.is_ascii_digit(),
+            Property::Punct => c.is_ascii_punctuation(),
+            Property::Space => c.is_whitespace() && !c.is_control(),
+            Property::Control => c.is_control(),
+        }
+    }
+}
+
+impl ClassItem {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            ClassItem::Range(lo, hi) => *lo <= c && c <= *hi,
+            ClassItem::Digit(neg) => c.is_ascii_digit() != *neg,
+            ClassItem::Word(neg) => (c.is_ascii_alphanumeric() || c == '_') != *neg,
+            ClassItem::Space(neg) => c.is_whitespace() != *neg,
+            ClassItem::Property(p, neg) => p.matches(c) != *neg,
+        }
+    }
+}
+
+/// An instruction of a compiled pattern.
+#[derive(Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Vec<ClassItem>, bool),
+    Start,
+    End,
+    /// Continue at both.
+    Split(usize, usize),
+    Jump(usize),
+    Match,
+}
+
+/// The most instructions a pattern may compile to. Counted repetitions are
+/// compiled to a copy of what's repeated each time, so `(a{1000}){1000}`
+/// would otherwise take a million.
+const MAX_PROGRAM: usize = 100_000;
+
+#[derive(Debug)]
+pub(crate) struct Regex {
+    program: Vec<Inst>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct RegexError;
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Parser<'_> {
+    fn alternatives(&mut self) -> Result<Vec<Vec<Node>>, RegexError> {
+        let mut alts = vec![self.sequence()?];
+
+        while self.chars.peek() == Some(&'|') {
+            self.chars.next();
+            alts.push(self.sequence()?);
+        }
+
+        Ok(alts)
+    }
+
+    fn sequence(&mut self) -> Result<Vec<Node>, RegexError> {
+        let mut seq = Vec::new();
+
+        while let Some(&c) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+
+            let atom = self.atom()?;
+            let atom = self.quantifier(atom)?;
+            seq.push(atom);
+        }
+
+        Ok(seq)
+    }
+
+    fn atom(&mut self) -> Result<Node, RegexError> {
+        match self.chars.next().ok_or(RegexError)? {
+            '.' => Ok(Node::Any),
+            '^' => Ok(Node::Start),
+            '$' => Ok(Node::End),
+            '(' => {
+                if self.chars.peek() == Some(&'?') {
+                    self.chars.next();
+                    if self.chars.next() != Some(':') {
+                        return Err(RegexError);
+                    }
+                }
+
+                let alts = self.alternatives()?;
+                if self.chars.next() == Some(')') {
+                    Ok(Node::Group(alts))
+                } else {
+                    Err(RegexError)
+                }
+            }
+            '[' => self.class(),
+            '\\' => match self.escape()? {
+                ClassItem::Range(c, _) => Ok(Node::Char(c)),
+                item => Ok(Node::Class(vec![item], false)),
+            },
+            '*' | '+' | '?' | '{' | ')' | ']' | '}' => Err(RegexError),
+            c => Ok(Node::Char(c)),
+        }
+    }
+
+    fn quantifier(&mut self, atom: Node) -> Result<Node, RegexError> {
+        let (min, max) = match self.chars.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => {
+                self.chars.next();
+                let min = self.integer()?;
+                let max = match self.chars.next() {
+                    Some('}') => return Ok(Node::Repeat(Box::new(atom), min, Some(min))),
+                    Some(',') if self.chars.peek() == Some(&'}') => None,
+                    Some(',') => Some(self.integer()?),
+                    _ => return Err(RegexError),
+                };
+                if self.chars.next() != Some('}') || max.is_some_and(|m| m < min) {
+                    return Err(RegexError);
+                }
+                return Ok(Node::Repeat(Box::new(atom), min, max));
+            }
+            _ => return Ok(atom),
+        };
+
+        self.chars.next();
+        Ok(Node::Repeat(Box::new(atom), min, max))
+    }
+
+    fn integer(&mut self) -> Result<usize, RegexError> {
+        let mut digits = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !c.is_ascii_digit() {
+                break;
+            }
+            digits.push(c);
+            self.chars.next();
+        }
+        digits.parse().map_err(|_| RegexError)
+    }
+
+    fn class(&mut self) -> Result<Node, RegexError> {
+        let negated = self.chars.peek() == Some(&'^');
+        if negated {
+            self.chars.next();
+        }
+
+        let mut items = Vec::new();
+        let mut first = true;
+
+        loop {
+            let c = self.chars.next().ok_or(RegexError)?;
+            let item = match c {
+                ']' if !first => break,
+                '\\' => self.escape()?,
+                c => ClassItem::Range(c, c),
+            };
+            first = false;
+
+            match item {
+                ClassItem::Range(lo, _) if self.chars.peek() == Some(&'-') => {
+                    self.chars.next();
+                    match self.chars.next().ok_or(RegexError)? {
+                        ']' => {
+                            items.push(ClassItem::Range(lo, lo));
+                            items.push(ClassItem::Range('-', '-'));
+                            break;
+                        }
+                        '\\' => match self.escape()? {
+                            ClassItem::Range(hi, _) if hi >= lo => {
+                                items.push(ClassItem::Range(lo, hi));
+                            }
+                            _ => return Err(RegexError),
+                        },
+                        hi if hi >= lo => items.push(ClassItem::Range(lo, hi)),
+                        _ => return Err(RegexError),
+                    }
+                }
+                item => items.push(item),
+            }
+        }
+
+        Ok(Node::Class(items, negated))
+    }
+
+    fn escape(&mut self) -> Result<ClassItem, RegexError> {
+        let single = |c| Ok(ClassItem::Range(c, c));
+
+        match self.chars.next().ok_or(RegexError)? {
+            'd' => Ok(ClassItem::Digit(false)),
+            'D' => Ok(ClassItem::Digit(true)),
+            'w' => Ok(ClassItem::Word(false)),
+            'W' => Ok(ClassItem::Word(true)),
+            's' => Ok(ClassItem::Space(false)),
+            'S' => Ok(ClassItem::Space(true)),
+            'n' => single('\n'),
+            'r' => single('\r'),
+            't' => single('\t'),
+            'f' => single('\u{c}'),
+            'v' => single('\u{b}'),
+            'u' => {
+                let hex: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+                    .map_or(Err(RegexError), single)
+            }
+            p @ ('p' | 'P') => {
+                if self.chars.next() != Some('{') {
+                    return Err(RegexError);
+                }
+                let name: String = self.chars.by_ref().take_while(|&c| c != '}').collect();
+                Property::from_name(&name)
+                    .map(|prop| ClassItem::Property(prop, p == 'P'))
+                    .ok_or(RegexError)
+            }
+            c if c.is_ascii_punctuation() => single(c),
+            _ => Err(RegexError),
+        }
+    }
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> Result<usize, RegexError> {
+        if self.program.len() == MAX_PROGRAM {
+            return Err(RegexError);
+        }
+        self.program.push(inst);
+        Ok(self.program.len() - 1)
+    }
+
+    fn alternatives(&mut self, alts: &[Vec<Node>]) -> Result<(), RegexError> {
+        let mut jumps = Vec::new();
+
+        for (i, seq) in alts.iter().enumerate() {
+            if i + 1 == alts.len() {
+                self.sequence(seq)?;
+            } else {
+                let split = self.push(Inst::Split(0, 0))?;
+                self.sequence(seq)?;
+                jumps.push(self.push(Inst::Jump(0))?);
+                self.program[split] = Inst::Split(split + 1, self.program.len());
+            }
+        }
+
+        let end = self.program.len();
+        for jump in jumps {
+            self.program[jump] = Inst::Jump(end);
+        }
+        Ok(())
+    }
+
+    fn sequence(&mut self, seq: &[Node]) -> Result<(), RegexError> {
+        seq.iter().try_for_each(|node| self.node(node))
+    }
+
+    fn node(&mut self, node: &Node) -> Result<(), RegexError> {
+        match node {
+            Node::Char(c) => self.push(Inst::Char(*c)).map(drop),
+            Node::Any => self.push(Inst::Any).map(drop),
+            Node::Class(items, negated) => {
+                self.push(Inst::Class(items.clone(), *negated)).map(drop)
+            }
+            Node::Start => self.push(Inst::Start).map(drop),
+            Node::End => self.push(Inst::End).map(drop),
+            Node::Group(alts) => self.alternatives(alts),
+            Node::Repeat(inner, min, max) => {
+                for _ in 0..*min {
+                    self.node(inner)?;
+                }
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0))?;
+                        self.node(inner)?;
+                        self.push(Inst::Jump(split))?;
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                    Some(max) => {
+                        for _ in *min..*max {
+                            let split = self.push(Inst::Split(0, 0))?;
+                            self.node(inner)?;
+                            self.program[split] = Inst::Split(split + 1, self.program.len());
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The instructions waiting on the next character, and at which position
+/// each instruction was last added, so it's only added once per position.
+struct Threads {
+    pcs: Vec<usize>,
+    added: Vec<usize>,
+}
+
+impl Threads {
+    fn new(len: usize) -> Self {
+        Threads {
+            pcs: Vec::new(),
+            added: vec![usize::MAX; len],
+        }
+    }
+}
+
+impl Regex {
+    pub(crate) fn new(pattern: &str) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            chars: pattern.chars().peekable(),
+        };
+        let alternatives = parser.alternatives()?;
+
+        if parser.chars.next().is_some() {
+            return Err(RegexError);
+        }
+
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        compiler.alternatives(&alternatives)?;
+        compiler.push(Inst::Match)?;
+        Ok(Regex {
+            program: compiler.program,
+        })
+    }
+
+    /// Whether the whole of `s` matches the pattern.
+    pub(crate) fn is_full_match(&self, s: &str) -> bool {
+        let input: Vec<char> = s.chars().collect();
+        self.run(&input, false)
+    }
+
+    /// Whether any substring of `s` matches the pattern.
+    pub(crate) fn is_match(&self, s: &str) -> bool {
+        let input: Vec<char> = s.chars().collect();
+        self.run(&input, true)
+    }
+
+    /// Run the program over `input`, starting a match at each position if
+    /// `search`, or else only at the start and needing it to end at the
+    /// end.
+    fn run(&self, input: &[char], search: bool) -> bool {
+        let mut current = Threads::new(self.program.len());
+        let mut next = Threads::new(self.program.len());
+        let mut stack = Vec::new();
+
+        for pos in 0..=input.len() {
+            if (search || pos == 0)
+                && self.add(&mut current, 0, pos, input.len(), search, &mut stack)
+            {
+                return true;
+            }
+            let Some(&c) = input.get(pos) else {
+                break;
+            };
+            for &pc in &current.pcs {
+                let matches = match &self.program[pc] {
+                    Inst::Char(x) => *x == c,
+                    Inst::Any => c != '\n' && c != '\r',
+                    Inst::Class(items, negated) => items.iter().any(|i| i.matches(c)) != *negated,
+                    _ => false,
+                };
+                if matches && self.add(&mut next, pc + 1, pos + 1, input.len(), search, &mut stack)
+                {
+                    return true;
+                }
+            }
+            current.pcs.clear();
+            std::mem::swap(&mut current, &mut next);
+        }
+
+        false
+    }
+
+    /// Add the instruction at `pc` to `threads` for position `pos`, and
+    /// everything it leads to without reading a character. Returns whether
+    /// that reaches a match.
+    fn add(
+        &self,
+        threads: &mut Threads,
+        pc: usize,
+        pos: usize,
+        len: usize,
+        search: bool,
+        stack: &mut Vec<usize>,
+    ) -> bool {
+        stack.push(pc);
+        while let Some(pc) = stack.pop() {
+            if threads.added[pc] == pos {
+                continue;
+            }
+            threads.added[pc] = pos;
+            match self.program[pc] {
+                Inst::Split(a, b) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                Inst::Jump(to) => stack.push(to),
+                Inst::Start if pos == 0 => stack.push(pc + 1),
+                Inst::End if pos == len => stack.push(pc + 1),
+                Inst::Match if search || pos == len => {
+                    stack.clear();
+                    return true;
+                }
+                Inst::Start | Inst::End | Inst::Match => {}
+                Inst::Char(_) | Inst::Any | Inst::Class(..) => threads.pcs.push(pc),
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,85 @@
+#![cfg(test)]
+
+use super::*;
+
+fn full(pattern: &str, s: &str) -> bool {
+    Regex::new(pattern).unwrap().is_full_match(s)
+}
+
+fn search(pattern: &str, s: &str) -> bool {
+    Regex::new(pattern).unwrap().is_match(s)
+}
+
+#[test]
+fn literals_and_any() {
+    assert!(full("abc", "abc"));
+    assert!(!full("abc", "abcd"));
+    assert!(full("a.c", "abc"));
+    assert!(!full("a.c", "a\nc"));
+    assert!(full(r"a\.c", "a.c"));
+    assert!(!full(r"a\.c", "abc"));
+}
+
+#[test]
+fn classes() {
+    assert!(full("[a-c]+", "abcba"));
+    assert!(!full("[a-c]+", "abd"));
+    assert!(full("[^0-9]*", "xyz"));
+    assert!(full(r"[\d-]+", "12-34"));
+    assert!(full(r"\w+\s\W", "ab_1 !"));
+    assert!(full(r"\p{Lu}\p{Ll}+", "Émile"));
+}
+
+#[test]
+fn quantifiers() {
+    assert!(full("a{2,3}", "aa"));
+    assert!(full("a{2,3}", "aaa"));
+    assert!(!full("a{2,3}", "aaaa"));
+    assert!(full("a{2}b?", "aab"));
+    assert!(full("a{1,}", "aaaaa"));
+    assert!(full("(a*)*b", "aaab"));
+}
+
+#[test]
+fn groups_and_alternation() {
+    assert!(full("(ab|cd)+", "abcdab"));
+    assert!(full("(?:x|y)z", "yz"));
+    assert!(!full("ab|cd", "abcd"));
+}
+
+#[test]
+fn searching() {
+    assert!(search("b.d", "abcde"));
+    assert!(!search("^b", "abc"));
+    assert!(search("^a", "abc"));
+    assert!(search("c$", "abc"));
+    assert!(search("", "anything"));
+}
+
+#[test]
+fn reject_invalid() {
+    assert!(Regex::new("(ab").is_err());
+    assert!(Regex::new("ab)").is_err());
+    assert!(Regex::new("[ab").is_err());
+    assert!(Regex::new("*a").is_err());
+    assert!(Regex::new("a{3,2}").is_err());
+    assert!(Regex::new(r"\p{Nope}").is_err());
+}
+
+#[test]
+fn long_input() {
+    // Deep enough to overflow the stack matching a character at a time by
+    // recursion.
+    let s = "a".repeat(100_000);
+    assert!(full("a*", &s));
+    assert!(full("^(a|b)*$", &s));
+    assert!(!full("a*b", &s));
+    assert!(search("a{3}$", &s));
+    assert!(!search("(a*)*b", &s));
+}
+
+#[test]
+fn too_big() {
+    assert!(Regex::new("(a{1000}){1000}").is_err());
+    assert!(Regex::new("a{1000}").is_ok());
+}
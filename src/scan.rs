@@ -0,0 +1,390 @@
+//! Finding the structure of a document in bulk, before tokenizing it.
+//!
+//! This is the first stage of [simdjson]: the input is classified 64 bytes
+//! at a time into bitmasks of quotes, backslashes, operators and
+//! whitespace, which give where strings are and so which characters
+//! start a token. With SSE2 or AVX2 the classifying takes a few vector
+//! compares per block; elsewhere it's done a byte at a time, and the rest
+//! is the same `u64` arithmetic either way.
+//!
+//! The input is scanned a few kilobytes at a time, and the tokens are read
+//! at the offsets in each window before the next is scanned, so the limits
+//! are checked as it goes. The tokens are read with the same functions the
+//! byte-at-a-time tokenizer uses. Anything unusual, like an error, an
+//! escape the scan and the tokenizer might disagree on or non-ASCII
+//! whitespace, gives up, and the input is tokenized the slow way instead.
+//!
+//! [simdjson]: https://arxiv.org/abs/1902.08318
+
+use std::convert::TryInto;
+
+use super::{next_token, try_get_string, Limits, Token, TokenRecord};
+
+#[cfg(test)]
+mod tests;
+
+/// Which bytes of a block are which.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Block {
+    quote: u64,
+    backslash: u64,
+    /// `{`, `}`, `[`, `]`, `,` and `:`.
+    op: u64,
+    /// JSON whitespace: space, tab, line feed and carriage return.
+    space: u64,
+}
+
+/// What's carried from one block to the next.
+#[derive(Default)]
+struct Scanner {
+    /// Whether the last block ended in an odd run of backslashes.
+    escape: bool,
+    /// All ones if the last block ended inside a string.
+    string: u64,
+    /// Whether the last block ended in a scalar.
+    scalar: bool,
+}
+
+impl Scanner {
+    /// The offsets in the block at `base` that start a token: operators,
+    /// both quotes around strings, and the starts of numbers and literals.
+    fn structurals(&mut self, block: Block, base: usize, out: &mut Vec<usize>) {
+        let quote = block.quote & !escaped(block.backslash, &mut self.escape);
+        let string = prefix_xor(quote) ^ self.string;
+        self.string = 0u64.wrapping_sub(string >> 63);
+
+        let scalar = !(block.op | block.space | block.quote | string);
+        let scalar_start = scalar & !(scalar << 1 | u64::from(self.scalar));
+        self.scalar = scalar >> 63 == 1;
+
+        let mut bits = (block.op & !string) | quote | scalar_start;
+        while bits != 0 {
+            out.push(base + bits.trailing_zeros() as usize);
+            bits &= bits - 1;
+        }
+    }
+}
+
+/// The bytes escaped by a backslash, given where the backslashes are and
+/// whether the last block left the first byte escaped. Backslashes are rare
+/// enough that this walks them one at a time.
+fn escaped(backslash: u64, carry: &mut bool) -> u64 {
+    let mut escaped = u64::from(*carry);
+    // An escaped backslash doesn't escape anything.
+    let mut bits = backslash & !escaped;
+    *carry = false;
+    while bits != 0 {
+        let i = bits.trailing_zeros();
+        if i == 63 {
+            *carry = true;
+            break;
+        }
+        escaped |= 1 << (i + 1);
+        bits &= !(0b11 << i);
+    }
+    escaped
+}
+
+/// Each bit set if an odd number of bits at or below it are set in `x`,
+/// which turns the opening and closing quotes into the insides of strings,
+/// including the opening quotes.
+fn prefix_xor(mut x: u64) -> u64 {
+    x ^= x << 1;
+    x ^= x << 2;
+    x ^= x << 4;
+    x ^= x << 8;
+    x ^= x << 16;
+    x ^= x << 32;
+    x
+}
+
+/// How many bytes are scanned at once. Only the offsets in one window are
+/// held at a time, so the tokens can be checked against the limits as
+/// they're found.
+const WINDOW: usize = 64 * 64;
+
+/// The offsets of the tokens in an input, scanned a window at a time.
+/// These are only all of them if it's valid: a backslash outside a string,
+/// say, can hide a quote.
+pub(crate) struct Structurals<'a> {
+    input: &'a [u8],
+    scanner: Scanner,
+    /// Where the next window starts.
+    base: usize,
+    /// The offsets in the current window, and how many have been taken.
+    offsets: Vec<usize>,
+    taken: usize,
+}
+
+impl<'a> Structurals<'a> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Structurals {
+            input,
+            scanner: Scanner::default(),
+            base: 0,
+            offsets: Vec::new(),
+            taken: 0,
+        }
+    }
+}
+
+impl Iterator for Structurals<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.taken == self.offsets.len() {
+            if self.base == self.input.len() {
+                return None;
+            }
+            let end = self.input.len().min(self.base + WINDOW);
+            self.offsets.clear();
+            self.taken = 0;
+            scan_window(
+                &mut self.scanner,
+                &self.input[self.base..end],
+                self.base,
+                &mut self.offsets,
+            );
+            self.base = end;
+        }
+        self.taken += 1;
+        Some(self.offsets[self.taken - 1])
+    }
+}
+
+/// Push the offsets of the structurals in `window`, which starts at `base`
+/// in the input, onto `out`.
+fn scan_window(scanner: &mut Scanner, window: &[u8], base: usize, out: &mut Vec<usize>) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU supports AVX2.
+            return unsafe { x86::scan_avx2(scanner, window, base, out) };
+        }
+        if is_x86_feature_detected!("sse2") {
+            // SAFETY: the CPU supports SSE2.
+            return unsafe { x86::scan_sse2(scanner, window, base, out) };
+        }
+    }
+    scan(scanner, window, base, classify, out);
+}
+
+/// Push the structurals of `input`, which starts at `base`, onto `out`,
+/// classifying each block with `classify`. Only the last block of the
+/// whole input may be short. Inlining this into the callers with vector
+/// instructions enabled lets `classify` use them too.
+#[allow(clippy::inline_always)]
+#[inline(always)]
+fn scan(
+    scanner: &mut Scanner,
+    input: &[u8],
+    mut base: usize,
+    classify: impl Fn(&[u8; 64]) -> Block,
+    out: &mut Vec<usize>,
+) {
+    let mut chunks = input.chunks_exact(64);
+    for chunk in &mut chunks {
+        let chunk = chunk.try_into().expect("chunk is 64 bytes");
+        scanner.structurals(classify(chunk), base, out);
+        base += 64;
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        // Pad with whitespace, which is never structural.
+        let mut last = [b' '; 64];
+        last[..rest.len()].copy_from_slice(rest);
+        scanner.structurals(classify(&last), base, out);
+    }
+}
+
+/// Classify a block a byte at a time.
+fn classify(chunk: &[u8; 64]) -> Block {
+    let mut block = Block::default();
+    for (i, &byte) in chunk.iter().enumerate() {
+        let bit = 1 << i;
+        match byte {
+            b'"' => block.quote |= bit,
+            b'\\' => block.backslash |= bit,
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => block.op |= bit,
+            b' ' | b'\t' | b'\n' | b'\r' => block.space |= bit,
+            _ => {}
+        }
+    }
+    block
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86 as arch;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64 as arch;
+
+    use arch::{
+        __m128i, __m256i, _mm256_cmpeq_epi8, _mm256_loadu_si256, _mm256_movemask_epi8,
+        _mm256_or_si256, _mm256_set1_epi8, _mm256_setzero_si256, _mm_cmpeq_epi8, _mm_loadu_si128,
+        _mm_movemask_epi8, _mm_or_si128, _mm_set1_epi8, _mm_setzero_si128,
+    };
+
+    use super::{scan, Block, Scanner};
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn scan_sse2(
+        scanner: &mut Scanner,
+        input: &[u8],
+        base: usize,
+        out: &mut Vec<usize>,
+    ) {
+        // SAFETY: SSE2 is enabled here, and `classify_sse2` only needs that.
+        scan(
+            scanner,
+            input,
+            base,
+            |chunk| unsafe { classify_sse2(chunk) },
+            out,
+        );
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn scan_avx2(
+        scanner: &mut Scanner,
+        input: &[u8],
+        base: usize,
+        out: &mut Vec<usize>,
+    ) {
+        // SAFETY: AVX2 is enabled here, and `classify_avx2` only needs that.
+        scan(
+            scanner,
+            input,
+            base,
+            |chunk| unsafe { classify_avx2(chunk) },
+            out,
+        );
+    }
+
+    // The casts reinterpret bytes and masks, and an SSE2 mask is 16 bits,
+    // so they're lossless.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn classify_sse2(chunk: &[u8; 64]) -> Block {
+        let splat = |byte: u8| _mm_set1_epi8(byte as i8);
+        let mut block = Block::default();
+        for (i, lane) in chunk.chunks_exact(16).enumerate() {
+            // SAFETY: `lane` is 16 bytes, and the load may be unaligned.
+            let v = unsafe { _mm_loadu_si128(lane.as_ptr().cast()) };
+            let is = |byte: u8| _mm_cmpeq_epi8(v, splat(byte));
+            let any = |bytes: &[u8]| {
+                bytes
+                    .iter()
+                    .fold(_mm_setzero_si128(), |acc, &b| _mm_or_si128(acc, is(b)))
+            };
+            let mask = |m: __m128i| u64::from(_mm_movemask_epi8(m) as u16) << (16 * i);
+            block.quote |= mask(is(b'"'));
+            block.backslash |= mask(is(b'\\'));
+            block.op |= mask(any(b"{}[],:"));
+            block.space |= mask(any(b" \t\n\r"));
+        }
+        block
+    }
+
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn classify_avx2(chunk: &[u8; 64]) -> Block {
+        let splat = |byte: u8| _mm256_set1_epi8(byte as i8);
+        let mut block = Block::default();
+        for (i, lane) in chunk.chunks_exact(32).enumerate() {
+            // SAFETY: `lane` is 32 bytes, and the load may be unaligned.
+            let v = unsafe { _mm256_loadu_si256(lane.as_ptr().cast()) };
+            let is = |byte: u8| _mm256_cmpeq_epi8(v, splat(byte));
+            let any = |bytes: &[u8]| {
+                bytes.iter().fold(_mm256_setzero_si256(), |acc, &b| {
+                    _mm256_or_si256(acc, is(b))
+                })
+            };
+            let mask = |m: __m256i| u64::from(_mm256_movemask_epi8(m) as u32) << (32 * i);
+            block.quote |= mask(is(b'"'));
+            block.backslash |= mask(is(b'\\'));
+            block.op |= mask(any(b"{}[],:"));
+            block.space |= mask(any(b" \t\n\r"));
+        }
+        block
+    }
+}
+
+/// Tokenize `s` at the offsets from [`Structurals`], returning the tokens
+/// and what's left after the last one, or `None` to tokenize it the slow
+/// way. That includes going past the limits on single tokens, which the
+/// slow way reports. Like the slow way, it stops once there must be too
+/// many values.
+pub(crate) fn tokens<'a>(s: &'a str, limits: &Limits) -> Option<(Vec<TokenRecord>, &'a str)> {
+    let bytes = s.as_bytes();
+    let mut tokens = Vec::new();
+    let mut offsets = Structurals::new(bytes);
+    let mut end = 0;
+
+    while let Some(start) = offsets.next() {
+        let rest = &s[start..];
+        let simple = |token| (token, 1, rest.len());
+        let token = match bytes[start] {
+            b'{' => simple(Token::OpenCurly),
+            b'}' => simple(Token::CloseCurly),
+            b'[' => simple(Token::OpenSquare),
+            b']' => simple(Token::CloseSquare),
+            b',' => simple(Token::Comma),
+            b':' => simple(Token::Colon),
+            b'"' => {
+                let close = offsets.next()?;
+                let body = &s[start + 1..close];
+                if body.contains('\\') {
+                    let (token, _) = try_get_string(rest, limits.string_length).ok()?;
+                    // The scan and the tokenizer must agree on where the
+                    // string ends.
+                    if token.1 != close + 1 - start {
+                        return None;
+                    }
+                    token
+                } else if limits.string_length.is_some_and(|max| body.len() > max) {
+                    return None;
+                } else {
+                    (
+                        Token::StringLiteral(body.to_owned()),
+                        body.len() + 2,
+                        rest.len(),
+                    )
+                }
+            }
+            _ => {
+                let (Some(token), after) = next_token(rest, limits).ok()? else {
+                    return None;
+                };
+                // Only a number or literal starts here, and not after
+                // whitespace the scan took for part of it. It must be
+                // followed by something that starts a token or is skipped,
+                // or else the rest of it wasn't read.
+                let scalar = matches!(
+                    token.0,
+                    Token::NumberLiteral(_) | Token::Null | Token::True | Token::False
+                ) && token.2 == rest.len();
+                let ends = match after.as_bytes().first() {
+                    None => true,
+                    Some(&byte) => b"\"{}[],: \t\n\r".contains(&byte),
+                };
+                if !scalar || !ends {
+                    return None;
+                }
+                token
+            }
+        };
+        if limits.too_many_tokens(tokens.len() + 1) {
+            break;
+        }
+        end = s.len() - token.2 + token.1;
+        tokens.push(token);
+    }
+
+    Some((tokens, &s[end..]))
+}
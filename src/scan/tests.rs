@@ -0,0 +1,172 @@
+#![cfg(test)]
+
+use std::convert::TryFrom;
+
+use super::*;
+use crate::{ErrorKind, Json};
+
+/// The structurals of `input`, found a byte at a time.
+fn reference(input: &[u8]) -> Vec<usize> {
+    let mut out = Vec::new();
+    let (mut string, mut escaped, mut scalar) = (false, false, false);
+    for (i, &byte) in input.iter().enumerate() {
+        let was_escaped = escaped;
+        escaped = byte == b'\\' && !was_escaped;
+        let was_scalar = scalar;
+        scalar = false;
+        match byte {
+            b'"' if !was_escaped => {
+                out.push(i);
+                string = !string;
+            }
+            b'"' => {}
+            _ if string => {}
+            b'{' | b'}' | b'[' | b']' | b',' | b':' => out.push(i),
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            _ => {
+                scalar = true;
+                if !was_scalar {
+                    out.push(i);
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The tokens of `s` and what's after them, found a character at a time,
+/// if there are no errors.
+fn slow_tokens(mut s: &str) -> Option<(Vec<TokenRecord>, &str)> {
+    let mut tokens = Vec::new();
+    while let (Some(token), rest) = next_token(s, &Limits::default()).ok()? {
+        tokens.push(token);
+        s = rest;
+    }
+    Some((tokens, s))
+}
+
+/// Pseudo-random documents made of characters that matter to the scan,
+/// long enough to cross a few blocks.
+fn samples() -> Vec<String> {
+    let alphabet = [
+        "\"", "\"", "\\", "\\", "{", "}", "[", "]", ",", ":", " ", "\n", "\t", "1", "-0.5e3", "a",
+        "true", "null", "\u{e9}", "\u{a0}", "\u{c}", "\\\"", "\\u00e9",
+    ];
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        // The high bits are the most random.
+        usize::try_from(seed >> 33).unwrap()
+    };
+    (0..2000)
+        .map(|_| {
+            let len = next() % 200;
+            (0..len)
+                .map(|_| alphabet[next() % alphabet.len()])
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn backends_agree() {
+    let mut inputs = samples();
+    // Runs of backslashes and strings across the ends of blocks.
+    for pad in 58..66 {
+        for run in 0..4 {
+            let mut input = " ".repeat(pad);
+            input.push('"');
+            input.push_str(&"\\".repeat(run));
+            input.push_str("\"], [\"a\", 1]");
+            inputs.push(input);
+        }
+    }
+
+    // And across the ends of windows.
+    for pad in WINDOW - 3..WINDOW + 2 {
+        inputs.push(format!("[{}\"a\\\"b\", 1]", " ".repeat(pad)));
+    }
+
+    for input in &inputs {
+        let bytes = input.as_bytes();
+        let expected = reference(bytes);
+        let mut scalar = Vec::new();
+        scan(&mut Scanner::default(), bytes, 0, classify, &mut scalar);
+        assert_eq!(scalar, expected, "{input:?}");
+        assert_eq!(
+            Structurals::new(bytes).collect::<Vec<_>>(),
+            expected,
+            "{input:?}"
+        );
+
+        #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+        {
+            if is_x86_feature_detected!("sse2") {
+                let mut sse2 = Vec::new();
+                unsafe { x86::scan_sse2(&mut Scanner::default(), bytes, 0, &mut sse2) };
+                assert_eq!(sse2, expected, "{input:?}");
+            }
+            if is_x86_feature_detected!("avx2") {
+                let mut avx2 = Vec::new();
+                unsafe { x86::scan_avx2(&mut Scanner::default(), bytes, 0, &mut avx2) };
+                assert_eq!(avx2, expected, "{input:?}");
+            }
+        }
+    }
+}
+
+#[test]
+fn tokens_agree() {
+    for input in samples() {
+        if let Some(scanned) = tokens(&input, &Limits::default()) {
+            assert_eq!(Some(scanned), slow_tokens(&input), "{input:?}");
+        }
+    }
+}
+
+#[test]
+fn falls_back() {
+    let documents = [
+        (
+            "{\"a\": [1, -2.5e3, \"b\\\"\\u00e9\"], \"c\": {}, \"d\": null}",
+            true,
+        ),
+        ("  [true, false, \"\u{1f600}\"]  \n", true),
+        ("", true),
+        ("   ", true),
+        ("1 2", true),
+        ("[1,]", true),
+        ("[1,\u{a0}2]", false),
+        ("[\u{a0}]", false),
+        // The tokenizer doesn't end an escape after an unknown character.
+        ("\"\\x\", \"", false),
+        ("[\"\\x\"]", false),
+        ("[truex]", false),
+        ("[1-2]", false),
+        ("\"abc", false),
+        ("[\\\"a\"]", false),
+        ("[1, \u{e9}]", false),
+        // Whitespace the scan doesn't know, then some it does.
+        ("\u{2028}\t1", false),
+        ("[\u{a0} 1]", false),
+    ];
+    for (doc, scanned) in &documents {
+        assert_eq!(
+            tokens(doc, &Limits::default()).is_some(),
+            *scanned,
+            "{doc:?}"
+        );
+        if let Some(tokens) = tokens(doc, &Limits::default()) {
+            assert_eq!(Some(tokens), slow_tokens(doc), "{doc:?}");
+        }
+    }
+    assert_eq!("[1,\u{a0}2]".parse::<Json>().unwrap(), json!([1, 2]));
+    assert_eq!("\u{2028}\t1".parse::<Json>().unwrap(), json!(1));
+    assert_eq!("\"\\x\", \"".parse::<Json>().unwrap(), json!("x\", "));
+    assert_eq!(
+        "[1, \u{e9}]".parse::<Json>().unwrap_err(),
+        vec![(ErrorKind::UnexpectedToken, 4..6)]
+    );
+}
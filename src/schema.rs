@@ -0,0 +1,1155 @@
+//! JSON Schema (draft 2020-12) validation.
+//!
+//! ```
+//! # use jval::{json, schema::Schema};
+//! let schema = Schema::compile(&json!({
+//!     "type": "object",
+//!     "properties": {"port": {"type": "integer", "maximum": 65535}},
+//!     "required": ["port"]
+//! })).unwrap();
+//!
+//! assert!(schema.is_valid(&json!({"port": 8080})));
+//!
+//! let errors = schema.validate(&json!({"port": 70000})).unwrap_err();
+//! assert_eq!(errors[0].instance_path, "/port");
+//! assert_eq!(errors[0].schema_path, "/properties/port/maximum");
+//! ```
+//!
+//! `$ref` may point into the schema itself, at an embedded resource with its
+//! own `$id`, or at a local file; remote references are never fetched.
+//! `$dynamicRef` is resolved like `$ref`, and `format` is an annotation only.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::iter;
+use std::path::Path;
+
+use super::pointer;
+use super::regex::Regex;
+use super::Json;
+
+mod infer;
+
+#[cfg(test)]
+mod tests;
+
+pub use infer::infer;
+
+#[derive(Debug)]
+pub enum SchemaError {
+    /// A keyword has a value of the wrong type, e.g. a non-numeric `minimum`.
+    Invalid { path: String, message: String },
+    /// A `$ref` to something that is neither part of the schema nor a local file.
+    UnresolvedRef(String),
+    /// A schema file could not be read or parsed.
+    Load { uri: String, message: String },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::Invalid { path, message } => {
+                write!(f, "invalid schema at \"{path}\": {message}")
+            }
+            SchemaError::UnresolvedRef(r) => write!(f, "cannot resolve reference \"{r}\""),
+            SchemaError::Load { uri, message } => write!(f, "cannot load \"{uri}\": {message}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending value in the instance.
+    pub instance_path: String,
+    /// JSON Pointer to the failing keyword, prefixed with the file it is in
+    /// when that is not the root schema.
+    pub schema_path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at \"{}\" (schema \"{}\")",
+            self.message, self.instance_path, self.schema_path
+        )
+    }
+}
+
+/// A compiled schema, ready to validate instances.
+#[derive(Debug)]
+pub struct Schema {
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+impl Schema {
+    /// Compile an in-memory schema. Relative file references are resolved
+    /// against the working directory, unless the schema has an `$id`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a keyword is malformed, a `pattern` is not a supported regular
+    /// expression, or a `$ref` cannot be resolved or leads back to where it
+    /// started without moving into the instance.
+    pub fn compile(schema: &Json) -> Result<Self, SchemaError> {
+        Self::compile_document(schema.clone(), "")
+    }
+
+    /// Load and compile a schema file. Relative references are resolved
+    /// against the file's location.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the file cannot be read or parsed, or as for `compile`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let path = path.as_ref();
+        let uri = fs::canonicalize(path)
+            .map_err(|e| SchemaError::Load {
+                uri: path.display().to_string(),
+                message: e.to_string(),
+            })?
+            .to_string_lossy()
+            .into_owned();
+
+        Self::compile_document(load(&uri)?, &uri)
+    }
+
+    fn compile_document(schema: Json, uri: &str) -> Result<Self, SchemaError> {
+        let mut compiler = Compiler::default();
+        let doc = compiler.add_document(uri.to_string(), schema);
+        let root = compiler.compile(doc, "", uri)?;
+        check_cycles(&compiler.nodes)?;
+
+        Ok(Schema {
+            nodes: compiler.nodes,
+            root,
+        })
+    }
+
+    /// Check an instance against the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns every keyword the instance failed, with where it failed.
+    pub fn validate(&self, instance: &Json) -> Result<(), Vec<ValidationError>> {
+        let mut validator = Validator {
+            nodes: &self.nodes,
+            path: Vec::new(),
+            errors: Vec::new(),
+        };
+        validator.validate(self.root, instance);
+
+        if validator.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(validator.errors)
+        }
+    }
+
+    #[must_use]
+    pub fn is_valid(&self, instance: &Json) -> bool {
+        self.validate(instance).is_ok()
+    }
+}
+
+// compiled form
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Type {
+    Null,
+    Boolean,
+    Object,
+    Array,
+    Number,
+    String,
+    Integer,
+}
+
+impl Type {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "null" => Some(Type::Null),
+            "boolean" => Some(Type::Boolean),
+            "object" => Some(Type::Object),
+            "array" => Some(Type::Array),
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "integer" => Some(Type::Integer),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Object => "object",
+            Type::Array => "array",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Integer => "integer",
+        }
+    }
+
+    fn of(value: &Json) -> Self {
+        match value {
+            Json::Null => Type::Null,
+            Json::Boolean(_) => Type::Boolean,
+            Json::Number(n) if n.fract() == 0. => Type::Integer,
+            Json::Number(_) => Type::Number,
+            Json::String(_) => Type::String,
+            Json::Array(_) => Type::Array,
+            Json::Object(_) => Type::Object,
+        }
+    }
+
+    fn matches(self, value: &Json) -> bool {
+        let actual = Type::of(value);
+        actual == self || (self == Type::Number && actual == Type::Integer)
+    }
+}
+
+#[derive(Debug)]
+enum Node {
+    Accept,
+    Reject(String),
+    Keywords(Box<Keywords>),
+}
+
+#[derive(Debug, Default)]
+struct Keywords {
+    location: String,
+
+    reference: Option<usize>,
+    all_of: Vec<usize>,
+    any_of: Vec<usize>,
+    one_of: Vec<usize>,
+    not: Option<usize>,
+    condition: Option<(usize, Option<usize>, Option<usize>)>,
+
+    types: Vec<Type>,
+    enumeration: Option<Vec<Json>>,
+    constant: Option<Json>,
+
+    multiple_of: Option<f64>,
+    maximum: Option<f64>,
+    exclusive_maximum: Option<f64>,
+    minimum: Option<f64>,
+    exclusive_minimum: Option<f64>,
+
+    max_length: Option<usize>,
+    min_length: Option<usize>,
+    pattern: Option<(String, Regex)>,
+
+    prefix_items: Vec<usize>,
+    items: Option<usize>,
+    contains: Option<usize>,
+    max_contains: Option<usize>,
+    min_contains: Option<usize>,
+    max_items: Option<usize>,
+    min_items: Option<usize>,
+    unique_items: bool,
+    unevaluated_items: Option<usize>,
+
+    properties: BTreeMap<String, usize>,
+    pattern_properties: Vec<(Regex, usize)>,
+    additional_properties: Option<usize>,
+    property_names: Option<usize>,
+    max_properties: Option<usize>,
+    min_properties: Option<usize>,
+    required: Vec<String>,
+    dependent_required: BTreeMap<String, Vec<String>>,
+    dependent_schemas: BTreeMap<String, usize>,
+    unevaluated_properties: Option<usize>,
+}
+
+impl Keywords {
+    /// The subschemas applied to the same instance as this one.
+    fn in_place(&self) -> impl Iterator<Item = usize> + '_ {
+        let condition = self
+            .condition
+            .iter()
+            .flat_map(|(cond, then, otherwise)| iter::once(cond).chain(then).chain(otherwise));
+        self.reference
+            .iter()
+            .chain(&self.all_of)
+            .chain(&self.any_of)
+            .chain(&self.one_of)
+            .chain(&self.not)
+            .chain(condition)
+            .chain(self.dependent_schemas.values())
+            .copied()
+    }
+}
+
+// compilation
+
+type Object = BTreeMap<String, Json>;
+
+struct Document {
+    uri: String,
+    json: Json,
+}
+
+#[derive(Default)]
+struct Compiler {
+    documents: Vec<Document>,
+    /// Resource and anchor URIs, mapped to a document and a pointer into it.
+    resources: BTreeMap<String, (usize, String)>,
+    compiled: BTreeMap<(usize, String), usize>,
+    nodes: Vec<Node>,
+}
+
+/// Keywords whose value is a single subschema.
+const SINGLE: &[&str] = &[
+    "additionalProperties",
+    "contains",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+/// Keywords whose value is an array of subschemas.
+const LIST: &[&str] = &["allOf", "anyOf", "oneOf", "prefixItems"];
+/// Keywords whose value is an object of subschemas.
+const MAP: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+fn child(ptr: &str, tokens: &[&str]) -> String {
+    let mut out = ptr.to_string();
+    out.push_str(&pointer::join(tokens));
+    out
+}
+
+fn invalid(location: &str, keyword: &str, message: &str) -> SchemaError {
+    SchemaError::Invalid {
+        path: format!("{location}/{keyword}"),
+        message: message.to_string(),
+    }
+}
+
+/// Fail if a schema reaches itself again through references and the other
+/// keywords that apply to the same instance, like `{"$ref": "#"}`, which
+/// would be evaluated without end.
+fn check_cycles(nodes: &[Node]) -> Result<(), SchemaError> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Visit {
+        New,
+        Open,
+        Done,
+    }
+
+    let in_place = |id: usize| -> Vec<usize> {
+        match &nodes[id] {
+            Node::Keywords(k) => k.in_place().collect(),
+            _ => Vec::new(),
+        }
+    };
+    let mut visits = vec![Visit::New; nodes.len()];
+
+    for root in 0..nodes.len() {
+        if visits[root] != Visit::New {
+            continue;
+        }
+        visits[root] = Visit::Open;
+        let mut stack = vec![(root, in_place(root))];
+        while let Some((id, next)) = stack.last_mut() {
+            let id = *id;
+            match next.pop() {
+                Some(next) if visits[next] == Visit::Open => {
+                    let path = match &nodes[next] {
+                        Node::Keywords(k) => k.location.clone(),
+                        _ => String::new(),
+                    };
+                    return Err(SchemaError::Invalid {
+                        path,
+                        message: "refers back to itself without moving into the instance"
+                            .to_string(),
+                    });
+                }
+                Some(next) if visits[next] == Visit::New => {
+                    visits[next] = Visit::Open;
+                    stack.push((next, in_place(next)));
+                }
+                Some(_) => {}
+                None => {
+                    visits[id] = Visit::Done;
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Register every `$id` and `$anchor` in a document, so references can find
+/// them before they are compiled.
+fn scan(
+    schema: &Json,
+    base: &str,
+    doc: usize,
+    ptr: &str,
+    resources: &mut BTreeMap<String, (usize, String)>,
+) {
+    let Json::Object(o) = schema else {
+        return;
+    };
+
+    let base = match o.get("$id") {
+        Some(Json::String(id)) => {
+            let base = resolve_uri(id, base);
+            resources.insert(base.clone(), (doc, ptr.to_string()));
+            base
+        }
+        _ => base.to_string(),
+    };
+
+    for keyword in &["$anchor", "$dynamicAnchor"] {
+        if let Some(Json::String(anchor)) = o.get(*keyword) {
+            resources.insert(format!("{base}#{anchor}"), (doc, ptr.to_string()));
+        }
+    }
+
+    for keyword in SINGLE {
+        if let Some(sub) = o.get(*keyword) {
+            scan(sub, &base, doc, &child(ptr, &[keyword]), resources);
+        }
+    }
+    for keyword in LIST {
+        if let Some(Json::Array(subs)) = o.get(*keyword) {
+            for (i, sub) in subs.iter().enumerate() {
+                let ptr = child(ptr, &[keyword, &i.to_string()]);
+                scan(sub, &base, doc, &ptr, resources);
+            }
+        }
+    }
+    for keyword in MAP {
+        if let Some(Json::Object(subs)) = o.get(*keyword) {
+            for (name, sub) in subs {
+                scan(sub, &base, doc, &child(ptr, &[keyword, name]), resources);
+            }
+        }
+    }
+}
+
+fn has_scheme(uri: &str) -> bool {
+    match uri.find(':') {
+        // a single letter is more likely a Windows drive than a scheme
+        Some(i) if i > 1 => uri[..i]
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c)),
+        _ => false,
+    }
+}
+
+/// Resolve a URI reference against a base URI, without a trailing empty
+/// fragment.
+fn resolve_uri(reference: &str, base: &str) -> String {
+    let base = base.split('#').next().unwrap_or_default();
+
+    let resolved = if has_scheme(reference) {
+        reference.to_string()
+    } else if reference.is_empty() || reference.starts_with('#') {
+        format!("{base}{reference}")
+    } else if reference.starts_with('/') {
+        let authority = base.find("://").map_or(0, |i| {
+            base[i + 3..].find('/').map_or(base.len(), |j| i + 3 + j)
+        });
+        format!("{}{reference}", &base[..authority])
+    } else {
+        let dir = base.rfind('/').map_or(0, |i| i + 1);
+        format!("{}{reference}", &base[..dir])
+    };
+
+    normalize(resolved.strip_suffix('#').unwrap_or(&resolved))
+}
+
+/// Remove `.` and `..` path segments.
+fn normalize(uri: &str) -> String {
+    let (uri, fragment) = match uri.find('#') {
+        Some(i) => uri.split_at(i),
+        None => (uri, ""),
+    };
+    let start = uri.find("://").map_or(0, |i| {
+        uri[i + 3..].find('/').map_or(uri.len(), |j| i + 3 + j)
+    });
+    let (prefix, path) = uri.split_at(start);
+
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "." => {}
+            ".." => {
+                if segments.last().is_some_and(|s| !s.is_empty()) {
+                    segments.pop();
+                }
+            }
+            s => segments.push(s),
+        }
+    }
+
+    format!("{prefix}{}{fragment}", segments.join("/"))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let hex = s
+            .get(i + 1..i + 3)
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(b)) => {
+                out.push(b);
+                i += 3;
+            }
+            (b, _) => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Read a schema from a local file. Anything with a scheme other than
+/// `file://` is refused.
+fn load(uri: &str) -> Result<Json, SchemaError> {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    if path.is_empty() || has_scheme(path) {
+        return Err(SchemaError::UnresolvedRef(uri.to_string()));
+    }
+
+    let text = fs::read_to_string(path).map_err(|e| SchemaError::Load {
+        uri: uri.to_string(),
+        message: e.to_string(),
+    })?;
+
+    text.parse().map_err(|errors: Vec<_>| SchemaError::Load {
+        uri: uri.to_string(),
+        message: format!("{} JSON syntax error(s)", errors.len()),
+    })
+}
+
+fn number(o: &Object, keyword: &str, location: &str) -> Result<Option<f64>, SchemaError> {
+    match o.get(keyword) {
+        None => Ok(None),
+        Some(Json::Number(n)) => Ok(Some(*n)),
+        Some(_) => Err(invalid(location, keyword, "must be a number")),
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn count(o: &Object, keyword: &str, location: &str) -> Result<Option<usize>, SchemaError> {
+    match o.get(keyword) {
+        None => Ok(None),
+        Some(Json::Number(n)) if *n >= 0. && n.fract() == 0. => Ok(Some(*n as usize)),
+        Some(_) => Err(invalid(location, keyword, "must be a non-negative integer")),
+    }
+}
+
+fn strings(value: &Json, keyword: &str, location: &str) -> Result<Vec<String>, SchemaError> {
+    let error = || invalid(location, keyword, "must be an array of strings");
+    match value {
+        Json::Array(a) => a
+            .iter()
+            .map(|s| s.as_str().map(String::from).ok_or_else(error))
+            .collect(),
+        _ => Err(error()),
+    }
+}
+
+fn regex(pattern: &str, location: &str, keyword: &str) -> Result<Regex, SchemaError> {
+    Regex::new(pattern).map_err(|_| {
+        invalid(
+            location,
+            keyword,
+            &format!("unsupported regular expression \"{pattern}\""),
+        )
+    })
+}
+
+impl Compiler {
+    fn add_document(&mut self, uri: String, json: Json) -> usize {
+        let doc = self.documents.len();
+        self.resources.insert(uri.clone(), (doc, String::new()));
+        scan(&json, &uri, doc, "", &mut self.resources);
+        self.documents.push(Document { uri, json });
+        doc
+    }
+
+    fn compile(&mut self, doc: usize, ptr: &str, base: &str) -> Result<usize, SchemaError> {
+        if let Some(&id) = self.compiled.get(&(doc, ptr.to_string())) {
+            return Ok(id);
+        }
+
+        // reserve the slot first, so that recursive references find it
+        let id = self.nodes.len();
+        self.nodes.push(Node::Accept);
+        self.compiled.insert((doc, ptr.to_string()), id);
+
+        let location = if doc == 0 {
+            ptr.to_string()
+        } else {
+            format!("{}#{}", self.documents[doc].uri, ptr)
+        };
+        let schema = self.documents[doc]
+            .json
+            .pointer(ptr)
+            .cloned()
+            .ok_or_else(|| SchemaError::UnresolvedRef(location.clone()))?;
+
+        self.nodes[id] = match schema {
+            Json::Boolean(true) => Node::Accept,
+            Json::Boolean(false) => Node::Reject(location),
+            Json::Object(o) => {
+                let base = match o.get("$id") {
+                    Some(Json::String(id)) => resolve_uri(id, base),
+                    _ => base.to_string(),
+                };
+                let keywords = self.keywords(&o, doc, ptr, &base, &location)?;
+                Node::Keywords(Box::new(keywords))
+            }
+            _ => {
+                return Err(SchemaError::Invalid {
+                    path: location,
+                    message: "a schema must be an object or a boolean".to_string(),
+                })
+            }
+        };
+
+        Ok(id)
+    }
+
+    fn reference(&mut self, reference: &str, base: &str) -> Result<usize, SchemaError> {
+        let unresolved = || SchemaError::UnresolvedRef(reference.to_string());
+
+        let uri = resolve_uri(reference, base);
+        let (resource, fragment) = match uri.find('#') {
+            Some(i) => (&uri[..i], percent_decode(&uri[i + 1..])),
+            None => (&uri[..], String::new()),
+        };
+
+        let (doc, root) = if let Some(found) = self.resources.get(resource) {
+            found.clone()
+        } else {
+            let json = load(resource)?;
+            (self.add_document(resource.to_string(), json), String::new())
+        };
+
+        let (doc, ptr) = if fragment.is_empty() || fragment.starts_with('/') {
+            (doc, root + &fragment)
+        } else {
+            self.resources
+                .get(&format!("{resource}#{fragment}"))
+                .cloned()
+                .ok_or_else(unresolved)?
+        };
+
+        if self.documents[doc].json.pointer(&ptr).is_none() {
+            return Err(unresolved());
+        }
+        self.compile(doc, &ptr, resource)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn keywords(
+        &mut self,
+        o: &Object,
+        doc: usize,
+        ptr: &str,
+        base: &str,
+        loc: &str,
+    ) -> Result<Keywords, SchemaError> {
+        let single = |c: &mut Self, keyword: &str| match o.get(keyword) {
+            Some(_) => c.compile(doc, &child(ptr, &[keyword]), base).map(Some),
+            None => Ok(None),
+        };
+
+        let mut k = Keywords {
+            location: loc.to_string(),
+            ..Keywords::default()
+        };
+
+        for keyword in &["$ref", "$dynamicRef"] {
+            match o.get(*keyword) {
+                Some(Json::String(r)) => k.reference = Some(self.reference(r, base)?),
+                Some(_) => return Err(invalid(loc, keyword, "must be a string")),
+                None => {}
+            }
+        }
+
+        for keyword in LIST {
+            let ids = match o.get(*keyword) {
+                Some(Json::Array(subs)) => (0..subs.len())
+                    .map(|i| self.compile(doc, &child(ptr, &[keyword, &i.to_string()]), base))
+                    .collect::<Result<Vec<_>, _>>()?,
+                Some(_) => return Err(invalid(loc, keyword, "must be an array of schemas")),
+                None => continue,
+            };
+            match *keyword {
+                "allOf" => k.all_of = ids,
+                "anyOf" => k.any_of = ids,
+                "oneOf" => k.one_of = ids,
+                _ => k.prefix_items = ids,
+            }
+        }
+
+        let mut maps = BTreeMap::new();
+        for keyword in &["properties", "patternProperties", "dependentSchemas"] {
+            match o.get(*keyword) {
+                Some(Json::Object(subs)) => {
+                    let mut ids = BTreeMap::new();
+                    for name in subs.keys() {
+                        let id = self.compile(doc, &child(ptr, &[keyword, name]), base)?;
+                        ids.insert(name.clone(), id);
+                    }
+                    maps.insert(*keyword, ids);
+                }
+                Some(_) => return Err(invalid(loc, keyword, "must be an object of schemas")),
+                None => {}
+            }
+        }
+        k.properties = maps.remove("properties").unwrap_or_default();
+        k.dependent_schemas = maps.remove("dependentSchemas").unwrap_or_default();
+        k.pattern_properties = maps
+            .remove("patternProperties")
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(p, id)| regex(&p, loc, "patternProperties").map(|re| (re, id)))
+            .collect::<Result<_, _>>()?;
+
+        k.not = single(self, "not")?;
+        if let Some(cond) = single(self, "if")? {
+            k.condition = Some((cond, single(self, "then")?, single(self, "else")?));
+        }
+        k.items = single(self, "items")?;
+        k.contains = single(self, "contains")?;
+        k.unevaluated_items = single(self, "unevaluatedItems")?;
+        k.additional_properties = single(self, "additionalProperties")?;
+        k.property_names = single(self, "propertyNames")?;
+        k.unevaluated_properties = single(self, "unevaluatedProperties")?;
+
+        k.types = match o.get("type") {
+            None => Vec::new(),
+            Some(Json::String(t)) => vec![t.as_str()],
+            Some(Json::Array(ts)) => ts.iter().filter_map(Json::as_str).collect(),
+            Some(_) => return Err(invalid(loc, "type", "must be a string or an array")),
+        }
+        .into_iter()
+        .map(|t| Type::from_name(t).ok_or_else(|| invalid(loc, "type", "unknown type")))
+        .collect::<Result<_, _>>()?;
+
+        k.enumeration = match o.get("enum") {
+            Some(Json::Array(values)) => Some(values.clone()),
+            Some(_) => return Err(invalid(loc, "enum", "must be an array")),
+            None => None,
+        };
+        k.constant = o.get("const").cloned();
+
+        k.multiple_of = number(o, "multipleOf", loc)?;
+        if k.multiple_of.is_some_and(|m| m <= 0.) {
+            return Err(invalid(loc, "multipleOf", "must be greater than 0"));
+        }
+        k.maximum = number(o, "maximum", loc)?;
+        k.exclusive_maximum = number(o, "exclusiveMaximum", loc)?;
+        k.minimum = number(o, "minimum", loc)?;
+        k.exclusive_minimum = number(o, "exclusiveMinimum", loc)?;
+
+        k.max_length = count(o, "maxLength", loc)?;
+        k.min_length = count(o, "minLength", loc)?;
+        k.pattern = match o.get("pattern") {
+            Some(Json::String(p)) => Some((p.clone(), regex(p, loc, "pattern")?)),
+            Some(_) => return Err(invalid(loc, "pattern", "must be a string")),
+            None => None,
+        };
+
+        k.max_items = count(o, "maxItems", loc)?;
+        k.min_items = count(o, "minItems", loc)?;
+        k.max_contains = count(o, "maxContains", loc)?;
+        k.min_contains = count(o, "minContains", loc)?;
+        k.unique_items = match o.get("uniqueItems") {
+            Some(Json::Boolean(b)) => *b,
+            Some(_) => return Err(invalid(loc, "uniqueItems", "must be a boolean")),
+            None => false,
+        };
+
+        k.max_properties = count(o, "maxProperties", loc)?;
+        k.min_properties = count(o, "minProperties", loc)?;
+        if let Some(required) = o.get("required") {
+            k.required = strings(required, "required", loc)?;
+        }
+        match o.get("dependentRequired") {
+            Some(Json::Object(deps)) => {
+                for (name, required) in deps {
+                    let required = strings(required, "dependentRequired", loc)?;
+                    k.dependent_required.insert(name.clone(), required);
+                }
+            }
+            Some(_) => return Err(invalid(loc, "dependentRequired", "must be an object")),
+            None => {}
+        }
+
+        Ok(k)
+    }
+}
+
+// validation
+
+/// Instance locations that some keyword has successfully evaluated, which
+/// `unevaluatedItems` and `unevaluatedProperties` must skip.
+#[derive(Default)]
+struct Evaluated {
+    items: BTreeSet<usize>,
+    properties: BTreeSet<String>,
+}
+
+impl Evaluated {
+    fn merge(&mut self, other: Evaluated) {
+        self.items.extend(other.items);
+        self.properties.extend(other.properties);
+    }
+}
+
+struct Validator<'a> {
+    nodes: &'a [Node],
+    path: Vec<String>,
+    errors: Vec<ValidationError>,
+}
+
+impl Validator<'_> {
+    fn error(&mut self, location: &str, keyword: &str, message: String) {
+        self.errors.push(ValidationError {
+            instance_path: pointer::join(&self.path),
+            schema_path: if keyword.is_empty() {
+                location.to_string()
+            } else {
+                format!("{location}/{keyword}")
+            },
+            message,
+        });
+    }
+
+    /// Validate without reporting errors, returning the annotations if the
+    /// instance is valid.
+    fn probe(&mut self, id: usize, instance: &Json) -> Option<Evaluated> {
+        let saved = std::mem::take(&mut self.errors);
+        let evaluated = self.validate(id, instance);
+        let valid = self.errors.is_empty();
+        self.errors = saved;
+        valid.then_some(evaluated)
+    }
+
+    fn at(&mut self, token: String, id: usize, instance: &Json) {
+        self.path.push(token);
+        self.validate(id, instance);
+        self.path.pop();
+    }
+
+    fn validate(&mut self, id: usize, instance: &Json) -> Evaluated {
+        let nodes = self.nodes;
+        match &nodes[id] {
+            Node::Accept => Evaluated::default(),
+            Node::Reject(location) => {
+                self.error(location, "", "no value is allowed here".to_string());
+                Evaluated::default()
+            }
+            Node::Keywords(k) => self.keywords(k, instance),
+        }
+    }
+
+    fn keywords(&mut self, k: &Keywords, instance: &Json) -> Evaluated {
+        let loc = k.location.as_str();
+        let mut evaluated = Evaluated::default();
+
+        if let Some(r) = k.reference {
+            evaluated.merge(self.validate(r, instance));
+        }
+        for &s in &k.all_of {
+            evaluated.merge(self.validate(s, instance));
+        }
+        if !k.any_of.is_empty() {
+            let mut matched = false;
+            for &s in &k.any_of {
+                if let Some(e) = self.probe(s, instance) {
+                    evaluated.merge(e);
+                    matched = true;
+                }
+            }
+            if !matched {
+                self.error(loc, "anyOf", "does not match any anyOf schema".to_string());
+            }
+        }
+        if !k.one_of.is_empty() {
+            let mut matches: Vec<_> = k
+                .one_of
+                .iter()
+                .filter_map(|&s| self.probe(s, instance))
+                .collect();
+            if matches.len() == 1 {
+                evaluated.merge(matches.remove(0));
+            } else {
+                let message = format!("matches {} oneOf schemas, not exactly one", matches.len());
+                self.error(loc, "oneOf", message);
+            }
+        }
+        if let Some(s) = k.not {
+            if self.probe(s, instance).is_some() {
+                self.error(loc, "not", "must not match the schema".to_string());
+            }
+        }
+        if let Some((cond, then, otherwise)) = k.condition {
+            match self.probe(cond, instance) {
+                Some(e) => {
+                    evaluated.merge(e);
+                    if let Some(then) = then {
+                        evaluated.merge(self.validate(then, instance));
+                    }
+                }
+                None => {
+                    if let Some(otherwise) = otherwise {
+                        evaluated.merge(self.validate(otherwise, instance));
+                    }
+                }
+            }
+        }
+
+        if !k.types.is_empty() && !k.types.iter().any(|t| t.matches(instance)) {
+            let expected: Vec<_> = k.types.iter().map(|t| t.name()).collect();
+            let message = format!(
+                "expected {}, found {}",
+                expected.join(" or "),
+                Type::of(instance).name()
+            );
+            self.error(loc, "type", message);
+        }
+        if k.enumeration
+            .as_ref()
+            .is_some_and(|e| !e.contains(instance))
+        {
+            self.error(loc, "enum", "is not one of the allowed values".to_string());
+        }
+        if let Some(c) = &k.constant {
+            if c != instance {
+                self.error(loc, "const", format!("must be {c}"));
+            }
+        }
+
+        match instance {
+            Json::Number(n) => self.number(k, *n),
+            Json::String(s) => self.string(k, s),
+            Json::Array(a) => self.array(k, a, &mut evaluated),
+            Json::Object(o) => self.object(k, instance, o, &mut evaluated),
+            Json::Null | Json::Boolean(_) => {}
+        }
+
+        evaluated
+    }
+
+    fn number(&mut self, k: &Keywords, n: f64) {
+        let loc = k.location.as_str();
+
+        if let Some(m) = k.multiple_of {
+            let q = n / m;
+            if !q.is_finite() || (q - q.round()).abs() > f64::EPSILON * q.abs().max(1.) {
+                self.error(loc, "multipleOf", format!("must be a multiple of {m}"));
+            }
+        }
+        if let Some(max) = k.maximum.filter(|&max| n > max) {
+            self.error(loc, "maximum", format!("must be at most {max}"));
+        }
+        if let Some(max) = k.exclusive_maximum.filter(|&max| n >= max) {
+            self.error(loc, "exclusiveMaximum", format!("must be less than {max}"));
+        }
+        if let Some(min) = k.minimum.filter(|&min| n < min) {
+            self.error(loc, "minimum", format!("must be at least {min}"));
+        }
+        if let Some(min) = k.exclusive_minimum.filter(|&min| n <= min) {
+            self.error(
+                loc,
+                "exclusiveMinimum",
+                format!("must be greater than {min}"),
+            );
+        }
+    }
+
+    fn string(&mut self, k: &Keywords, s: &str) {
+        let loc = k.location.as_str();
+        let length = s.chars().count();
+
+        if let Some(max) = k.max_length.filter(|&max| length > max) {
+            self.error(
+                loc,
+                "maxLength",
+                format!("must be at most {max} characters long"),
+            );
+        }
+        if let Some(min) = k.min_length.filter(|&min| length < min) {
+            self.error(
+                loc,
+                "minLength",
+                format!("must be at least {min} characters long"),
+            );
+        }
+        if let Some((pattern, re)) = &k.pattern {
+            if !re.is_match(s) {
+                self.error(loc, "pattern", format!("does not match \"{pattern}\""));
+            }
+        }
+    }
+
+    fn array(&mut self, k: &Keywords, a: &[Json], evaluated: &mut Evaluated) {
+        let loc = k.location.as_str();
+
+        if let Some(max) = k.max_items.filter(|&max| a.len() > max) {
+            self.error(loc, "maxItems", format!("must have at most {max} items"));
+        }
+        if let Some(min) = k.min_items.filter(|&min| a.len() < min) {
+            self.error(loc, "minItems", format!("must have at least {min} items"));
+        }
+        if k.unique_items {
+            let duplicate = (0..a.len())
+                .flat_map(|i| (i + 1..a.len()).map(move |j| (i, j)))
+                .find(|&(i, j)| a[i] == a[j]);
+            if let Some((i, j)) = duplicate {
+                let message = format!("items {i} and {j} are equal");
+                self.error(loc, "uniqueItems", message);
+            }
+        }
+
+        for (i, (&s, item)) in k.prefix_items.iter().zip(a).enumerate() {
+            self.at(i.to_string(), s, item);
+            evaluated.items.insert(i);
+        }
+        if let Some(s) = k.items {
+            for (i, item) in a.iter().enumerate().skip(k.prefix_items.len()) {
+                self.at(i.to_string(), s, item);
+                evaluated.items.insert(i);
+            }
+        }
+        if let Some(s) = k.contains {
+            let matched: Vec<_> = (0..a.len())
+                .filter(|&i| self.probe(s, &a[i]).is_some())
+                .collect();
+            let min = k.min_contains.unwrap_or(1);
+            if matched.len() < min {
+                let keyword = if k.min_contains.is_some() {
+                    "minContains"
+                } else {
+                    "contains"
+                };
+                let message = format!(
+                    "must contain at least {min} matching item(s), found {}",
+                    matched.len()
+                );
+                self.error(loc, keyword, message);
+            }
+            if let Some(max) = k.max_contains.filter(|&max| matched.len() > max) {
+                let message = format!("must contain at most {max} matching item(s)");
+                self.error(loc, "maxContains", message);
+            }
+            evaluated.items.extend(matched);
+        }
+        if let Some(s) = k.unevaluated_items {
+            for (i, item) in a.iter().enumerate() {
+                if !evaluated.items.contains(&i) {
+                    self.at(i.to_string(), s, item);
+                }
+            }
+            evaluated.items.extend(0..a.len());
+        }
+    }
+
+    fn object(&mut self, k: &Keywords, instance: &Json, o: &Object, evaluated: &mut Evaluated) {
+        let loc = k.location.as_str();
+
+        if let Some(max) = k.max_properties.filter(|&max| o.len() > max) {
+            self.error(
+                loc,
+                "maxProperties",
+                format!("must have at most {max} properties"),
+            );
+        }
+        if let Some(min) = k.min_properties.filter(|&min| o.len() < min) {
+            self.error(
+                loc,
+                "minProperties",
+                format!("must have at least {min} properties"),
+            );
+        }
+        for name in k.required.iter().filter(|r| !o.contains_key(*r)) {
+            self.error(
+                loc,
+                "required",
+                format!("missing required property \"{name}\""),
+            );
+        }
+        for (name, required) in k
+            .dependent_required
+            .iter()
+            .filter(|(n, _)| o.contains_key(*n))
+        {
+            for missing in required.iter().filter(|r| !o.contains_key(*r)) {
+                let message =
+                    format!("property \"{missing}\" is required when \"{name}\" is present");
+                self.error(loc, "dependentRequired", message);
+            }
+        }
+        for (name, &s) in &k.dependent_schemas {
+            if o.contains_key(name) {
+                evaluated.merge(self.validate(s, instance));
+            }
+        }
+        if let Some(s) = k.property_names {
+            for name in o.keys() {
+                self.validate(s, &Json::String(name.clone()));
+            }
+        }
+
+        for (name, value) in o {
+            let mut matched = false;
+            if let Some(&s) = k.properties.get(name) {
+                self.at(name.clone(), s, value);
+                matched = true;
+            }
+            for (re, s) in &k.pattern_properties {
+                if re.is_match(name) {
+                    self.at(name.clone(), *s, value);
+                    matched = true;
+                }
+            }
+
+            if matched {
+                evaluated.properties.insert(name.clone());
+            } else if let Some(s) = k.additional_properties {
+                self.at(name.clone(), s, value);
+                evaluated.properties.insert(name.clone());
+            }
+        }
+
+        if let Some(s) = k.unevaluated_properties {
+            for (name, value) in o {
+                if !evaluated.properties.contains(name) {
+                    self.at(name.clone(), s, value);
+                }
+            }
+            evaluated.properties.extend(o.keys().cloned());
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+/// Strings with at most this many distinct values may become an `enum`.
+const MAX_ENUM: usize = 8;
+
+/// Infer a schema that every sample satisfies.
+///
+/// Members present in every sample object are `required`, numbers get a
+/// `minimum`/`maximum` and are `integer` when they have no fractional part,
+/// and strings get a `format` (`date-time`, `uuid` or `email`) when every
+/// value has one, or an `enum` when only a few distinct values recur.
+pub fn infer<'a>(samples: impl IntoIterator<Item = &'a Json>) -> Json {
+    let mut shape = Shape::default();
+    for sample in samples {
+        shape.observe(sample);
+    }
+
+    let mut schema = match shape.to_schema() {
+        Json::Object(o) => o,
+        _ => BTreeMap::new(),
+    };
+    schema.insert(
+        "$schema".to_string(),
+        Json::from("https://json-schema.org/draft/2020-12/schema"),
+    );
+    Json::Object(schema)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    DateTime,
+    Uuid,
+    Email,
+}
+
+impl Format {
+    fn detect(s: &str) -> Option<Self> {
+        if is_date_time(s) {
+            Some(Format::DateTime)
+        } else if is_uuid(s) {
+            Some(Format::Uuid)
+        } else if is_email(s) {
+            Some(Format::Email)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Format::DateTime => "date-time",
+            Format::Uuid => "uuid",
+            Format::Email => "email",
+        }
+    }
+}
+
+fn digits(s: &str, n: usize) -> Option<(u32, &str)> {
+    let head = s.get(..n)?;
+    if !head.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((head.parse().ok()?, &s[n..]))
+}
+
+fn expect<'a>(s: &'a str, c: &[char]) -> Option<&'a str> {
+    s.strip_prefix(|x| c.contains(&x))
+}
+
+/// RFC 3339 `date-time`, e.g. `2024-02-29T12:00:00.5+01:00`.
+fn is_date_time(s: &str) -> bool {
+    let parse = || {
+        let (_, s) = digits(s, 4)?;
+        let (month, s) = digits(expect(s, &['-'])?, 2)?;
+        let (day, s) = digits(expect(s, &['-'])?, 2)?;
+        let (hour, s) = digits(expect(s, &['T', 't'])?, 2)?;
+        let (minute, s) = digits(expect(s, &[':'])?, 2)?;
+        let (second, mut s) = digits(expect(s, &[':'])?, 2)?;
+
+        if let Some(frac) = s.strip_prefix('.') {
+            let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+            if len == 0 {
+                return None;
+            }
+            s = &frac[len..];
+        }
+
+        let offset_ok = match expect(s, &['+', '-']) {
+            Some(rest) => {
+                let (h, rest) = digits(rest, 2)?;
+                let (m, rest) = digits(expect(rest, &[':'])?, 2)?;
+                h < 24 && m < 60 && rest.is_empty()
+            }
+            None => s == "Z" || s == "z",
+        };
+
+        Some(
+            offset_ok
+                && (1..=12).contains(&month)
+                && (1..=31).contains(&day)
+                && hour < 24
+                && minute < 60
+                && second <= 60,
+        )
+    };
+    parse() == Some(true)
+}
+
+/// A hyphenated UUID, e.g. `123e4567-e89b-12d3-a456-426614174000`.
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<_> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip(&[8, 4, 4, 4, 12])
+            .all(|(g, &n)| g.len() == n && g.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !s.contains(char::is_whitespace)
+        && !domain.contains('@')
+        && domain.contains('.')
+        && !domain.starts_with('.')
+        && !domain.ends_with('.')
+}
+
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    numbers: Option<Numbers>,
+    strings: Option<Strings>,
+    arrays: Option<Arrays>,
+    objects: Option<Objects>,
+}
+
+#[derive(Debug)]
+struct Numbers {
+    min: f64,
+    max: f64,
+    integer: bool,
+}
+
+#[derive(Debug)]
+struct Strings {
+    count: usize,
+    /// Distinct values seen, until there are too many for an `enum`.
+    values: Option<BTreeSet<String>>,
+    format: Option<Format>,
+}
+
+#[derive(Debug)]
+struct Arrays {
+    min: usize,
+    max: usize,
+    items: Box<Shape>,
+}
+
+#[derive(Debug)]
+struct Objects {
+    count: usize,
+    /// Each member's shape, and how many objects had it.
+    properties: BTreeMap<String, (usize, Shape)>,
+}
+
+impl Shape {
+    fn observe(&mut self, value: &Json) {
+        match value {
+            Json::Null => self.null = true,
+            Json::Boolean(_) => self.boolean = true,
+            Json::Number(n) => {
+                let numbers = self.numbers.get_or_insert(Numbers {
+                    min: *n,
+                    max: *n,
+                    integer: true,
+                });
+                numbers.min = numbers.min.min(*n);
+                numbers.max = numbers.max.max(*n);
+                numbers.integer &= n.fract() == 0.;
+            }
+            Json::String(s) => {
+                let format = Format::detect(s);
+                let strings = self.strings.get_or_insert(Strings {
+                    count: 0,
+                    values: Some(BTreeSet::new()),
+                    format,
+                });
+                strings.count += 1;
+                if strings.format != format {
+                    strings.format = None;
+                }
+                if let Some(values) = &mut strings.values {
+                    values.insert(s.clone());
+                    if values.len() > MAX_ENUM {
+                        strings.values = None;
+                    }
+                }
+            }
+            Json::Array(a) => {
+                let arrays = self.arrays.get_or_insert_with(|| Arrays {
+                    min: a.len(),
+                    max: a.len(),
+                    items: Box::default(),
+                });
+                arrays.min = arrays.min.min(a.len());
+                arrays.max = arrays.max.max(a.len());
+                for item in a {
+                    arrays.items.observe(item);
+                }
+            }
+            Json::Object(o) => {
+                let objects = self.objects.get_or_insert_with(|| Objects {
+                    count: 0,
+                    properties: BTreeMap::new(),
+                });
+                objects.count += 1;
+                for (key, member) in o {
+                    let (seen, shape) = objects.properties.entry(key.clone()).or_default();
+                    *seen += 1;
+                    shape.observe(member);
+                }
+            }
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.null
+            && !self.boolean
+            && self.numbers.is_none()
+            && self.strings.is_none()
+            && self.arrays.is_none()
+            && self.objects.is_none()
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn to_schema(&self) -> Json {
+        if self.is_empty() {
+            return Json::Boolean(true);
+        }
+
+        let mut types = Vec::new();
+        let mut schema = BTreeMap::new();
+        let mut set = |key: &str, value: Json| {
+            schema.insert(key.to_string(), value);
+        };
+
+        if self.null {
+            types.push("null");
+        }
+        if self.boolean {
+            types.push("boolean");
+        }
+        if let Some(n) = &self.numbers {
+            types.push(if n.integer { "integer" } else { "number" });
+            set("minimum", Json::Number(n.min));
+            set("maximum", Json::Number(n.max));
+        }
+        if let Some(s) = &self.strings {
+            types.push("string");
+            match (&s.format, &s.values) {
+                (Some(format), _) => set("format", Json::from(format.name())),
+                (None, Some(values)) if s.count >= 2 * values.len() => {
+                    set("enum", values.iter().map(String::as_str).collect());
+                }
+                _ => {}
+            }
+        }
+        if let Some(a) = &self.arrays {
+            types.push("array");
+            if !a.items.is_empty() {
+                set("items", a.items.to_schema());
+            }
+            set("minItems", Json::Number(a.min as f64));
+            set("maxItems", Json::Number(a.max as f64));
+        }
+        if let Some(o) = &self.objects {
+            types.push("object");
+            let properties = o
+                .properties
+                .iter()
+                .map(|(k, (_, shape))| (k.as_str(), shape.to_schema()))
+                .collect();
+            let required: Json = o
+                .properties
+                .iter()
+                .filter(|(_, (seen, _))| *seen == o.count)
+                .map(|(k, _)| k.as_str())
+                .collect();
+            set("properties", properties);
+            if required.as_array().is_some_and(|r| !r.is_empty()) {
+                set("required", required);
+            }
+        }
+
+        let types = match types.as_slice() {
+            [single] => Json::from(*single),
+            _ => types.into_iter().collect(),
+        };
+        schema.insert("type".to_string(), types);
+        Json::Object(schema)
+    }
+}
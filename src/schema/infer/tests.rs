@@ -0,0 +1,80 @@
+#![cfg(test)]
+
+use super::*;
+use crate::schema::Schema;
+
+fn without_meta(mut schema: Json) -> Json {
+    schema.as_object_mut().unwrap().remove("$schema");
+    schema
+}
+
+#[test]
+fn scalars() {
+    assert_eq!(
+        without_meta(infer(&[json!(1), json!(5)])),
+        json!({"type": "integer", "minimum": 1, "maximum": 5})
+    );
+    assert_eq!(
+        without_meta(infer(&[json!(1), json!(2.5), json!(null)])),
+        json!({"type": ["null", "number"], "minimum": 1, "maximum": 2.5})
+    );
+    assert_eq!(without_meta(infer(&[])), json!({}));
+    assert_eq!(
+        infer(&[json!(true)])["$schema"],
+        json!("https://json-schema.org/draft/2020-12/schema")
+    );
+}
+
+#[test]
+fn strings() {
+    let colors = [json!("red"), json!("blue"), json!("red"), json!("blue")];
+    assert_eq!(
+        without_meta(infer(&colors)),
+        json!({"type": "string", "enum": ["blue", "red"]})
+    );
+    assert_eq!(
+        without_meta(infer(&[json!("a"), json!("b")])),
+        json!({"type": "string"})
+    );
+    assert_eq!(
+        without_meta(infer(&[
+            json!("2024-02-29T12:00:00Z"),
+            json!("2024-03-01t00:00:00.125+05:30")
+        ]))["format"],
+        json!("date-time")
+    );
+    assert_eq!(
+        infer(&[json!("123e4567-e89b-12d3-a456-426614174000")])["format"],
+        json!("uuid")
+    );
+    assert_eq!(infer(&[json!("ops@example.com")])["format"], json!("email"));
+    assert_eq!(
+        infer(&[json!("ops@example.com"), json!("2024-02-29T12:00:00Z")])["format"],
+        json!(null)
+    );
+    assert_eq!(
+        infer(&[json!("2024-13-01T00:00:00Z")])["format"],
+        json!(null)
+    );
+}
+
+#[test]
+fn objects() {
+    let samples = [
+        json!({"id": 1, "name": "a", "tags": ["x"]}),
+        json!({"id": 2, "tags": [], "extra": null}),
+    ];
+    let schema = infer(&samples);
+
+    assert_eq!(schema["type"], json!("object"));
+    assert_eq!(schema["required"], json!(["id", "tags"]));
+    assert_eq!(
+        schema["properties"]["tags"],
+        json!({"type": "array", "items": {"type": "string"}, "minItems": 0, "maxItems": 1})
+    );
+    assert_eq!(schema["properties"]["extra"], json!({"type": "null"}));
+
+    let compiled = Schema::compile(&schema).unwrap();
+    assert!(samples.iter().all(|s| compiled.is_valid(s)));
+    assert!(!compiled.is_valid(&json!({"id": 1.5, "tags": []})));
+}
@@ -0,0 +1,302 @@
+#![cfg(test)]
+
+use super::*;
+
+fn schema(s: &Json) -> Schema {
+    Schema::compile(s).unwrap()
+}
+
+fn failures(s: &Json, instance: &Json) -> Vec<(String, String)> {
+    match schema(s).validate(instance) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .into_iter()
+            .map(|e| (e.instance_path, e.schema_path))
+            .collect(),
+    }
+}
+
+#[test]
+fn types_and_values() {
+    let s = schema(&json!({"type": ["integer", "null"]}));
+    assert!(s.is_valid(&json!(1)));
+    assert!(s.is_valid(&json!(1.0)));
+    assert!(s.is_valid(&json!(null)));
+    assert!(!s.is_valid(&json!(1.5)));
+    assert!(!s.is_valid(&json!("1")));
+
+    assert!(schema(&json!({"type": "number"})).is_valid(&json!(3)));
+    assert!(schema(&json!(true)).is_valid(&json!({})));
+    assert!(!schema(&json!(false)).is_valid(&json!({})));
+
+    let s = schema(&json!({"enum": [1, "a", [true]], "const": [true]}));
+    assert!(s.is_valid(&json!([true])));
+    assert!(!s.is_valid(&json!(1)));
+    assert!(!s.is_valid(&json!("b")));
+}
+
+#[test]
+fn bounds() {
+    let s = schema(&json!({
+        "minimum": 1, "exclusiveMaximum": 10, "multipleOf": 0.5,
+        "minLength": 2, "maxLength": 3, "pattern": "^[a-z]+$",
+        "minItems": 1, "maxItems": 2, "uniqueItems": true,
+        "minProperties": 1, "maxProperties": 1
+    }));
+
+    assert!(s.is_valid(&json!(9.5)));
+    assert!(!s.is_valid(&json!(10)));
+    assert!(!s.is_valid(&json!(0.5)));
+    assert!(!s.is_valid(&json!(1.25)));
+    assert!(schema(&json!({"multipleOf": 0.1})).is_valid(&json!(0.3)));
+
+    assert!(s.is_valid(&json!("abc")));
+    assert!(!s.is_valid(&json!("a")));
+    assert!(!s.is_valid(&json!("abcd")));
+    assert!(!s.is_valid(&json!("ab1")));
+    assert!(schema(&json!({"maxLength": 2})).is_valid(&json!("\u{e9}\u{e9}")));
+
+    assert!(s.is_valid(&json!([1, 2])));
+    assert!(!s.is_valid(&json!([])));
+    assert!(!s.is_valid(&json!([1, 1])));
+    assert!(!s.is_valid(&json!([1, 2, 3])));
+
+    assert!(s.is_valid(&json!({"a": 1})));
+    assert!(!s.is_valid(&json!({})));
+
+    // Long enough to overflow the stack matching by recursion.
+    let long = "a".repeat(100_000);
+    assert!(schema(&json!({"pattern": "^a*$"})).is_valid(&json!(long.clone())));
+    let s = schema(&json!({"patternProperties": {"^a*$": false}}));
+    assert!(!s.is_valid(&Json::Object(iter::once((long, json!(1))).collect())));
+}
+
+#[test]
+fn objects() {
+    let s = json!({
+        "properties": {"name": {"type": "string"}},
+        "patternProperties": {"^x-": {"type": "integer"}},
+        "additionalProperties": false,
+        "required": ["name"],
+        "dependentRequired": {"x-a": ["x-b"]},
+        "propertyNames": {"maxLength": 4}
+    });
+
+    assert!(failures(&s, &json!({"name": "n", "x-b": 1})).is_empty());
+    assert_eq!(
+        failures(&s, &json!({"name": 1, "x-a": "1", "other": 2})),
+        vec![
+            (String::new(), "/dependentRequired".to_string()),
+            (String::new(), "/propertyNames/maxLength".to_string()),
+            ("/name".to_string(), "/properties/name/type".to_string()),
+            ("/other".to_string(), "/additionalProperties".to_string()),
+            (
+                "/x-a".to_string(),
+                "/patternProperties/^x-/type".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        failures(&s, &json!({})),
+        vec![(String::new(), "/required".to_string())]
+    );
+}
+
+#[test]
+fn arrays() {
+    let s = json!({
+        "prefixItems": [{"type": "string"}],
+        "items": {"type": "integer"},
+        "contains": {"type": "integer", "minimum": 10},
+        "maxContains": 1
+    });
+
+    assert!(failures(&s, &json!(["a", 1, 10])).is_empty());
+    assert_eq!(
+        failures(&s, &json!([1, "a"])),
+        vec![
+            ("/0".to_string(), "/prefixItems/0/type".to_string()),
+            ("/1".to_string(), "/items/type".to_string()),
+            (String::new(), "/contains".to_string()),
+        ]
+    );
+    assert_eq!(
+        failures(&s, &json!(["a", 10, 11])),
+        vec![(String::new(), "/maxContains".to_string())]
+    );
+    assert!(schema(&json!({"contains": false, "minContains": 0})).is_valid(&json!([1])));
+}
+
+#[test]
+fn combinators() {
+    let s = schema(&json!({
+        "anyOf": [{"type": "string"}, {"type": "number"}],
+        "oneOf": [{"minimum": 0}, {"maximum": 10}],
+        "not": {"const": 20}
+    }));
+    assert!(s.is_valid(&json!(-1)));
+    assert!(s.is_valid(&json!(11)));
+    assert!(!s.is_valid(&json!(5)));
+    assert!(!s.is_valid(&json!(20)));
+    assert!(!s.is_valid(&json!("y")));
+    assert!(!s.is_valid(&json!(null)));
+
+    let s = schema(&json!({
+        "if": {"properties": {"kind": {"const": "a"}}},
+        "then": {"required": ["a"]},
+        "else": {"required": ["b"]}
+    }));
+    assert!(s.is_valid(&json!({"kind": "a", "a": 1})));
+    assert!(!s.is_valid(&json!({"kind": "a", "b": 1})));
+    assert!(s.is_valid(&json!({"kind": "b", "b": 1})));
+}
+
+#[test]
+fn references() {
+    let s = json!({
+        "$defs": {
+            "positive": {"type": "integer", "exclusiveMinimum": 0},
+            "node": {
+                "$anchor": "node",
+                "type": "object",
+                "properties": {"children": {"items": {"$ref": "#node"}}}
+            }
+        },
+        "properties": {
+            "count": {"$ref": "#/$defs/positive"},
+            "tree": {"$ref": "#/$defs/node", "required": ["children"]}
+        }
+    });
+
+    let tree = json!({"count": 1, "tree": {"children": [{"children": []}]}});
+    assert!(failures(&s, &tree).is_empty());
+    assert_eq!(
+        failures(
+            &s,
+            &json!({"count": 0, "tree": {"children": [{"children": [5]}]}})
+        ),
+        vec![
+            (
+                "/count".to_string(),
+                "/$defs/positive/exclusiveMinimum".to_string()
+            ),
+            (
+                "/tree/children/0/children/0".to_string(),
+                "/$defs/node/type".to_string()
+            ),
+        ]
+    );
+    assert_eq!(
+        failures(&s, &json!({"tree": {}})),
+        vec![("/tree".to_string(), "/properties/tree/required".to_string())]
+    );
+}
+
+#[test]
+fn embedded_resources() {
+    let s = schema(&json!({
+        "$id": "https://example.com/root.json",
+        "items": {"$ref": "item.json"},
+        "$defs": {
+            "item": {
+                "$id": "item.json",
+                "properties": {"id": {"$ref": "#/$defs/id"}},
+                "$defs": {"id": {"type": "integer"}}
+            }
+        }
+    }));
+    assert!(s.is_valid(&json!([{"id": 1}])));
+    assert!(!s.is_valid(&json!([{"id": "1"}])));
+
+    let remote = Schema::compile(&json!({"$ref": "https://example.com/other.json"}));
+    assert!(matches!(remote, Err(SchemaError::UnresolvedRef(_))));
+}
+
+#[test]
+fn file_references() {
+    let dir = std::env::temp_dir().join(format!("jval-schema-{}", std::process::id()));
+    fs::create_dir_all(dir.join("defs")).unwrap();
+    fs::write(
+        dir.join("root.json"),
+        r#"{"properties": {"port": {"$ref": "defs/port.json"}}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("defs/port.json"),
+        r#"{"$ref": "../common.json#/$defs/port"}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.join("common.json"),
+        r#"{"$defs": {"port": {"type": "integer", "maximum": 65535}}}"#,
+    )
+    .unwrap();
+
+    let s = Schema::from_file(dir.join("root.json")).unwrap();
+    assert!(s.is_valid(&json!({"port": 80})));
+
+    let errors = s.validate(&json!({"port": 65536})).unwrap_err();
+    assert_eq!(errors[0].instance_path, "/port");
+    assert!(errors[0]
+        .schema_path
+        .ends_with("common.json#/$defs/port/maximum"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unevaluated() {
+    let s = schema(&json!({
+        "properties": {"a": true},
+        "allOf": [{"properties": {"b": true}}],
+        "anyOf": [{"properties": {"c": true}, "required": ["c"]}, true],
+        "if": {"properties": {"d": {"const": 1}}},
+        "unevaluatedProperties": false
+    }));
+    assert!(s.is_valid(&json!({"a": 1, "b": 1, "c": 1, "d": 1})));
+    assert!(!s.is_valid(&json!({"d": 2})));
+    assert!(!s.is_valid(&json!({"e": 1})));
+
+    let s = schema(&json!({
+        "prefixItems": [true],
+        "contains": {"type": "string"},
+        "unevaluatedItems": {"type": "null"}
+    }));
+    assert!(s.is_valid(&json!([1, "a", null])));
+    assert!(!s.is_valid(&json!([1, "a", 2])));
+
+    let s = schema(&json!({
+        "$ref": "#/$defs/base",
+        "$defs": {"base": {"properties": {"x": true}}},
+        "unevaluatedProperties": false
+    }));
+    assert!(s.is_valid(&json!({"x": 1})));
+    assert!(!s.is_valid(&json!({"y": 1})));
+}
+
+#[test]
+fn invalid_schemas() {
+    for bad in &[
+        json!(1),
+        json!({"type": "thing"}),
+        json!({"minimum": "1"}),
+        json!({"minLength": -1}),
+        json!({"required": [1]}),
+        json!({"pattern": "(?=x)"}),
+        json!({"properties": {"a": 1}}),
+        json!({"$ref": "#/$defs/missing"}),
+        json!({"$ref": "#nowhere"}),
+        json!({"$ref": "#"}),
+        json!({
+            "$defs": {"a": {"allOf": [{"$ref": "#/$defs/b"}]}, "b": {"$ref": "#/$defs/a"}},
+            "$ref": "#/$defs/a"
+        }),
+        json!({"anyOf": [{"type": "string"}, {"not": {"$dynamicRef": "#"}}]}),
+    ] {
+        assert!(
+            Schema::compile(bad).is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
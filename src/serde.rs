@@ -0,0 +1,865 @@
+//! `serde` support, behind the `serde` feature: `Serialize` and
+//! `Deserialize` for [`Json`], and conversions between Rust values and JSON
+//! text or [`Json`] trees.
+//!
+//! ```
+//! # use serde::Deserialize;
+//! #[derive(Debug, Deserialize)]
+//! struct Server {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! let servers: Vec<Server> = jval::from_str(r#"[{"host": "a", "port": 80}]"#).unwrap();
+//! assert_eq!(servers[0].port, 80);
+//!
+//! let e = jval::from_str::<Vec<Server>>(r#"[{"host": "a", "port": -1}]"#).unwrap_err();
+//! assert_eq!(e.path, "/0/port");
+//! assert_eq!(e.span, Some(23..25));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Unexpected, Visitor};
+use serde::ser::{self, Serialize};
+
+use super::parse::parse_tokens;
+use super::{pointer, Json, ParseOptions, Token, TokenRecord};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerdeError {
+    pub message: String,
+    /// Byte range of the offending value in the input text, when there was
+    /// input text.
+    pub span: Option<Range<usize>>,
+    /// JSON Pointer to the offending value.
+    pub path: String,
+    /// Whether `span` and `path` have been filled in yet.
+    located: bool,
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at \"{}\"", self.message, self.path)?;
+        if let Some(Range { start, end }) = &self.span {
+            write!(f, " (bytes {start}..{end})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeError {
+            message: msg.to_string(),
+            span: None,
+            path: String::new(),
+            located: false,
+        }
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        <Self as ser::Error>::custom(msg)
+    }
+}
+
+/// Deserialize a `T` from JSON text.
+///
+/// # Errors
+///
+/// Fails if `s` isn't valid JSON or doesn't have the shape of a `T`. The
+/// error has the byte range and the path of the offending value.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, SerdeError> {
+    let (json, tokens) = parse_tokens(s, &ParseOptions::default()).map_err(|errors| {
+        let (kind, span) = errors
+            .into_iter()
+            .next()
+            .map_or((None, 0..s.len()), |(kind, span)| (Some(kind), span));
+        SerdeError {
+            message: format!("invalid JSON: {kind:?}"),
+            span: Some(span),
+            path: String::new(),
+            located: true,
+        }
+    })?;
+
+    let spans = Spans::build(&mut tokens.iter().peekable(), s.len());
+
+    T::deserialize(Deserializer {
+        value: &json,
+        spans: spans.as_ref(),
+        path: String::new(),
+    })
+}
+
+/// Deserialize a `T` from a [`Json`] tree.
+///
+/// # Errors
+///
+/// Fails if `value` doesn't have the shape of a `T`. The error has the path
+/// of the offending value.
+pub fn from_value<T: DeserializeOwned>(value: &Json) -> Result<T, SerdeError> {
+    T::deserialize(Deserializer {
+        value,
+        spans: None,
+        path: String::new(),
+    })
+}
+
+/// Serialize `value` into a [`Json`] tree.
+///
+/// # Errors
+///
+/// Fails if `value`'s `Serialize` implementation does, or if it has map keys
+/// other than strings, numbers and booleans.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Json, SerdeError> {
+    value.serialize(Serializer)
+}
+
+/// Serialize `value` as compact JSON text.
+///
+/// # Errors
+///
+/// Fails where [`to_value`] does.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, SerdeError> {
+    Ok(to_value(value)?.to_string())
+}
+
+impl Serialize for Json {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Json::Null => serializer.serialize_unit(),
+            Json::Boolean(b) => serializer.serialize_bool(*b),
+            Json::Number(n) => serializer.serialize_f64(*n),
+            Json::String(s) => serializer.serialize_str(s),
+            Json::Array(a) => serializer.collect_seq(a),
+            Json::Object(o) => serializer.collect_map(o),
+        }
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Json {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+struct JsonVisitor;
+
+impl<'de> Visitor<'de> for JsonVisitor {
+    type Value = Json;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Json, E> {
+        Ok(Json::Boolean(b))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_i64<E>(self, n: i64) -> Result<Json, E> {
+        Ok(Json::Number(n as f64))
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn visit_u64<E>(self, n: u64) -> Result<Json, E> {
+        Ok(Json::Number(n as f64))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<Json, E> {
+        Ok(Json::Number(n))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Json, E> {
+        Ok(Json::String(s.to_string()))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Json, E> {
+        Ok(Json::String(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Json, E> {
+        Ok(Json::Null)
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Json, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Json, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Json::Array(items))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Json, A::Error> {
+        let mut members = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            members.insert(key, value);
+        }
+        Ok(Json::Object(members))
+    }
+}
+
+/// Serializes into a [`Json`] tree.
+struct Serializer;
+
+/// Wrap `value` as `{variant: value}`, if it is the content of an enum variant.
+fn wrap(variant: Option<&str>, value: Json) -> Json {
+    match variant {
+        Some(variant) => Json::Object(Some((variant.to_string(), value)).into_iter().collect()),
+        None => value,
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl ser::Serializer for Serializer {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Json, SerdeError> {
+        Ok(Json::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v as f64))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v.into()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Json, SerdeError> {
+        Ok(Json::Number(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Json, SerdeError> {
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Json, SerdeError> {
+        Ok(Json::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Json, SerdeError> {
+        Ok(Json::Array(
+            v.iter().map(|b| Json::Number((*b).into())).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Json, SerdeError> {
+        Ok(Json::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Json, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Json, SerdeError> {
+        Ok(Json::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Json, SerdeError> {
+        Ok(Json::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Json, SerdeError> {
+        Ok(Json::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Json, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Json, SerdeError> {
+        Ok(wrap(Some(variant), value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: None,
+            members: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: Some(variant),
+            members: BTreeMap::new(),
+            key: None,
+        })
+    }
+}
+
+struct SerializeArray {
+    variant: Option<&'static str>,
+    items: Vec<Json>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        Ok(wrap(self.variant, Json::Array(self.items)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeObject {
+    variant: Option<&'static str>,
+    members: BTreeMap<String, Json>,
+    /// The key of the member whose value is serialized next.
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(match key.serialize(Serializer)? {
+            Json::String(s) => s,
+            key @ (Json::Number(_) | Json::Boolean(_)) => key.to_string(),
+            _ => {
+                return Err(ser::Error::custom(
+                    "object keys must be strings, numbers or booleans",
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self.key.take().unwrap_or_default();
+        self.members.insert(key, value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        Ok(wrap(self.variant, Json::Object(self.members)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.members
+            .insert(key.to_string(), value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Json;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Json, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Byte ranges of the values in a parsed document, shaped like the document.
+enum Spans {
+    Scalar(Range<usize>),
+    Array(Range<usize>, Vec<Spans>),
+    Object(Range<usize>, BTreeMap<String, Spans>),
+}
+
+impl Spans {
+    /// Read the spans of the next value from the tokens of valid JSON text
+    /// `len` bytes long.
+    fn build<'a, I>(tokens: &mut std::iter::Peekable<I>, len: usize) -> Option<Self>
+    where
+        I: Iterator<Item = &'a TokenRecord>,
+    {
+        let range = |(_, l, rest): &TokenRecord| len - rest..len - rest + l;
+        let first = tokens.next()?;
+        let start = range(first).start;
+
+        match &first.0 {
+            Token::OpenSquare => {
+                let mut items = Vec::new();
+                loop {
+                    if let Some(close @ (Token::CloseSquare, _, _)) = tokens.peek() {
+                        let end = range(close).end;
+                        tokens.next();
+                        return Some(Spans::Array(start..end, items));
+                    }
+                    items.push(Self::build(tokens, len)?);
+                    if let Some((Token::Comma, _, _)) = tokens.peek() {
+                        tokens.next();
+                    }
+                }
+            }
+            Token::OpenCurly => {
+                let mut members = BTreeMap::new();
+                loop {
+                    match tokens.next()? {
+                        close @ (Token::CloseCurly, _, _) => {
+                            return Some(Spans::Object(start..range(close).end, members));
+                        }
+                        (Token::StringLiteral(key), _, _) => {
+                            tokens.next()?;
+                            members.insert(key.clone(), Self::build(tokens, len)?);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => Some(Spans::Scalar(range(first))),
+        }
+    }
+
+    fn range(&self) -> Range<usize> {
+        match self {
+            Spans::Scalar(r) | Spans::Array(r, _) | Spans::Object(r, _) => r.clone(),
+        }
+    }
+}
+
+/// Deserializes from a [`Json`] tree, keeping track of where in the tree,
+/// and in the text it came from, each value is.
+struct Deserializer<'a> {
+    value: &'a Json,
+    spans: Option<&'a Spans>,
+    path: String,
+}
+
+impl<'a> Deserializer<'a> {
+    /// Fill in the location of an error that hasn't got one yet.
+    fn locate(&self, mut e: SerdeError) -> SerdeError {
+        if !e.located {
+            e.located = true;
+            e.path.clone_from(&self.path);
+            e.span = self.spans.map(Spans::range);
+        }
+        e
+    }
+
+    fn child(&self, token: &str, value: &'a Json, spans: Option<&'a Spans>) -> Self {
+        Deserializer {
+            value,
+            spans,
+            path: format!("{}/{}", self.path, pointer::escape(token)),
+        }
+    }
+
+    fn unexpected(&self) -> Unexpected<'a> {
+        match self.value {
+            Json::Null => Unexpected::Unit,
+            Json::Boolean(b) => Unexpected::Bool(*b),
+            Json::Number(n) => Unexpected::Float(*n),
+            Json::String(s) => Unexpected::Str(s),
+            Json::Array(_) => Unexpected::Seq,
+            Json::Object(_) => Unexpected::Map,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = SerdeError;
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        let result = match self.value {
+            Json::Null => visitor.visit_unit(),
+            Json::Boolean(b) => visitor.visit_bool(*b),
+            Json::Number(n) if n.fract() == 0. && *n >= 0. && *n < 18_446_744_073_709_551_616. => {
+                visitor.visit_u64(*n as u64)
+            }
+            Json::Number(n) if n.fract() == 0. && *n >= -9_223_372_036_854_775_808. && *n < 0. => {
+                visitor.visit_i64(*n as i64)
+            }
+            Json::Number(n) => visitor.visit_f64(*n),
+            Json::String(s) => visitor.visit_borrowed_str(s),
+            Json::Array(a) => {
+                let mut seq = SeqAccess {
+                    de: &self,
+                    items: a.iter().enumerate(),
+                };
+                match visitor.visit_seq(&mut seq) {
+                    Ok(_) if seq.items.len() > 0 => Err(de::Error::invalid_length(
+                        a.len(),
+                        &"fewer elements in array",
+                    )),
+                    result => result,
+                }
+            }
+            Json::Object(o) => visitor.visit_map(MapAccess {
+                de: &self,
+                members: o.iter(),
+                value: None,
+            }),
+        };
+        result.map_err(|e| self.locate(e))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Json::Null => visitor.visit_none().map_err(|e| self.locate(e)),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        let single = self
+            .value
+            .as_object()
+            .filter(|o| o.len() == 1)
+            .and_then(|o| o.iter().next());
+        let result = match (self.value, single) {
+            (Json::String(s), _) => visitor.visit_enum(s.as_str().into_deserializer()),
+            (_, Some((variant, value))) => {
+                let spans = match self.spans {
+                    Some(Spans::Object(_, members)) => members.get(variant),
+                    _ => None,
+                };
+                visitor.visit_enum(Enum {
+                    variant,
+                    de: self.child(variant, value, spans),
+                })
+            }
+            _ => Err(de::Error::invalid_type(
+                self.unexpected(),
+                &"a string or an object with one member",
+            )),
+        };
+        result.map_err(|e| self.locate(e))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a Deserializer<'de>,
+    items: std::iter::Enumerate<std::slice::Iter<'de, Json>>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'_, 'de> {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        let Some((i, item)) = self.items.next() else {
+            return Ok(None);
+        };
+        let spans = match self.de.spans {
+            Some(Spans::Array(_, items)) => items.get(i),
+            _ => None,
+        };
+        seed.deserialize(self.de.child(&i.to_string(), item, spans))
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapAccess<'a, 'de> {
+    de: &'a Deserializer<'de>,
+    members: std::collections::btree_map::Iter<'de, String, Json>,
+    /// The member whose key was just deserialized.
+    value: Option<Deserializer<'de>>,
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'_, 'de> {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        let Some((key, value)) = self.members.next() else {
+            return Ok(None);
+        };
+        let spans = match self.de.spans {
+            Some(Spans::Object(_, members)) => members.get(key),
+            _ => None,
+        };
+        let value = self.de.child(key, value, spans);
+        let key = seed
+            .deserialize(de::value::BorrowedStrDeserializer::new(key))
+            .map_err(|e| value.locate(e))?;
+        self.value = Some(value);
+        Ok(Some(key))
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.members.len())
+    }
+}
+
+/// An enum variant written as `{"Variant": content}`.
+struct Enum<'de> {
+    variant: &'de str,
+    de: Deserializer<'de>,
+}
+
+impl<'de> de::EnumAccess<'de> for Enum<'de> {
+    type Error = SerdeError;
+    type Variant = Deserializer<'de>;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Deserializer<'de>), SerdeError> {
+        let variant = seed
+            .deserialize(de::value::BorrowedStrDeserializer::new(self.variant))
+            .map_err(|e| self.de.locate(e))?;
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Deserializer<'de> {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            Json::Null => Ok(()),
+            _ => Err(self.locate(de::Error::invalid_type(
+                self.unexpected(),
+                &"null for a unit variant",
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Config {
+    name: String,
+    port: u16,
+    #[serde(default)]
+    tags: Vec<String>,
+    limits: Option<Limits>,
+    mode: Mode,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Limits {
+    ratio: f64,
+    depth: i32,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Fast,
+    Pinned(u8),
+    Range(u8, u8),
+    Custom { level: u8 },
+}
+
+#[test]
+fn round_trip() {
+    let config = Config {
+        name: "svc \"a\"\n".to_string(),
+        port: 8080,
+        tags: vec!["x".to_string()],
+        limits: Some(Limits {
+            ratio: 0.5,
+            depth: -3,
+        }),
+        mode: Mode::Custom { level: 2 },
+    };
+
+    let value = to_value(&config).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "name": "svc \"a\"\n", "port": 8080, "tags": ["x"],
+            "limits": {"ratio": 0.5, "depth": -3}, "mode": {"custom": {"level": 2}}
+        })
+    );
+    assert_eq!(from_value::<Config>(&value).unwrap(), config);
+
+    let text = to_string(&config).unwrap();
+    assert!(
+        text.contains(r#""name":"svc \"a\"\n""#),
+        "{text}",
+        text = text
+    );
+    assert_eq!(from_str::<Config>(&text).unwrap(), config);
+
+    for mode in &[Mode::Fast, Mode::Pinned(1), Mode::Range(1, 2)] {
+        assert_eq!(&from_value::<Mode>(&to_value(mode).unwrap()).unwrap(), mode);
+    }
+    assert_eq!(to_value(&Mode::Fast).unwrap(), json!("fast"));
+    assert_eq!(
+        to_value(&Mode::Range(1, 2)).unwrap(),
+        json!({"range": [1, 2]})
+    );
+}
+
+#[test]
+fn json_values() {
+    let doc = json!({"a": [1, 2.5, null, true, "s"], "b": {}});
+    assert_eq!(from_value::<Json>(&doc).unwrap(), doc);
+    assert_eq!(to_value(&doc).unwrap(), doc);
+    assert_eq!(
+        from_str::<Json>("[1, {\"x\": null}]").unwrap(),
+        json!([1, {"x": null}])
+    );
+
+    let mut map = HashMap::new();
+    map.insert(1, "one");
+    assert_eq!(to_value(&map).unwrap(), json!({"1": "one"}));
+    let map: BTreeMap<String, (i8, bool)> = from_str(r#"{"k": [-1, false]}"#).unwrap();
+    assert_eq!(map["k"], (-1, false));
+}
+
+#[test]
+fn error_locations() {
+    let text = r#"{"name": "a", "port": 70000, "mode": "fast"}"#;
+    let e = from_str::<Config>(text).unwrap_err();
+    assert_eq!(e.path, "/port");
+    assert_eq!(&text[e.span.clone().unwrap()], "70000");
+    assert!(e.message.contains("u16"), "{e}", e = e);
+
+    let text = r#"{"name": "a", "port": 1, "mode": "fast", "limits": {"ratio": "1", "depth": 1}}"#;
+    let e = from_str::<Config>(text).unwrap_err();
+    assert_eq!(e.path, "/limits/ratio");
+    assert_eq!(&text[e.span.unwrap()], "\"1\"");
+
+    let text = r#"{"name": "a", "port": 1, "mode": {"slow": 1}}"#;
+    let e = from_str::<Config>(text).unwrap_err();
+    assert_eq!(e.path, "/mode/slow");
+
+    let text = "{\"name\": \"a\",\n \"port\": 1}";
+    let e = from_str::<Config>(text).unwrap_err();
+    assert_eq!(e.path, "");
+    assert_eq!(e.span, Some(0..text.len()));
+    assert!(e.message.contains("missing field `mode`"), "{e}", e = e);
+
+    let e = from_str::<Vec<u8>>("[1, 2,]").unwrap_err();
+    assert!(e.message.starts_with("invalid JSON"), "{e}", e = e);
+    assert_eq!(e.span, Some(6..7));
+
+    // Spans are offsets into the input, byte order mark and all.
+    let e = from_str::<Vec<u8>>("[1, 300]").unwrap_err();
+    assert_eq!(e.span, Some(4..7));
+    let e = from_str::<Vec<u8>>("\u{feff}[1, 300]").unwrap_err();
+    assert_eq!((e.path.as_str(), e.span), ("/1", Some(7..10)));
+
+    let e = from_value::<(u8, u8)>(&json!([1, 2, 3])).unwrap_err();
+    assert_eq!((e.path.as_str(), e.span), ("", None));
+    let e = from_value::<Vec<u8>>(&json!([1, 2.5])).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "invalid type: floating point `2.5`, expected u8 at \"/1\""
+    );
+}
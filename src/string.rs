@@ -5,7 +5,7 @@ use super::*;
 #[test]
 fn basic() {
     assert_eq!(
-        try_get_string("\"foobar\"").unwrap().0 .0,
+        try_get_string("\"foobar\"", None).unwrap().0 .0,
         Token::StringLiteral("foobar".to_string())
     );
 }
@@ -13,7 +13,7 @@ fn basic() {
 #[test]
 fn spaces() {
     assert_eq!(
-        try_get_string("\"this is a string with spaces\"")
+        try_get_string("\"this is a string with spaces\"", None)
             .unwrap()
             .0
              .0,
@@ -25,7 +25,8 @@ fn spaces() {
 fn control_chars() {
     assert_eq!(
         try_get_string(
-            "\"i \\n have \\b every \\t control \\r character \\f type \\u1234 inside \\\\ me!\""
+            "\"i \\n have \\b every \\t control \\r character \\f type \\u1234 inside \\\\ me!\"",
+            None
         )
         .unwrap()
         .0
@@ -40,11 +41,49 @@ fn control_chars() {
 #[test]
 fn trailing_content() {
     assert_eq!(
-        try_get_string("\"foo\" bar baz ok there is more stuff here after the closing quote")
-            .unwrap(),
+        try_get_string(
+            "\"foo\" bar baz ok there is more stuff here after the closing quote",
+            None
+        )
+        .unwrap(),
         (
             (Token::StringLiteral("foo".to_string()), 5, 65),
             " bar baz ok there is more stuff here after the closing quote"
         )
     );
 }
+
+#[test]
+fn escaped_quotes() {
+    assert_eq!(
+        try_get_string(r#""say \"hi\" \/ bye""#, None).unwrap().0 .0,
+        Token::StringLiteral("say \"hi\" / bye".to_string())
+    );
+}
+
+#[test]
+fn surrogate_pairs() {
+    assert_eq!(
+        try_get_string(r#""a\ud83d\ude00b""#, None).unwrap().0,
+        (Token::StringLiteral("a\u{1f600}b".to_string()), 16, 16)
+    );
+    assert_eq!(
+        try_get_string(r#""\ud834\udd1e""#, None).unwrap().0 .0,
+        Token::StringLiteral("\u{1d11e}".to_string())
+    );
+    // Each error covers the digits of the surrogate without its other half.
+    for (s, rest) in &[
+        (r#""\ud83d""#, 5),
+        (r#""\ud83dx""#, 6),
+        (r#""\ud83d\n""#, 7),
+        (r#""\ud83d\ud83d""#, 11),
+        (r#""\ude00\ud83d""#, 11),
+        (r#""a\ud83d\u00e9""#, 11),
+    ] {
+        assert_eq!(
+            try_get_string(s, None).unwrap_err(),
+            (ErrorKind::InvalidUnicode, 4, *rest),
+            "{s}"
+        );
+    }
+}
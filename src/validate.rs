@@ -0,0 +1,205 @@
+//! Checking that bytes are one JSON document, without building it.
+//!
+//! ```
+//! assert!(jval::validate("{\"a\": [1, 2.5e3, \"é\"]}".as_bytes()).is_ok());
+//!
+//! let errors = jval::validate(b"[1, 2,]").unwrap_err();
+//! assert_eq!(errors[0].1, 5..6);
+//! ```
+
+use std::ops::Range;
+use std::str;
+
+use super::{next_token, read_string, ErrorKind, Errors, Limits};
+
+#[cfg(test)]
+mod tests;
+
+type Error = (ErrorKind, Range<usize>);
+
+/// Check that `input` is a single JSON document, accepting exactly what
+/// [`Json::from_slice`](crate::Json::from_slice) does.
+///
+/// This walks the input once, keeping only the offsets of the open arrays
+/// and objects, so it's much cheaper than parsing when the value itself
+/// isn't needed. It reads tokens the way the parser does, so a byte order
+/// mark, escapes the parser is lenient about and any Unicode whitespace are
+/// all accepted, but it stops at the first error.
+///
+/// # Errors
+///
+/// Fails with the first error found and the byte range it covers.
+pub fn validate(input: &[u8]) -> Result<(), Errors> {
+    let (text, utf8) = match str::from_utf8(input) {
+        Ok(text) => (text, None),
+        Err(e) => (
+            str::from_utf8(&input[..e.valid_up_to()]).unwrap_or_default(),
+            Some(e),
+        ),
+    };
+    let mut validator = Validator {
+        text,
+        pos: if text.starts_with('\u{feff}') { 3 } else { 0 },
+        open: Vec::new(),
+    };
+    let result = validator.run();
+
+    let Some(e) = utf8 else {
+        return result.map_err(|e| vec![e]);
+    };
+    // Like the parser, an error that runs up to where the text stops being
+    // UTF-8 may only be there because it stops.
+    let valid = e.valid_up_to();
+    match result {
+        Err(e) if e.1.end < valid => Err(vec![e]),
+        _ => {
+            let len = e.error_len().unwrap_or(input.len() - valid);
+            Err(vec![(ErrorKind::InvalidUtf8, valid..valid + len)])
+        }
+    }
+}
+
+/// What may come next.
+#[derive(Clone, Copy)]
+enum State {
+    /// A value, after a comma in an array, a colon, or at the start.
+    Value,
+    /// A value or `]`, just after `[`.
+    ArrayStart,
+    /// A member name or `}`, just after `{`.
+    ObjectStart,
+    /// A member name, after a comma in an object.
+    Key,
+    /// The colon after a member name.
+    Colon,
+    /// A comma or the end of the enclosing container, after a value, or
+    /// the end of the input at the top level.
+    Next,
+}
+
+struct Validator<'a> {
+    text: &'a str,
+    pos: usize,
+    /// The offsets of the `[` and `{` of the containers we're inside.
+    open: Vec<usize>,
+}
+
+impl Validator<'_> {
+    fn run(&mut self) -> Result<(), Error> {
+        let mut state = State::Value;
+        // Where the last comma was, for reporting a trailing one.
+        let mut comma = 0;
+
+        loop {
+            let rest = &self.text[self.pos..];
+            self.pos += rest.len() - rest.trim_start().len();
+            let Some(&byte) = self.text.as_bytes().get(self.pos) else {
+                return self.end(state);
+            };
+            let top = self.open.last().map(|&i| self.text.as_bytes()[i]);
+
+            state = match (state, byte) {
+                (State::Value | State::ArrayStart, b'[') => {
+                    self.open.push(self.pos);
+                    self.pos += 1;
+                    State::ArrayStart
+                }
+                (State::Value | State::ArrayStart, b'{') => {
+                    self.open.push(self.pos);
+                    self.pos += 1;
+                    State::ObjectStart
+                }
+                (State::Value | State::ArrayStart, b'"') => {
+                    self.string()?;
+                    State::Next
+                }
+                (State::ObjectStart | State::Key, b'"') => {
+                    self.string()?;
+                    State::Colon
+                }
+                (State::Colon, b':') => {
+                    self.pos += 1;
+                    State::Value
+                }
+                (State::Next, b',') if top.is_some() => {
+                    comma = self.pos;
+                    self.pos += 1;
+                    if top == Some(b'[') {
+                        State::Value
+                    } else {
+                        State::Key
+                    }
+                }
+                (State::ArrayStart | State::Next, b']') if top == Some(b'[') => {
+                    self.open.pop();
+                    self.pos += 1;
+                    State::Next
+                }
+                (State::ObjectStart | State::Next, b'}') if top == Some(b'{') => {
+                    self.open.pop();
+                    self.pos += 1;
+                    State::Next
+                }
+                // Only reachable after a comma: a value in an array follows
+                // `[` or a comma, and one in an object follows a colon.
+                (State::Value, b']') if top == Some(b'[') => {
+                    return Err((ErrorKind::TrailingComma, comma..comma + 1))
+                }
+                (State::Key, b'}') => return Err((ErrorKind::TrailingComma, comma..comma + 1)),
+                (State::Value | State::ArrayStart, _) if !b"{}[],:".contains(&byte) => {
+                    self.scalar()?;
+                    State::Next
+                }
+                _ => return Err(self.unexpected()),
+            };
+        }
+    }
+
+    /// Check that the input can end in `state`.
+    fn end(&self, state: State) -> Result<(), Error> {
+        match (state, self.open.last()) {
+            (State::Next, None) => Ok(()),
+            (_, None) => Err((ErrorKind::UnexpectedToken, self.pos..self.pos)),
+            (_, Some(&open)) if self.text.as_bytes()[open] == b'[' => {
+                Err((ErrorKind::UnterminatedArray, open..open + 1))
+            }
+            (_, Some(&open)) => Err((ErrorKind::UnterminatedObject, open..open + 1)),
+        }
+    }
+
+    /// The error for a character that can't appear at `pos`.
+    fn unexpected(&self) -> Error {
+        let len = self.text[self.pos..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        (ErrorKind::UnexpectedToken, self.pos..self.pos + len)
+    }
+
+    /// Skip the string starting at `pos`.
+    fn string(&mut self) -> Result<(), Error> {
+        let len =
+            read_string(&self.text[self.pos..], drop).map_err(|e| tokenizer_error(e, self.text))?;
+        self.pos += len;
+        Ok(())
+    }
+
+    /// Skip the number or literal starting at `pos`. Only strings make the
+    /// tokenizer allocate, so it's used as is for these.
+    fn scalar(&mut self) -> Result<(), Error> {
+        match next_token(&self.text[self.pos..], &Limits::default()) {
+            Ok((_, rest)) => {
+                self.pos = self.text.len() - rest.len();
+                Ok(())
+            }
+            Err(e) => Err(tokenizer_error(e, self.text)),
+        }
+    }
+}
+
+/// The error the tokenizer gives as its kind, length and how much of `text`
+/// is left from where it starts, with its span.
+fn tokenizer_error((kind, len, rest): super::Error, text: &str) -> Error {
+    let start = text.len() - rest;
+    (kind, start..start + len)
+}
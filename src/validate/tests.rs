@@ -0,0 +1,226 @@
+#![cfg(test)]
+
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+use super::*;
+use crate::Json;
+
+fn error(input: &[u8]) -> (ErrorKind, Range<usize>) {
+    let mut errors = validate(input).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    errors.remove(0)
+}
+
+/// A pseudo-random number generator, for generating inputs.
+fn rng() -> impl FnMut() -> usize {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    move || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        // The high bits are the most random.
+        usize::try_from(seed >> 33).unwrap()
+    }
+}
+
+/// Pieces of documents, including the parser's quirks.
+const PIECES: &[&str] = &[
+    "\"", "\\", "{", "}", "[", "]", ",", ":", " ", "\t", "\n", "\u{a0}", "\u{2028}", "\u{feff}",
+    "\u{e9}", "0", "1", "-", ".", "e", "+", "-0.5e3", "1.e5", "01", "a", "g", "true", "null",
+    "false", "nul", "\\u", "\\ud83d", "\\ude00", "\\u00e9", "\\n", "\\x", "\"a\"", "\"\\x\"",
+];
+
+/// A document, usually valid, spaced with `space` pieces.
+fn document(next: &mut impl FnMut() -> usize, depth: usize) -> String {
+    const SCALARS: &[&str] = &[
+        "null",
+        "true",
+        "false",
+        "0",
+        "-1",
+        "2.5e-3",
+        "1.e5",
+        "1E+2",
+        "\"\"",
+        "\"a\\tb\"",
+        "\"a\tb\"",
+        "\"\\u00e9\"",
+        "\"\\ud83d\\ude00\"",
+        "\"\\x\\\"\"",
+        "\"\\u12g4\"",
+        "\"\u{e9}\"",
+    ];
+    const SPACE: &[&str] = &["", "", " ", "\n", "\t", "\u{a0}", "\u{3000}"];
+    let space = |next: &mut dyn FnMut() -> usize| SPACE[next() % SPACE.len()];
+
+    let mut out = space(next).to_string();
+    match next() % 4 {
+        0 if depth < 4 => {
+            out.push('[');
+            for i in 0..next() % 4 {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&document(next, depth + 1));
+            }
+            out.push_str(space(next));
+            out.push(']');
+        }
+        1 if depth < 4 => {
+            out.push('{');
+            for i in 0..next() % 4 {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(space(next));
+                write!(out, "\"k{i}\"").unwrap();
+                out.push_str(space(next));
+                out.push(':');
+                out.push_str(&document(next, depth + 1));
+            }
+            out.push_str(space(next));
+            out.push('}');
+        }
+        _ => out.push_str(SCALARS[next() % SCALARS.len()]),
+    }
+    out.push_str(space(next));
+    out
+}
+
+/// Generated inputs: documents, some of them broken by inserting or
+/// removing a byte or a piece, and random strings of pieces.
+fn samples() -> Vec<Vec<u8>> {
+    let mut next = rng();
+    let mut out = Vec::new();
+    for _ in 0..3000 {
+        let mut input = document(&mut next, 0).into_bytes();
+        if next().is_multiple_of(8) {
+            input.splice(0..0, "\u{feff}".bytes());
+        }
+        let at = next() % (input.len() + 1);
+        match next() % 4 {
+            0 => drop(input.splice(at..at, PIECES[next() % PIECES.len()].bytes())),
+            1 if at < input.len() => drop(input.remove(at)),
+            _ => {}
+        }
+        out.push(input);
+
+        let soup: String = (0..next() % 12)
+            .map(|_| PIECES[next() % PIECES.len()])
+            .collect();
+        out.push(soup.into_bytes());
+    }
+    out
+}
+
+#[test]
+fn agrees_with_the_parser() {
+    let mut valid = 0;
+    for input in samples() {
+        let expected = Json::from_slice(&input).is_ok();
+        assert_eq!(
+            validate(&input).is_ok(),
+            expected,
+            "{:?}",
+            String::from_utf8_lossy(&input)
+        );
+        valid += usize::from(expected);
+    }
+    // Enough of both to mean something.
+    assert!(valid > 1000 && valid < 5000, "{}", valid);
+}
+
+#[test]
+fn parser_quirks() {
+    // Each is accepted or rejected as the parser does, not as RFC 8259
+    // says.
+    let documents: [&[u8]; 8] = [
+        b"\xef\xbb\xbf[1]",
+        b"\"a\tb\"",
+        "[\u{a0}1]".as_bytes(),
+        b"[1.e5]",
+        b"\"\\x\\\"\"",
+        b"\"\\u12g4\"",
+        b"\"\\ud83d\\ude00\"",
+        b"[\xef\xbb\xbf1]",
+    ];
+    for input in &documents {
+        assert_eq!(
+            validate(input).is_ok(),
+            Json::from_slice(input).is_ok(),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn errors() {
+    assert_eq!(error(b""), (ErrorKind::UnexpectedToken, 0..0));
+    assert_eq!(error(b"  "), (ErrorKind::UnexpectedToken, 2..2));
+    assert_eq!(error(b"1 2"), (ErrorKind::UnexpectedToken, 2..3));
+    assert_eq!(error(b"[1, 2"), (ErrorKind::UnterminatedArray, 0..1));
+    assert_eq!(
+        error(b"[{\"a\": [], "),
+        (ErrorKind::UnterminatedObject, 1..2)
+    );
+    assert_eq!(error(b"[1,]"), (ErrorKind::TrailingComma, 2..3));
+    assert_eq!(error(b"{\"a\": 1 ,}"), (ErrorKind::TrailingComma, 8..9));
+    assert_eq!(error(b"[1 2]"), (ErrorKind::UnexpectedToken, 3..4));
+    assert_eq!(error(b"[1}"), (ErrorKind::UnexpectedToken, 2..3));
+    assert_eq!(error(b"{\"a\" 1}"), (ErrorKind::UnexpectedToken, 5..6));
+    assert_eq!(error(b"{1: 2}"), (ErrorKind::UnexpectedToken, 1..2));
+    assert_eq!(error(b"[nulll]"), (ErrorKind::UnexpectedToken, 5..6));
+
+    // Errors in tokens are the parser's.
+    let tokens: [&[u8]; 8] = [
+        b"[-01.5]",
+        b"[1e+]",
+        b"[1.]",
+        b"\"ab",
+        b"\"ab\\",
+        b"[\"\\ud800\"]",
+        b"\"\\ude00\\ud83d\"",
+        b"\"\\ud83d\\n\"",
+    ];
+    for input in &tokens {
+        let expected = Json::from_slice(input).unwrap_err().remove(0);
+        assert_eq!(error(input), expected, "{input:?}");
+    }
+    assert_eq!(error(b"[-01.5]"), (ErrorKind::InvalidNumber, 3..4));
+    assert_eq!(error(b"\"ab"), (ErrorKind::UnterminatedString, 0..3));
+}
+
+#[test]
+fn utf8() {
+    assert!(
+        validate("\"\u{7f}\u{80}\u{7ff}\u{800}\u{ffff}\u{10000}\u{10ffff}\"".as_bytes()).is_ok()
+    );
+    let invalid: [&[u8]; 7] = [
+        b"\"\x80\"",
+        b"\"\xc0\xaf\"",
+        b"\"\xe0\x80\xaf\"",
+        b"\"\xed\xa0\x80\"",
+        b"\"\xf4\x90\x80\x80\"",
+        b"\"\xf5\x80\x80\x80\"",
+        b"[\xff]",
+    ];
+    for input in &invalid {
+        assert_eq!(error(input), (ErrorKind::InvalidUtf8, 1..2), "{input:?}");
+    }
+    assert_eq!(error(b"\"\xe2\x82\""), (ErrorKind::InvalidUtf8, 1..3));
+    // An error before it comes first, but not one it might have caused.
+    assert_eq!(error(b"[1.,\xff]"), (ErrorKind::InvalidNumber, 2..3));
+    assert_eq!(error(b"\"a\xff"), (ErrorKind::InvalidUtf8, 2..3));
+}
+
+#[test]
+fn deep_nesting() {
+    let depth = 100_000;
+    let mut doc = "[{\"a\":".repeat(depth);
+    doc.push_str("null");
+    doc.push_str(&"}]".repeat(depth));
+    assert!(validate(doc.as_bytes()).is_ok());
+    doc.pop();
+    assert_eq!(error(doc.as_bytes()), (ErrorKind::UnterminatedArray, 0..1));
+}
@@ -0,0 +1,350 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use std::ops;
+
+use super::Json;
+
+#[cfg(test)]
+mod tests;
+
+static NULL: Json = Json::Null;
+
+/// A type that can be used to look up a member of a `Json` value: `str` and
+/// `String` address object members, `usize` addresses array elements.
+pub trait JsonIndex: private::Sealed {
+    #[doc(hidden)]
+    fn index_into<'a>(&self, v: &'a Json) -> Option<&'a Json>;
+    #[doc(hidden)]
+    fn index_into_mut<'a>(&self, v: &'a mut Json) -> Option<&'a mut Json>;
+}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for usize {}
+    impl Sealed for str {}
+    impl Sealed for String {}
+    impl<T: Sealed + ?Sized> Sealed for &T {}
+}
+
+impl JsonIndex for usize {
+    fn index_into<'a>(&self, v: &'a Json) -> Option<&'a Json> {
+        match v {
+            Json::Array(a) => a.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'a>(&self, v: &'a mut Json) -> Option<&'a mut Json> {
+        match v {
+            Json::Array(a) => a.get_mut(*self),
+            _ => None,
+        }
+    }
+}
+
+impl JsonIndex for str {
+    fn index_into<'a>(&self, v: &'a Json) -> Option<&'a Json> {
+        match v {
+            Json::Object(o) => o.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'a>(&self, v: &'a mut Json) -> Option<&'a mut Json> {
+        match v {
+            Json::Object(o) => o.get_mut(self),
+            _ => None,
+        }
+    }
+}
+
+impl JsonIndex for String {
+    fn index_into<'a>(&self, v: &'a Json) -> Option<&'a Json> {
+        self.as_str().index_into(v)
+    }
+
+    fn index_into_mut<'a>(&self, v: &'a mut Json) -> Option<&'a mut Json> {
+        self.as_str().index_into_mut(v)
+    }
+}
+
+impl<T: JsonIndex + ?Sized> JsonIndex for &T {
+    fn index_into<'a>(&self, v: &'a Json) -> Option<&'a Json> {
+        (**self).index_into(v)
+    }
+
+    fn index_into_mut<'a>(&self, v: &'a mut Json) -> Option<&'a mut Json> {
+        (**self).index_into_mut(v)
+    }
+}
+
+impl<I: JsonIndex> ops::Index<I> for Json {
+    type Output = Self;
+
+    fn index(&self, index: I) -> &Self {
+        index.index_into(self).unwrap_or(&NULL)
+    }
+}
+
+impl Json {
+    /// Look up an object member or array element, if it exists.
+    pub fn get<I: JsonIndex>(&self, index: I) -> Option<&Self> {
+        index.index_into(self)
+    }
+
+    pub fn get_mut<I: JsonIndex>(&mut self, index: I) -> Option<&mut Self> {
+        index.index_into_mut(self)
+    }
+
+    /// Follow a sequence of keys from this value. Each segment names an
+    /// object member, or an array element if it is a decimal index.
+    pub fn get_path<'a, P>(&self, path: P) -> Option<&Self>
+    where
+        P: IntoIterator<Item = &'a str>,
+    {
+        path.into_iter().try_fold(self, |v, seg| match v {
+            Json::Array(a) => seg.parse::<usize>().ok().and_then(|i| a.get(i)),
+            _ => v.get(seg),
+        })
+    }
+
+    pub fn get_path_mut<'a, P>(&mut self, path: P) -> Option<&mut Self>
+    where
+        P: IntoIterator<Item = &'a str>,
+    {
+        path.into_iter().try_fold(self, |v, seg| match v {
+            Json::Array(a) => seg.parse::<usize>().ok().and_then(move |i| a.get_mut(i)),
+            _ => v.get_mut(seg),
+        })
+    }
+
+    /// Replace this value with `null`, returning what was there.
+    #[must_use]
+    pub fn take(&mut self) -> Self {
+        mem::replace(self, Json::Null)
+    }
+
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Json::Null)
+    }
+
+    #[must_use]
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, Json::Boolean(_))
+    }
+
+    #[must_use]
+    pub fn is_number(&self) -> bool {
+        matches!(self, Json::Number(_))
+    }
+
+    #[must_use]
+    pub fn is_string(&self) -> bool {
+        matches!(self, Json::String(_))
+    }
+
+    #[must_use]
+    pub fn is_array(&self) -> bool {
+        matches!(self, Json::Array(_))
+    }
+
+    #[must_use]
+    pub fn is_object(&self) -> bool {
+        matches!(self, Json::Object(_))
+    }
+
+    #[must_use]
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Json::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// The number as an `i64`, if it is integral and within range.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Json::Number(n) if n.fract() == 0. && *n >= i64::MIN as f64 && *n < i64::MAX as f64 => {
+                Some(*n as i64)
+            }
+            _ => None,
+        }
+    }
+
+    /// The number as a `u64`, if it is integral, non-negative and within range.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Json::Number(n) if n.fract() == 0. && *n >= 0. && *n < u64::MAX as f64 => {
+                Some(*n as u64)
+            }
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_str_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_array(&self) -> Option<&Vec<Self>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Self>> {
+        match self {
+            Json::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Self>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut BTreeMap<String, Self>> {
+        match self {
+            Json::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Boolean(b)
+    }
+}
+
+macro_rules! from_lossless {
+    ($($t:ty)*) => {
+        $(
+            impl From<$t> for Json {
+                fn from(n: $t) -> Self {
+                    Json::Number(f64::from(n))
+                }
+            }
+        )*
+    };
+}
+
+from_lossless!(i8 i16 i32 u8 u16 u32 f32 f64);
+
+macro_rules! from_lossy {
+    ($($t:ty)*) => {
+        $(
+            impl From<$t> for Json {
+                #[allow(clippy::cast_precision_loss)]
+                fn from(n: $t) -> Self {
+                    Json::Number(n as f64)
+                }
+            }
+        )*
+    };
+}
+
+from_lossy!(i64 u64 isize usize);
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+impl From<char> for Json {
+    fn from(c: char) -> Self {
+        Json::String(c.to_string())
+    }
+}
+
+impl From<()> for Json {
+    fn from((): ()) -> Self {
+        Json::Null
+    }
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+    fn from(o: Option<T>) -> Self {
+        o.map_or(Json::Null, Into::into)
+    }
+}
+
+impl<T: Into<Json>> From<Vec<T>> for Json {
+    fn from(v: Vec<T>) -> Self {
+        Json::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Clone + Into<Json>> From<&[T]> for Json {
+    fn from(v: &[T]) -> Self {
+        Json::Array(v.iter().cloned().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<Json>, S> From<HashMap<K, V, S>> for Json {
+    fn from(m: HashMap<K, V, S>) -> Self {
+        m.into_iter().collect()
+    }
+}
+
+impl<K: Into<String>, V: Into<Json>> From<BTreeMap<K, V>> for Json {
+    fn from(m: BTreeMap<K, V>) -> Self {
+        m.into_iter().collect()
+    }
+}
+
+impl<T: Into<Json>> std::iter::FromIterator<T> for Json {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Json::Array(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<K: Into<String>, V: Into<Json>> std::iter::FromIterator<(K, V)> for Json {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Json::Object(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,113 @@
+#![cfg(test)]
+
+use std::collections::HashMap;
+
+use super::*;
+
+fn sample() -> Json {
+    r#"{"name": "jval", "tags": ["json", "rust"], "stars": 3, "meta": {"ok": true}}"#
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn index() {
+    let j = sample();
+    assert_eq!(j["name"], Json::String("jval".into()));
+    assert_eq!(j["tags"][1], Json::String("rust".into()));
+    assert_eq!(j["meta"]["ok"], Json::Boolean(true));
+    assert_eq!(j[String::from("stars")], Json::Number(3.));
+}
+
+#[test]
+fn index_miss() {
+    let j = sample();
+    assert_eq!(j["nope"], Json::Null);
+    assert_eq!(j["tags"][9], Json::Null);
+    assert_eq!(j["name"]["deeper"][0], Json::Null);
+    assert_eq!(j[0], Json::Null);
+}
+
+#[test]
+fn getters() {
+    let mut j = sample();
+    assert_eq!(j["name"].as_str(), Some("jval"));
+    assert_eq!(j["stars"].as_f64(), Some(3.));
+    assert_eq!(j["stars"].as_i64(), Some(3));
+    assert_eq!(j["stars"].as_u64(), Some(3));
+    assert_eq!(j["meta"]["ok"].as_bool(), Some(true));
+    assert_eq!(j["tags"].as_array().map(Vec::len), Some(2));
+    assert!(j["meta"].as_object().unwrap().contains_key("ok"));
+    assert_eq!(j["name"].as_f64(), None);
+    assert_eq!(Json::Number(1.5).as_i64(), None);
+    assert_eq!(Json::Number(-1.).as_u64(), None);
+
+    j.get_mut("tags")
+        .and_then(Json::as_array_mut)
+        .unwrap()
+        .push("cli".into());
+    assert_eq!(j["tags"][2].as_str(), Some("cli"));
+}
+
+#[test]
+fn predicates() {
+    let j = sample();
+    assert!(j["nope"].is_null());
+    assert!(j["meta"]["ok"].is_boolean());
+    assert!(j["stars"].is_number());
+    assert!(j["name"].is_string());
+    assert!(j["tags"].is_array());
+    assert!(j.is_object());
+    assert!(!j.is_array());
+}
+
+#[test]
+fn paths() {
+    let mut j = sample();
+    assert_eq!(
+        j.get_path(vec!["tags", "0"]).and_then(Json::as_str),
+        Some("json")
+    );
+    assert_eq!(j.get_path(vec!["meta", "ok"]), Some(&Json::Boolean(true)));
+    assert_eq!(j.get_path(vec!["tags", "x"]), None);
+    assert_eq!(j.get_path(Vec::new()), Some(&j));
+
+    *j.get_path_mut(vec!["meta", "ok"]).unwrap() = false.into();
+    assert_eq!(j["meta"]["ok"], Json::Boolean(false));
+}
+
+#[test]
+fn take() {
+    let mut j = sample();
+    let tags = j.get_mut("tags").unwrap().take();
+    assert_eq!(tags.as_array().map(Vec::len), Some(2));
+    assert!(j["tags"].is_null());
+}
+
+#[test]
+fn conversions() {
+    assert_eq!(Json::from(true), Json::Boolean(true));
+    assert_eq!(Json::from(7_u8), Json::Number(7.));
+    assert_eq!(Json::from(-2_i64), Json::Number(-2.));
+    assert_eq!(Json::from("hi"), Json::String("hi".into()));
+    assert_eq!(Json::from(None::<bool>), Json::Null);
+    assert_eq!(Json::from(Some(1.5)), Json::Number(1.5));
+    assert_eq!(
+        Json::from(vec![1, 2]),
+        Json::Array(vec![Json::Number(1.), Json::Number(2.)])
+    );
+
+    let mut m = HashMap::new();
+    m.insert("a", vec![Some("x"), None]);
+    assert_eq!(
+        Json::from(m),
+        Json::Object(
+            vec![(
+                "a".to_string(),
+                Json::Array(vec![Json::String("x".into()), Json::Null])
+            )]
+            .into_iter()
+            .collect()
+        )
+    );
+}
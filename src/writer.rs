@@ -0,0 +1,254 @@
+//! Writing a JSON document piece by piece, without building a [`Json`].
+//!
+//! ```
+//! use jval::{json, FormatOptions, Writer};
+//!
+//! let mut w = Writer::new(Vec::new(), FormatOptions::compact());
+//! w.begin_object()?;
+//! w.key("rows")?;
+//! w.begin_array()?;
+//! for i in 0..3 {
+//!     w.value(&json!({"id": i}))?;
+//! }
+//! w.end_array()?;
+//! w.end_object()?;
+//! let out = w.finish()?;
+//! assert_eq!(out, br#"{"rows":[{"id":0},{"id":1},{"id":2}]}"#);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fmt;
+use std::io;
+
+use super::color::Kind;
+use super::format::{check_finite, IoWrite, Printer};
+use super::{FormatOptions, Json};
+
+#[cfg(test)]
+mod tests;
+
+/// A container the writer is inside of.
+#[derive(Debug)]
+enum Frame {
+    Array {
+        len: usize,
+    },
+    Object {
+        len: usize,
+        /// Whether a key has been written that still needs its value.
+        keyed: bool,
+    },
+}
+
+/// Writes one JSON document to `W` as a sequence of calls, laid out
+/// according to [`FormatOptions`].
+///
+/// Every call checks that it makes sense where it's made, failing with
+/// [`io::ErrorKind::InvalidInput`] otherwise: keys may only be written
+/// directly inside objects, each key needs exactly one value, ends must
+/// match their beginnings, and there's only one document. Members are
+/// written in the order they're given, whatever the
+/// [`KeyOrder`](crate::KeyOrder), and `max_width`, `table_align` and
+/// `inline_single_element` only apply within values passed to
+/// [`Writer::value`].
+pub struct Writer<W: io::Write> {
+    inner: W,
+    options: FormatOptions,
+    stack: Vec<Frame>,
+    /// Whether the whole document has been written.
+    done: bool,
+    column: usize,
+}
+
+impl<W: io::Write> Writer<W> {
+    pub fn new(inner: W, options: FormatOptions) -> Self {
+        Writer {
+            inner,
+            options,
+            stack: Vec::new(),
+            done: false,
+            column: 0,
+        }
+    }
+
+    /// Write the name of the next member of the current object.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails, or if the writer isn't directly inside an
+    /// object or the last key has no value yet.
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        let (i, depth) = match self.stack.last_mut() {
+            Some(Frame::Object { keyed: true, .. }) => {
+                return Err(invalid("key written before the last key's value"))
+            }
+            Some(Frame::Object { len, keyed }) => {
+                *keyed = true;
+                *len += 1;
+                (*len - 1, self.stack.len())
+            }
+            _ => return Err(invalid("key written outside of an object")),
+        };
+        self.print(|p| {
+            p.separator(i, depth, Kind::Object)?;
+            p.key(key)
+        })
+    }
+
+    /// Write a whole value.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails, if a value can't go here, or if `value` has
+    /// a `NaN` or infinite number that the options don't allow.
+    pub fn value(&mut self, value: &Json) -> io::Result<()> {
+        check_finite(value, &self.options)?;
+        self.start_value()?;
+        let depth = self.stack.len();
+        self.print(|p| p.value(value, depth))?;
+        self.end_value();
+        Ok(())
+    }
+
+    /// Start an array, whose elements are the values written up to the
+    /// matching [`Writer::end_array`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails or a value can't go here.
+    pub fn begin_array(&mut self) -> io::Result<()> {
+        self.start_value()?;
+        self.print(|p| p.punct(Kind::Array, "["))?;
+        self.stack.push(Frame::Array { len: 0 });
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Fails if writing fails or the writer isn't directly inside an array.
+    pub fn end_array(&mut self) -> io::Result<()> {
+        let len = match self.stack.last() {
+            Some(Frame::Array { len }) => *len,
+            _ => return Err(invalid("end_array called outside of an array")),
+        };
+        self.stack.pop();
+        let depth = self.stack.len();
+        self.print(|p| {
+            if len > 0 {
+                p.close(depth)?;
+            }
+            p.punct(Kind::Array, "]")
+        })?;
+        self.end_value();
+        Ok(())
+    }
+
+    /// Start an object, whose members are the keys and values written up
+    /// to the matching [`Writer::end_object`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails or a value can't go here.
+    pub fn begin_object(&mut self) -> io::Result<()> {
+        self.start_value()?;
+        self.print(|p| p.punct(Kind::Object, "{"))?;
+        self.stack.push(Frame::Object {
+            len: 0,
+            keyed: false,
+        });
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Fails if writing fails, if the writer isn't directly inside an
+    /// object, or if the last key has no value.
+    pub fn end_object(&mut self) -> io::Result<()> {
+        let len = match self.stack.last() {
+            Some(Frame::Object { keyed: true, .. }) => {
+                return Err(invalid("end_object called before the last key's value"))
+            }
+            Some(Frame::Object { len, .. }) => *len,
+            _ => return Err(invalid("end_object called outside of an object")),
+        };
+        self.stack.pop();
+        let depth = self.stack.len();
+        self.print(|p| {
+            if len > 0 {
+                p.close(depth)?;
+            }
+            p.punct(Kind::Object, "}")
+        })?;
+        self.end_value();
+        Ok(())
+    }
+
+    /// Finish the document, writing the trailing newline if the options
+    /// ask for one, and flush and return the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Fails if writing fails or the document isn't complete.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.done {
+            return Err(invalid("document is incomplete"));
+        }
+        if self.options.trailing_newline {
+            let newline = self.options.newline.as_str();
+            self.inner.write_all(newline.as_bytes())?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Check that a value can be written here, and write what comes before
+    /// it.
+    fn start_value(&mut self) -> io::Result<()> {
+        let depth = self.stack.len();
+        match self.stack.last_mut() {
+            None if self.done => Err(invalid("document is already complete")),
+            None | Some(Frame::Object { keyed: true, .. }) => Ok(()),
+            Some(Frame::Object { .. }) => Err(invalid("value written in an object without a key")),
+            Some(Frame::Array { len }) => {
+                let i = *len;
+                *len += 1;
+                self.print(|p| p.separator(i, depth, Kind::Array))
+            }
+        }
+    }
+
+    /// Record that a value was written.
+    fn end_value(&mut self) {
+        match self.stack.last_mut() {
+            None => self.done = true,
+            Some(Frame::Object { keyed, .. }) => *keyed = false,
+            Some(Frame::Array { .. }) => {}
+        }
+    }
+
+    /// Run `print` on a printer writing to the underlying writer.
+    fn print(
+        &mut self,
+        print: impl FnOnce(&mut Printer<IoWrite<W>>) -> fmt::Result,
+    ) -> io::Result<()> {
+        let mut out = IoWrite {
+            inner: &mut self.inner,
+            error: None,
+        };
+        let mut printer = Printer::new(&mut out, &self.options);
+        printer.column = self.column;
+        // Whether a comma will follow isn't known yet, so leave room for one.
+        printer.suffix = usize::from(!self.stack.is_empty());
+        let result = print(&mut printer);
+        self.column = printer.column;
+        match (result, out.error) {
+            (Ok(()), _) => Ok(()),
+            (Err(_), Some(e)) => Err(e),
+            (Err(_), None) => Err(io::Error::other("formatting failed")),
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
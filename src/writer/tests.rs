@@ -0,0 +1,166 @@
+#![cfg(test)]
+
+use super::*;
+use crate::NonFinite;
+
+fn written(
+    options: FormatOptions,
+    write: impl FnOnce(&mut Writer<Vec<u8>>) -> io::Result<()>,
+) -> String {
+    let mut w = Writer::new(Vec::new(), options);
+    write(&mut w).unwrap();
+    String::from_utf8(w.finish().unwrap()).unwrap()
+}
+
+fn document(w: &mut Writer<Vec<u8>>) -> io::Result<()> {
+    w.begin_object()?;
+    w.key("b")?;
+    w.begin_array()?;
+    w.value(&json!(1))?;
+    w.begin_object()?;
+    w.end_object()?;
+    w.value(&json!({"c": [true]}))?;
+    w.end_array()?;
+    w.key("a")?;
+    w.begin_array()?;
+    w.end_array()?;
+    w.end_object()
+}
+
+#[test]
+fn matches_print() {
+    let doc = json!({"b": [1, {}, {"c": [true]}], "a": []});
+    for options in &[
+        FormatOptions::compact(),
+        FormatOptions::tabs(),
+        FormatOptions::spaces(2).space_after_colon(false),
+        FormatOptions::compact().space_after_comma(true),
+    ] {
+        let streamed = written(options.clone(), document);
+        // Members are written in call order, so only the keys' order differs.
+        let printed = doc.to_string_with(options).unwrap();
+        assert_eq!(streamed.len(), printed.len(), "{streamed}");
+        assert_eq!(streamed.parse::<Json>().unwrap(), doc);
+    }
+
+    assert_eq!(
+        written(FormatOptions::spaces(2).trailing_newline(true), document),
+        "{\n  \"b\": [\n    1,\n    {},\n    {\n      \"c\": [ true ]\n    }\n  ],\n  \"a\": []\n}\n"
+    );
+    assert_eq!(
+        written(FormatOptions::spaces(2).max_width(16), |w| {
+            w.begin_object()?;
+            w.key("key")?;
+            w.value(&json!([1, 2]))?;
+            w.key("long")?;
+            w.value(&json!([1, 2]))?;
+            w.end_object()
+        }),
+        "{\n  \"key\": [1, 2],\n  \"long\": [\n    1,\n    2\n  ]\n}"
+    );
+    assert_eq!(
+        written(FormatOptions::tabs(), |w| w.value(&json!("s"))),
+        "\"s\""
+    );
+}
+
+#[test]
+fn call_order() {
+    let fails = |write: fn(&mut Writer<Vec<u8>>) -> io::Result<()>, message: &str| {
+        let mut w = Writer::new(Vec::new(), FormatOptions::compact());
+        let e = write(&mut w).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(e.to_string(), message);
+    };
+
+    fails(|w| w.key("a"), "key written outside of an object");
+    fails(
+        |w| {
+            w.begin_array()?;
+            w.key("a")
+        },
+        "key written outside of an object",
+    );
+    fails(
+        |w| {
+            w.begin_object()?;
+            w.value(&json!(1))
+        },
+        "value written in an object without a key",
+    );
+    fails(
+        |w| {
+            w.begin_object()?;
+            w.key("a")?;
+            w.key("b")
+        },
+        "key written before the last key's value",
+    );
+    fails(
+        |w| {
+            w.begin_object()?;
+            w.key("a")?;
+            w.end_object()
+        },
+        "end_object called before the last key's value",
+    );
+    fails(
+        |w| {
+            w.begin_object()?;
+            w.end_array()
+        },
+        "end_array called outside of an array",
+    );
+    fails(Writer::end_object, "end_object called outside of an object");
+    fails(
+        |w| {
+            w.value(&json!(1))?;
+            w.begin_array()
+        },
+        "document is already complete",
+    );
+
+    let mut w = Writer::new(Vec::new(), FormatOptions::compact());
+    w.begin_array().unwrap();
+    assert_eq!(
+        w.finish().unwrap_err().to_string(),
+        "document is incomplete"
+    );
+    let w = Writer::new(Vec::new(), FormatOptions::compact());
+    assert!(w.finish().is_err());
+}
+
+#[test]
+fn non_finite() {
+    let options = FormatOptions::compact().non_finite(NonFinite::Error);
+    let mut w = Writer::new(Vec::new(), options);
+    w.begin_array().unwrap();
+    let e = w.value(&Json::Number(f64::NAN)).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    w.value(&json!(1)).unwrap();
+    w.end_array().unwrap();
+    assert_eq!(w.finish().unwrap(), b"[1]");
+
+    let nan = written(FormatOptions::compact(), |w| {
+        w.value(&Json::Number(f64::NAN))
+    });
+    assert_eq!(nan, "null");
+}
+
+#[test]
+fn io_errors() {
+    struct Full;
+
+    impl io::Write for Full {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut w = Writer::new(Full, FormatOptions::compact());
+    assert_eq!(w.begin_array().unwrap_err().to_string(), "disk full");
+}